@@ -14,19 +14,22 @@
 // limitations under the License.
 //
 
+use super::audio_output::AudioOutput;
 use super::mixing::{MixerCommand, MixerResult};
-use knodiq_engine::{AudioPlayer, AudioSource};
-use std::sync::mpsc;
+use super::transport::Transport;
+use std::sync::{Arc, atomic::AtomicBool, mpsc};
 
 pub struct AppState {
     /// Mixer mspc sender to communicate with the mixer.
     pub mixer_command_sender: Option<mpsc::Sender<MixerCommand>>,
     /// Mixer mspc receiver to receive results from the mixer.
     pub mixer_result_receiver: Option<mpsc::Receiver<MixerResult>>,
-    /// Audio player to handle audio playback.
-    pub audio_player: Option<AudioPlayer>,
-    /// Cached mixed buffers to avoid unnecessary mixing.
-    pub mixer_result_cache: Option<AudioSource>,
+    /// Output stream of the current playback.
+    pub audio_output: Option<AudioOutput>,
+    /// Playhead of the current playback.
+    pub transport: Option<Arc<Transport>>,
+    /// Flag to cancel the running export.
+    pub export_should_stop: Arc<AtomicBool>,
 }
 
 impl AppState {
@@ -34,24 +37,18 @@ impl AppState {
         AppState {
             mixer_command_sender: None,
             mixer_result_receiver: None,
-            audio_player: None,
-            mixer_result_cache: None,
+            audio_output: None,
+            transport: None,
+            export_should_stop: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    pub fn set_audio_player(&mut self, player: AudioPlayer) {
-        self.audio_player = Some(player);
+    pub fn set_audio_output(&mut self, output: AudioOutput) {
+        self.audio_output = Some(output);
     }
 
-    pub fn get_audio_player(&mut self) -> Option<&mut AudioPlayer> {
-        self.audio_player.as_mut()
+    /// Stop the output stream of the current playback.
+    pub fn clear_audio_output(&mut self) {
+        self.audio_output = None;
     }
-
-    pub fn clear_audio_player(&mut self) {
-        self.audio_player = None;
-    }
-
-    // pub fn set_mixer_result_cache(&mut self, cache: AudioSource) {
-    //     self.mixer_result_cache = Some(cache);
-    // }
 }
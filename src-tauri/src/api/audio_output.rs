@@ -0,0 +1,199 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::transport::Transport;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{BufferSize, OutputCallbackInfo, SampleRate, Stream, StreamConfig};
+use knodiq_engine::Sample;
+use std::sync::{
+    Arc,
+    mpsc::{self, Receiver, Sender, TryRecvError},
+};
+use std::thread;
+
+/// Output stream of a single playback, on the default output device.
+///
+/// The stream is kept on its own thread, as it can't be moved between threads on every
+/// platform. The thread ends and the stream stops when the output is dropped.
+pub struct AudioOutput {
+    _stop_sender: Sender<()>,
+}
+
+impl AudioOutput {
+    /// Start playing the samples sent to the returned sender.
+    /// The frames played are reported to the transport, which is finished once the sender
+    /// is dropped and every sample was taken.
+    pub fn start(
+        sample_rate: usize,
+        channels: usize,
+        transport: Arc<Transport>,
+    ) -> Result<(AudioOutput, Sender<Sample>), String> {
+        let (sample_sender, sample_receiver) = mpsc::channel();
+        let (stop_sender, stop_receiver) = mpsc::channel::<()>();
+        let (ready_sender, ready_receiver) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("audio_output_thread".into())
+            .spawn(move || {
+                match open_stream(sample_rate, channels, sample_receiver, transport) {
+                    Ok(stream) => {
+                        let _ = ready_sender.send(Ok(()));
+                        // Keep the stream playing until the output is dropped
+                        let _ = stop_receiver.recv();
+                        drop(stream);
+                    }
+                    Err(e) => {
+                        let _ = ready_sender.send(Err(e));
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        ready_receiver.recv().map_err(|e| e.to_string())??;
+        Ok((
+            AudioOutput {
+                _stop_sender: stop_sender,
+            },
+            sample_sender,
+        ))
+    }
+}
+
+fn open_stream(
+    sample_rate: usize,
+    channels: usize,
+    sample_receiver: Receiver<Sample>,
+    transport: Arc<Transport>,
+) -> Result<Stream, String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("No audio output device is available.")?;
+    let config = StreamConfig {
+        channels: channels as u16,
+        sample_rate: SampleRate(sample_rate as u32),
+        buffer_size: BufferSize::Default,
+    };
+
+    let mut queue = SampleQueue::new(sample_receiver, channels);
+    let stream = device
+        .build_output_stream(
+            &config,
+            move |data: &mut [Sample], info: &OutputCallbackInfo| {
+                // Time until the samples written now are heard
+                let timestamp = info.timestamp();
+                let latency = timestamp
+                    .playback
+                    .duration_since(&timestamp.callback)
+                    .unwrap_or_default();
+                let latency_frames = (latency.as_secs_f64() * sample_rate as f64).round() as usize;
+
+                let frames = queue.fill(data);
+                transport.on_played(frames, latency_frames);
+                if queue.ended {
+                    transport.finish();
+                }
+            },
+            |e| eprintln!("Audio output error: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+    stream.play().map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+/// Samples waiting to be played, taken a whole frame at a time.
+struct SampleQueue {
+    receiver: Receiver<Sample>,
+    channels: usize,
+    /// Samples of the frame which wasn't fully sent yet.
+    pending: Vec<Sample>,
+    /// Whether the sender was dropped and every sample was taken.
+    ended: bool,
+}
+
+impl SampleQueue {
+    fn new(receiver: Receiver<Sample>, channels: usize) -> Self {
+        SampleQueue {
+            receiver,
+            channels: channels.max(1),
+            pending: Vec::new(),
+            ended: false,
+        }
+    }
+
+    /// Write the next frames to the buffer, and silence where none were sent yet.
+    /// Returns the number of frames played: the silence after the end counts,
+    /// but not the silence played while waiting for the mixer.
+    fn fill(&mut self, data: &mut [Sample]) -> usize {
+        let mut frames = 0;
+        for frame in data.chunks_mut(self.channels) {
+            if !self.ended && !self.next_frame() {
+                frame.fill(0.0);
+                continue;
+            }
+            match self.ended {
+                true => frame.fill(0.0),
+                false => {
+                    frame.copy_from_slice(&self.pending);
+                    self.pending.clear();
+                }
+            }
+            frames += 1;
+        }
+        frames
+    }
+
+    /// Take the samples of the next frame into `pending`.
+    /// Returns whether a whole frame is ready, or the queue ended.
+    fn next_frame(&mut self) -> bool {
+        while self.pending.len() < self.channels {
+            match self.receiver.try_recv() {
+                Ok(sample) => self.pending.push(sample),
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => {
+                    self.ended = true;
+                    return true;
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waiting_for_the_mixer_plays_silence_which_doesnt_count() {
+        let (sender, receiver) = mpsc::channel();
+        let mut queue = SampleQueue::new(receiver, 2);
+        for sample in [0.5, 0.25, 0.5] {
+            let _ = sender.send(sample);
+        }
+
+        let mut data = [1.0; 6];
+        assert_eq!(queue.fill(&mut data), 1);
+        assert_eq!(data, [0.5, 0.25, 0.0, 0.0, 0.0, 0.0]);
+
+        // The rest of the half sent frame is played first
+        let _ = sender.send(0.25);
+        drop(sender);
+        assert_eq!(queue.fill(&mut data), 3);
+        assert_eq!(data, [0.5, 0.25, 0.0, 0.0, 0.0, 0.0]);
+        assert!(queue.ended);
+    }
+}
@@ -0,0 +1,133 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::Mixer;
+use serde::{Deserialize, Serialize};
+
+/// Sample rates a project can use.
+pub const SUPPORTED_SAMPLE_RATES: [u32; 4] = [44100, 48000, 88200, 96000];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChannelLayout {
+    Mono = 0,
+    Stereo = 1,
+}
+
+impl ChannelLayout {
+    pub fn channels(&self) -> usize {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+        }
+    }
+
+    pub fn from_channels(channels: usize) -> Option<Self> {
+        match channels {
+            1 => Some(ChannelLayout::Mono),
+            2 => Some(ChannelLayout::Stereo),
+            _ => None,
+        }
+    }
+}
+
+/// Format the project is mixed and played back in.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AudioSettings {
+    pub sample_rate: u32,
+    pub channel_layout: ChannelLayout,
+}
+
+impl AudioSettings {
+    /// Get the settings the mixer is currently using.
+    pub fn from_mixer(mixer: &Mixer) -> Self {
+        AudioSettings {
+            sample_rate: mixer.sample_rate as u32,
+            channel_layout: ChannelLayout::from_channels(mixer.channels)
+                .unwrap_or(ChannelLayout::Stereo),
+        }
+    }
+
+    /// Make the mixer use the settings.
+    pub fn apply(&self, mixer: &mut Mixer) {
+        mixer.sample_rate = self.sample_rate as usize;
+        mixer.channels = self.channel_layout.channels();
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channel_layout.channels()
+    }
+
+    /// Check that the project can use the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if !SUPPORTED_SAMPLE_RATES.contains(&self.sample_rate) {
+            return Err(format!(
+                "Sample rate {} Hz is not supported.",
+                self.sample_rate
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            sample_rate: 48000,
+            channel_layout: ChannelLayout::Stereo,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_applied_to_the_mixer() {
+        let mut mixer = Mixer::new(120.0, 48000, 2);
+        let settings = AudioSettings {
+            sample_rate: 96000,
+            channel_layout: ChannelLayout::Mono,
+        };
+        settings.apply(&mut mixer);
+        assert_eq!((mixer.sample_rate, mixer.channels), (96000, 1));
+        assert_eq!(AudioSettings::from_mixer(&mixer), settings);
+    }
+
+    #[test]
+    fn only_supported_sample_rates_are_valid() {
+        for sample_rate in SUPPORTED_SAMPLE_RATES {
+            let settings = AudioSettings {
+                sample_rate,
+                channel_layout: ChannelLayout::Stereo,
+            };
+            assert!(settings.validate().is_ok());
+        }
+        let settings = AudioSettings {
+            sample_rate: 22050,
+            channel_layout: ChannelLayout::Stereo,
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn channel_counts_map_to_layouts() {
+        assert_eq!(ChannelLayout::from_channels(1), Some(ChannelLayout::Mono));
+        assert_eq!(ChannelLayout::from_channels(2), Some(ChannelLayout::Stereo));
+        assert_eq!(ChannelLayout::from_channels(6), None);
+        assert_eq!(ChannelLayout::Stereo.channels(), 2);
+    }
+}
@@ -0,0 +1,73 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::audio_utils::Beats;
+use serde::{Deserialize, Serialize};
+
+/// Value of an automated parameter at a beat.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct AutomationPoint {
+    pub beat: Beats,
+    pub value: f32,
+}
+
+/// Changes of a parameter over the timeline.
+/// The value moves linearly between the points, and holds before the first and after the last one.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Automation {
+    /// Points sorted by beat.
+    pub points: Vec<AutomationPoint>,
+}
+
+impl Automation {
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Check that the points are on the timeline and sorted by beat.
+    pub fn validate(&self) -> Result<(), String> {
+        for point in &self.points {
+            if !point.beat.is_finite() || point.beat < 0.0 || !point.value.is_finite() {
+                return Err("Automation points must be within the project.".to_string());
+            }
+        }
+        if self
+            .points
+            .windows(2)
+            .any(|points| points[1].beat < points[0].beat)
+        {
+            return Err("Automation points must be sorted by beat.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Value at the beat, or `None` if there are no points.
+    pub fn value_at(&self, beat: Beats) -> Option<f32> {
+        let next = self.points.partition_point(|point| point.beat <= beat);
+        match (
+            next.checked_sub(1).map(|index| self.points[index]),
+            self.points.get(next),
+        ) {
+            (Some(previous), Some(next)) if next.beat > previous.beat => {
+                let position = (beat - previous.beat) / (next.beat - previous.beat);
+                Some(previous.value + (next.value - previous.value) * position)
+            }
+            (Some(previous), _) => Some(previous.value),
+            (None, Some(next)) => Some(next.value),
+            (None, None) => None,
+        }
+    }
+}
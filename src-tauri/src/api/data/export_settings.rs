@@ -0,0 +1,146 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::Beats;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Wav = 0,
+    Flac = 1,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::Flac => "flac",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BitDepth {
+    Int16 = 0,
+    Int24 = 1,
+    Float32 = 2,
+}
+
+impl BitDepth {
+    pub fn bits(&self) -> u32 {
+        match self {
+            BitDepth::Int16 => 16,
+            BitDepth::Int24 => 24,
+            BitDepth::Float32 => 32,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportSettings {
+    /// Path of the file to write.
+    pub path: String,
+    /// Beat to start rendering from.
+    pub start: Beats,
+    /// Beat to stop rendering at. Renders to the end of the project if `None`.
+    pub end: Option<Beats>,
+    pub sample_rate: u32,
+    pub bit_depth: BitDepth,
+    pub format: ExportFormat,
+}
+
+impl ExportSettings {
+    /// Check the settings before starting to render.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_render_settings(
+            self.start,
+            self.end,
+            self.sample_rate,
+            self.bit_depth,
+            self.format,
+        )
+    }
+}
+
+/// Where the stems are taken from in the signal chain.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StemSource {
+    /// What the track adds to the mix, after its volume and pan and those of its folders.
+    /// Bus tracks get a stem of their own, fed by the sends to them.
+    /// The graphs of the folders aren't applied.
+    PreMaster = 0,
+    /// The same as `PreMaster`, run through the master bus on its own.
+    PostMaster = 1,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StemExportSettings {
+    /// Directory to write the stems to. It's created if it doesn't exist.
+    pub directory: String,
+    /// Text put before each track name in the file names, e.g. the project name.
+    pub file_prefix: String,
+    /// Whether to number the files in track order.
+    pub numbered: bool,
+    /// Beat to start rendering from.
+    pub start: Beats,
+    /// Beat to stop rendering at. Renders to the end of the project if `None`.
+    pub end: Option<Beats>,
+    pub sample_rate: u32,
+    pub bit_depth: BitDepth,
+    pub format: ExportFormat,
+    pub source: StemSource,
+    /// Whether to render the tracks which aren't heard in the mix too,
+    /// because they're muted or other tracks are soloed.
+    /// Every track is then rendered as if it was heard, so the stems don't sum up to the mix.
+    pub include_muted: bool,
+}
+
+impl StemExportSettings {
+    /// Check the settings before starting to render.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.directory.is_empty() {
+            return Err("No directory was specified for the stems.".to_string());
+        }
+        validate_render_settings(
+            self.start,
+            self.end,
+            self.sample_rate,
+            self.bit_depth,
+            self.format,
+        )
+    }
+}
+
+fn validate_render_settings(
+    start: Beats,
+    end: Option<Beats>,
+    sample_rate: u32,
+    bit_depth: BitDepth,
+    format: ExportFormat,
+) -> Result<(), String> {
+    if sample_rate == 0 {
+        return Err("Sample rate must be greater than zero.".to_string());
+    }
+    if let Some(end) = end {
+        if end <= start {
+            return Err("The end of the range must be after its start.".to_string());
+        }
+    }
+    if format == ExportFormat::Flac && bit_depth == BitDepth::Float32 {
+        return Err("FLAC doesn't support 32-bit float samples.".to_string());
+    }
+    Ok(())
+}
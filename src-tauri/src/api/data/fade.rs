@@ -0,0 +1,72 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::{Beats, Sample};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// Level in dB an exponential fade starts from.
+const EXPONENTIAL_FADE_FLOOR_DB: f32 = -60.0;
+
+/// Shape of the gain change over a fade.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum FadeCurve {
+    #[default]
+    Linear,
+    /// Keeps the sum of the power constant over a crossfade.
+    EqualPower,
+    /// Changes by the same number of dB over time, which sounds even to the ear.
+    Exponential,
+}
+
+impl FadeCurve {
+    /// Gain of a fade in at the position, from 0 at its start to 1 at its end.
+    /// A fade out uses the same curve backwards.
+    pub fn gain(&self, position: f32) -> Sample {
+        let position = position.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => position,
+            FadeCurve::EqualPower => (position * FRAC_PI_2).sin(),
+            FadeCurve::Exponential => {
+                let floor = 10.0_f32.powf(EXPONENTIAL_FADE_FLOOR_DB / 20.0);
+                let gain = 10.0_f32.powf(EXPONENTIAL_FADE_FLOOR_DB * (1.0 - position) / 20.0);
+                (gain - floor) / (1.0 - floor)
+            }
+        }
+    }
+}
+
+/// A fade at the start or the end of a region.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub struct Fade {
+    /// Length of the fade in beats, no fade at 0.
+    pub length: Beats,
+    pub curve: FadeCurve,
+}
+
+impl Fade {
+    /// Check that the fade can be applied to a region.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.length.is_finite() || self.length < 0.0 {
+            return Err(format!("{} is not a valid fade length.", self.length));
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length <= 0.0
+    }
+}
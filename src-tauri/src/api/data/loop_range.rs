@@ -0,0 +1,78 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::audio_utils::Beats;
+use serde::{Deserialize, Serialize};
+
+/// Section of the timeline played repeatedly.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct LoopRange {
+    pub start: Beats,
+    pub end: Beats,
+    /// Whether the playback loops over the range.
+    pub enabled: bool,
+}
+
+impl LoopRange {
+    /// Check that the range can be looped over.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.start.is_finite() || !self.end.is_finite() || self.start < 0.0 {
+            return Err("The loop range must be within the project.".to_string());
+        }
+        if self.end <= self.start {
+            return Err("The end of the loop must be after its start.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for LoopRange {
+    fn default() -> Self {
+        LoopRange {
+            start: 0.0,
+            end: 16.0,
+            enabled: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loop_range(start: Beats, end: Beats) -> LoopRange {
+        LoopRange {
+            start,
+            end,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn loop_must_end_after_it_starts() {
+        assert!(LoopRange::default().validate().is_ok());
+        assert!(loop_range(4.0, 8.0).validate().is_ok());
+        assert!(loop_range(4.0, 4.0).validate().is_err());
+        assert!(loop_range(8.0, 4.0).validate().is_err());
+    }
+
+    #[test]
+    fn loop_must_be_within_the_project() {
+        assert!(loop_range(-1.0, 4.0).validate().is_err());
+        assert!(loop_range(0.0, Beats::INFINITY).validate().is_err());
+        assert!(loop_range(Beats::NAN, 4.0).validate().is_err());
+    }
+}
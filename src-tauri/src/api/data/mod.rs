@@ -14,12 +14,32 @@
 // limitations under the License.
 //
 
+pub mod audio_settings;
+pub mod automation;
+pub mod export_settings;
+pub mod fade;
+pub mod loop_range;
 pub mod node_type;
 pub mod note_data;
 pub mod region_data;
+pub mod region_settings;
+pub mod sidechain;
+pub mod time_signature;
 pub mod track_data;
+pub mod track_mix;
+pub mod track_send;
 
+pub use audio_settings::{AudioSettings, ChannelLayout};
+pub use automation::{Automation, AutomationPoint};
+pub use export_settings::{BitDepth, ExportFormat, ExportSettings, StemExportSettings, StemSource};
+pub use fade::{Fade, FadeCurve};
+pub use loop_range::LoopRange;
 pub use node_type::NodeType;
 pub use note_data::NoteData;
 pub use region_data::{RegionData, RegionType};
+pub use region_settings::RegionSettings;
+pub use sidechain::Sidechain;
+pub use time_signature::TimeSignature;
 pub use track_data::{TrackData, TrackType};
+pub use track_mix::TrackMix;
+pub use track_send::TrackSend;
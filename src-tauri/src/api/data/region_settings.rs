@@ -0,0 +1,181 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::Fade;
+use crate::api::data::track_mix::db_to_gain;
+use knodiq_engine::{Beats, Sample};
+use serde::{Deserialize, Serialize};
+
+/// Loudest clip gain a region can be set to.
+pub const MAX_REGION_GAIN_DB: f32 = 24.0;
+
+/// Largest pitch shift of a region in either direction, in semitones.
+pub const MAX_PITCH_SHIFT: f32 = 24.0;
+
+/// Shortest loop length of a region, so that the repeats stay reasonably few.
+pub const MIN_LOOP_LENGTH: Beats = 1.0 / 16.0;
+
+/// Settings of a region applied on top of its audio or notes when mixing.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct RegionSettings {
+    /// Fade at the start of a buffer region.
+    pub fade_in: Fade,
+    /// Fade at the end of a buffer region.
+    pub fade_out: Fade,
+    /// Clip gain in dB, applied to the audio of buffer regions
+    /// and to the velocity of the notes of note regions.
+    pub gain: f32,
+    /// Whether the polarity of the audio of a buffer region is inverted.
+    pub invert: bool,
+    /// How much longer the audio of a buffer region plays than at its own speed,
+    /// on top of following the tempo of the project.
+    pub stretch: f32,
+    /// Pitch shift of the audio of a buffer region in semitones, keeping its length.
+    pub pitch_shift: f32,
+    /// Length of the content repeated over the region, or `None` to play it once.
+    pub loop_length: Option<Beats>,
+}
+
+impl RegionSettings {
+    /// Check that the settings are within range.
+    pub fn validate(&self) -> Result<(), String> {
+        self.fade_in.validate()?;
+        self.fade_out.validate()?;
+        validate_region_gain(self.gain)?;
+        validate_pitch_shift(self.pitch_shift)?;
+        validate_loop_length(self.loop_length)?;
+        if !self.stretch.is_finite() || self.stretch <= 0.0 {
+            return Err(format!("{} is not a valid stretch ratio.", self.stretch));
+        }
+        Ok(())
+    }
+
+    /// Factor the audio of a buffer region is multiplied by.
+    pub fn amplitude(&self) -> Sample {
+        match self.invert {
+            true => -db_to_gain(self.gain),
+            false => db_to_gain(self.gain),
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == RegionSettings::default()
+    }
+}
+
+impl Default for RegionSettings {
+    fn default() -> Self {
+        RegionSettings {
+            fade_in: Fade::default(),
+            fade_out: Fade::default(),
+            gain: 0.0,
+            invert: false,
+            stretch: 1.0,
+            pitch_shift: 0.0,
+            loop_length: None,
+        }
+    }
+}
+
+pub fn validate_region_gain(gain: f32) -> Result<(), String> {
+    if !gain.is_finite() || gain > MAX_REGION_GAIN_DB {
+        return Err(format!(
+            "The region gain must be {} dB or lower.",
+            MAX_REGION_GAIN_DB
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_pitch_shift(semitones: f32) -> Result<(), String> {
+    if !(-MAX_PITCH_SHIFT..=MAX_PITCH_SHIFT).contains(&semitones) {
+        return Err(format!(
+            "The pitch shift must be between -{} and {} semitones.",
+            MAX_PITCH_SHIFT, MAX_PITCH_SHIFT
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_loop_length(loop_length: Option<Beats>) -> Result<(), String> {
+    match loop_length {
+        Some(length) if !length.is_finite() || length < MIN_LOOP_LENGTH => Err(format!(
+            "The loop length must be at least {} beats.",
+            MIN_LOOP_LENGTH
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_is_limited_from_above() {
+        assert!(validate_region_gain(MAX_REGION_GAIN_DB).is_ok());
+        assert!(validate_region_gain(-120.0).is_ok());
+        assert!(validate_region_gain(MAX_REGION_GAIN_DB + 0.5).is_err());
+        assert!(validate_region_gain(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn inverting_flips_the_amplitude() {
+        let mut settings = RegionSettings {
+            gain: 6.0,
+            ..RegionSettings::default()
+        };
+        let amplitude = settings.amplitude();
+        assert!((amplitude - 1.995).abs() < 1e-3);
+
+        settings.invert = true;
+        assert_eq!(settings.amplitude(), -amplitude);
+        assert!(!settings.is_default());
+        assert_eq!(RegionSettings::default().amplitude(), 1.0);
+    }
+
+    #[test]
+    fn stretch_and_pitch_shift_are_validated() {
+        let settings = RegionSettings {
+            stretch: 0.5,
+            pitch_shift: -MAX_PITCH_SHIFT,
+            ..RegionSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        for invalid in [
+            RegionSettings {
+                stretch: 0.0,
+                ..settings
+            },
+            RegionSettings {
+                pitch_shift: MAX_PITCH_SHIFT + 1.0,
+                ..settings
+            },
+        ] {
+            assert!(invalid.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn loops_need_a_minimum_length() {
+        assert!(validate_loop_length(None).is_ok());
+        assert!(validate_loop_length(Some(MIN_LOOP_LENGTH)).is_ok());
+        assert!(validate_loop_length(Some(MIN_LOOP_LENGTH / 2.0)).is_err());
+        assert!(validate_loop_length(Some(Beats::INFINITY)).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// Output of another track routed into an input of a node, e.g. to duck the track
+/// with the kick drum.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Sidechain {
+    /// ID of the track whose output is routed.
+    pub source_id: u32,
+    /// Node receiving the audio, in the graph of the track owning the sidechain.
+    pub node_id: NodeId,
+    /// Name of the input of the node.
+    pub param: String,
+}
@@ -0,0 +1,76 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimeSignature {
+    /// Number of beats in a bar.
+    pub numerator: u32,
+    /// Note value of a beat, e.g. 4 for a quarter note.
+    pub denominator: u32,
+}
+
+impl TimeSignature {
+    /// Check that the time signature can be used by the project.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.numerator == 0 {
+            return Err("A bar must have at least one beat.".to_string());
+        }
+        if !self.denominator.is_power_of_two() {
+            return Err(format!(
+                "{} is not a valid note value for a time signature.",
+                self.denominator
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        TimeSignature {
+            numerator: 4,
+            denominator: 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time_signature(numerator: u32, denominator: u32) -> TimeSignature {
+        TimeSignature {
+            numerator,
+            denominator,
+        }
+    }
+
+    #[test]
+    fn common_time_signatures_are_valid() {
+        assert!(TimeSignature::default().validate().is_ok());
+        assert!(time_signature(7, 8).validate().is_ok());
+        assert!(time_signature(1, 1).validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_time_signatures_are_rejected() {
+        assert!(time_signature(0, 4).validate().is_err());
+        assert!(time_signature(3, 0).validate().is_err());
+        assert!(time_signature(5, 6).validate().is_err());
+    }
+}
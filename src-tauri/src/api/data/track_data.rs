@@ -20,6 +20,26 @@ use serde::{Deserialize, Serialize};
 pub enum TrackType {
     BufferTrack = 0,
     NoteTrack = 1,
+    /// A track without regions, processing the audio sent from other tracks with its graph.
+    BusTrack = 2,
+    /// A track without regions, grouping other tracks and summing them with its graph.
+    FolderTrack = 3,
+}
+
+impl TrackType {
+    /// Whether the track sums other tracks instead of playing regions.
+    pub fn is_submix(&self) -> bool {
+        matches!(self, TrackType::BusTrack | TrackType::FolderTrack)
+    }
+
+    /// Get the track type from the type name reported by `Track::track_type`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "BufferTrack" => Some(TrackType::BufferTrack),
+            "NoteTrack" => Some(TrackType::NoteTrack),
+            _ => None,
+        }
+    }
 }
 
 impl Clone for TrackType {
@@ -27,6 +47,8 @@ impl Clone for TrackType {
         match self {
             TrackType::BufferTrack => TrackType::BufferTrack,
             TrackType::NoteTrack => TrackType::NoteTrack,
+            TrackType::BusTrack => TrackType::BusTrack,
+            TrackType::FolderTrack => TrackType::FolderTrack,
         }
     }
 }
@@ -0,0 +1,98 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::Sample;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// Volumes at or below this are silent.
+pub const MIN_VOLUME_DB: f32 = -96.0;
+
+/// Loudest volume a track can be set to.
+pub const MAX_VOLUME_DB: f32 = 12.0;
+
+/// Mixing console settings of a track.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TrackMix {
+    /// Volume of the track in dB.
+    pub volume: f32,
+    /// Position in the stereo field, from -1 (left) to 1 (right).
+    pub pan: f32,
+    pub mute: bool,
+    pub solo: bool,
+    /// Whether the track keeps playing when other tracks are soloed.
+    pub solo_safe: bool,
+}
+
+impl TrackMix {
+    /// Check that the settings are within range.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_volume(self.volume)?;
+        validate_pan(self.pan)
+    }
+
+    /// Gain of each output channel, given whether the track is audible.
+    /// Stereo outputs are balanced with a sine taper, so the centre keeps the full volume.
+    pub fn channel_gains(&self, channels: usize, audible: bool) -> Vec<Sample> {
+        let gain = if audible {
+            db_to_gain(self.volume)
+        } else {
+            0.0
+        };
+        if channels != 2 {
+            return vec![gain; channels];
+        }
+
+        let left = (self.pan.max(0.0) * FRAC_PI_2).cos();
+        let right = ((-self.pan).max(0.0) * FRAC_PI_2).cos();
+        vec![gain * left, gain * right]
+    }
+}
+
+impl Default for TrackMix {
+    fn default() -> Self {
+        TrackMix {
+            volume: 0.0,
+            pan: 0.0,
+            mute: false,
+            solo: false,
+            solo_safe: false,
+        }
+    }
+}
+
+/// Convert a level in dB to an amplitude, silent at `MIN_VOLUME_DB` and below.
+pub fn db_to_gain(db: f32) -> Sample {
+    if db > MIN_VOLUME_DB {
+        10.0_f32.powf(db / 20.0)
+    } else {
+        0.0
+    }
+}
+
+pub fn validate_volume(volume: f32) -> Result<(), String> {
+    if !volume.is_finite() || volume > MAX_VOLUME_DB {
+        return Err(format!("The volume must be {} dB or lower.", MAX_VOLUME_DB));
+    }
+    Ok(())
+}
+
+pub fn validate_pan(pan: f32) -> Result<(), String> {
+    if !(-1.0..=1.0).contains(&pan) {
+        return Err("The pan must be between -1 and 1.".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::data::automation::Automation;
+use crate::api::data::track_mix::validate_volume;
+use knodiq_engine::audio_utils::Beats;
+use serde::{Deserialize, Serialize};
+
+/// Part of the output of a track sent to a bus track.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct TrackSend {
+    /// ID of the bus track receiving the audio.
+    pub bus_id: u32,
+    /// Level of the send in dB.
+    pub level: f32,
+    /// Whether the audio is taken before the volume and pan of the track.
+    pub pre_fader: bool,
+    /// Level of the send in dB over the timeline. Overrides `level` when it has points.
+    #[serde(default)]
+    pub automation: Automation,
+}
+
+impl TrackSend {
+    pub fn new(bus_id: u32, level: f32, pre_fader: bool) -> Self {
+        TrackSend {
+            bus_id,
+            level,
+            pre_fader,
+            automation: Automation::default(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        validate_volume(self.level)?;
+        self.automation.validate()?;
+        for point in &self.automation.points {
+            validate_volume(point.value)?;
+        }
+        Ok(())
+    }
+
+    /// Level of the send in dB at the beat.
+    pub fn level_at(&self, beat: Beats) -> f32 {
+        self.automation.value_at(beat).unwrap_or(self.level)
+    }
+}
@@ -0,0 +1,157 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::data::{BitDepth, ExportFormat};
+use crate::api::export::flac::write_flac;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use knodiq_engine::Sample;
+use std::path::{Path, PathBuf};
+
+/// Add the extension of the format to the path if it has none.
+pub fn with_format_extension(path: &str, format: ExportFormat) -> PathBuf {
+    let mut path = PathBuf::from(path);
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+    path
+}
+
+/// Write the buffers, one per channel, to an audio file.
+pub fn write_audio_file(
+    path: &Path,
+    buffers: &[Vec<Sample>],
+    sample_rate: u32,
+    bit_depth: BitDepth,
+    format: ExportFormat,
+) -> Result<(), String> {
+    match format {
+        ExportFormat::Wav => write_wav(path, buffers, sample_rate, bit_depth),
+        ExportFormat::Flac => {
+            let channels = buffers
+                .iter()
+                .map(|buffer| quantize(buffer, bit_depth.bits()))
+                .collect::<Vec<_>>();
+            write_flac(path, &channels, sample_rate, bit_depth.bits())
+        }
+    }
+}
+
+fn write_wav(
+    path: &Path,
+    buffers: &[Vec<Sample>],
+    sample_rate: u32,
+    bit_depth: BitDepth,
+) -> Result<(), String> {
+    let spec = WavSpec {
+        channels: buffers.len() as u16,
+        sample_rate,
+        bits_per_sample: bit_depth.bits() as u16,
+        sample_format: match bit_depth {
+            BitDepth::Float32 => SampleFormat::Float,
+            BitDepth::Int16 | BitDepth::Int24 => SampleFormat::Int,
+        },
+    };
+    let mut writer = WavWriter::create(path, spec)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+
+    let frames = buffers.first().map(|buffer| buffer.len()).unwrap_or(0);
+    match bit_depth {
+        BitDepth::Float32 => {
+            for frame in 0..frames {
+                for buffer in buffers {
+                    writer
+                        .write_sample(buffer[frame])
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        BitDepth::Int16 | BitDepth::Int24 => {
+            let channels = buffers
+                .iter()
+                .map(|buffer| quantize(buffer, bit_depth.bits()))
+                .collect::<Vec<_>>();
+            for frame in 0..frames {
+                for channel in &channels {
+                    writer
+                        .write_sample(channel[frame])
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+    }
+
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+/// Convert the samples to signed integers of the given bit depth, clipping anything outside [-1, 1].
+fn quantize(buffer: &[Sample], bits: u32) -> Vec<i32> {
+    let max = ((1i64 << (bits - 1)) - 1) as f32;
+    buffer
+        .iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * max).round() as i32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hound::WavReader;
+
+    #[test]
+    fn extension_is_added_only_when_missing() {
+        assert_eq!(
+            with_format_extension("mix", ExportFormat::Wav),
+            PathBuf::from("mix.wav")
+        );
+        assert_eq!(
+            with_format_extension("mix", ExportFormat::Flac),
+            PathBuf::from("mix.flac")
+        );
+        assert_eq!(
+            with_format_extension("mix.aiff", ExportFormat::Wav),
+            PathBuf::from("mix.aiff")
+        );
+    }
+
+    #[test]
+    fn samples_are_clipped_when_quantized() {
+        assert_eq!(
+            quantize(&[0.0, 1.0, -1.0, 0.5, 2.0, -2.0], 16),
+            vec![0, 32767, -32767, 16384, 32767, -32767]
+        );
+        assert_eq!(quantize(&[1.0, -0.25], 24), vec![8388607, -2097152]);
+    }
+
+    #[test]
+    fn wav_file_holds_the_interleaved_samples() {
+        let path = std::env::temp_dir().join("knodiq_audio_file_test.wav");
+        let buffers = vec![vec![0.0, 0.5, -0.5], vec![1.0, -1.0, 0.25]];
+        write_audio_file(&path, &buffers, 44100, BitDepth::Int16, ExportFormat::Wav).unwrap();
+
+        let mut reader = WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(
+            (spec.channels, spec.sample_rate, spec.bits_per_sample),
+            (2, 44100, 16)
+        );
+        let samples = reader
+            .samples::<i32>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(samples, vec![0, 32767, 16384, -32767, -16384, 8192]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
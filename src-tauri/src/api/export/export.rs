@@ -0,0 +1,222 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::AppState;
+use crate::api::data::{ExportSettings, StemExportSettings};
+use crate::api::export::audio_file::{with_format_extension, write_audio_file};
+use crate::api::export::stems::export_stems_to_files;
+use crate::api::mixing::console::Console;
+use crate::api::mixing::render::render_mix;
+use crate::api::mixing::resample::resample;
+use crate::api::mixing::tempo::TempoMap;
+use crate::api::mixing::{MixerCommand, MixerResult, request_mixer_result};
+use knodiq_engine::{Beats, Mixer, Track};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+use std::thread;
+use tauri::{AppHandle, Emitter, State, command};
+
+/// Payload of the `export_progress` event.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportProgress {
+    /// Rendered fraction, from 0 to 1.
+    pub progress: f32,
+}
+
+/// Payload of the `export_finished` event.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportFinished {
+    pub path: String,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+#[command]
+pub fn export_mix(
+    settings: ExportSettings,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    settings.validate()?;
+
+    let (render, tempo_map, console, master_track) =
+        match request_mixer_result(MixerCommand::GetMixer, &state)? {
+            MixerResult::Mixer(render, tempo_map, console, master_track) => {
+                (render, tempo_map, console, master_track)
+            }
+            _ => return Err("Unexpected result type received.".to_string()),
+        };
+    let should_stop = reset_export_stop_flag(&state)?;
+
+    // Render on a separate thread so that the mixer stays responsive
+    thread::Builder::new()
+        .name("export_thread".into())
+        .spawn(move || {
+            let path = with_format_extension(&settings.path, settings.format);
+            let (mixer, _) = render.finish();
+            let result = export(
+                mixer,
+                &tempo_map,
+                &console,
+                master_track,
+                &settings,
+                should_stop,
+                &app,
+            );
+            emit_finished(&app, path.display().to_string(), result);
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start export thread: {}", e))
+}
+
+#[command]
+pub fn export_stems(
+    settings: StemExportSettings,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    settings.validate()?;
+
+    let (render, tempo_map, console, master_track) =
+        match request_mixer_result(MixerCommand::GetMixer, &state)? {
+            MixerResult::Mixer(render, tempo_map, console, master_track) => {
+                (render, tempo_map, console, master_track)
+            }
+            _ => return Err("Unexpected result type received.".to_string()),
+        };
+    let should_stop = reset_export_stop_flag(&state)?;
+
+    thread::Builder::new()
+        .name("export_thread".into())
+        .spawn(move || {
+            let (mixer, _) = render.finish();
+            let result = export_stems_to_files(
+                mixer,
+                &tempo_map,
+                &console,
+                master_track,
+                &settings,
+                should_stop,
+                &app,
+            );
+            emit_finished(&app, settings.directory.clone(), result);
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start export thread: {}", e))
+}
+
+#[command]
+pub fn cancel_export(state: State<'_, Mutex<AppState>>) {
+    let locked_state = state.lock().unwrap();
+    locked_state
+        .export_should_stop
+        .store(true, Ordering::Release);
+}
+
+/// Clear the stop flag before starting a new export and return it.
+pub fn reset_export_stop_flag(
+    state: &State<'_, Mutex<AppState>>,
+) -> Result<Arc<AtomicBool>, String> {
+    let locked_state = state.lock().map_err(|e| e.to_string())?;
+    locked_state
+        .export_should_stop
+        .store(false, Ordering::Release);
+    Ok(Arc::clone(&locked_state.export_should_stop))
+}
+
+/// Emit `export_finished` with the result of an export.
+/// `result` is `Ok(false)` if the export was cancelled.
+pub fn emit_finished(app: &AppHandle, path: String, result: Result<bool, String>) {
+    let payload = match result {
+        Ok(completed) => ExportFinished {
+            path,
+            cancelled: !completed,
+            error: None,
+        },
+        Err(error) => ExportFinished {
+            path,
+            cancelled: false,
+            error: Some(error),
+        },
+    };
+    app.emit("export_finished", payload).ok();
+}
+
+/// Render the mix and write it to the file.
+/// Returns `Ok(false)` if the export was cancelled.
+fn export(
+    mixer: Mixer,
+    tempo_map: &TempoMap,
+    console: &Console,
+    master_track: Box<dyn Track>,
+    settings: &ExportSettings,
+    should_stop: Arc<AtomicBool>,
+    app: &AppHandle,
+) -> Result<bool, String> {
+    let (start, end) = render_range(&mixer, tempo_map, settings.start, settings.end);
+
+    let app_handle = app.clone();
+    let on_progress = Arc::new(move |progress| {
+        app_handle
+            .emit("export_progress", ExportProgress { progress })
+            .ok();
+    });
+
+    match render_mix(
+        &mixer,
+        tempo_map,
+        console,
+        master_track,
+        start,
+        end,
+        should_stop,
+        on_progress,
+    )? {
+        Some(buffers) => {
+            // Audio sources are decoded at the project rate, so convert the result instead
+            let buffers = resample(&buffers, mixer.sample_rate, settings.sample_rate as usize);
+            let path = with_format_extension(&settings.path, settings.format);
+            write_audio_file(
+                &path,
+                &buffers,
+                settings.sample_rate,
+                settings.bit_depth,
+                settings.format,
+            )?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Convert the range to export to the timeline of the mixer warped by the tempo map.
+/// The range ends at the end of the project if `end` is `None`.
+pub fn render_range(
+    mixer: &Mixer,
+    tempo_map: &TempoMap,
+    start: Beats,
+    end: Option<Beats>,
+) -> (Beats, Beats) {
+    let start = tempo_map.to_linear(mixer.tempo, start);
+    let end = match end {
+        Some(end) => tempo_map.to_linear(mixer.tempo, end),
+        None => mixer.duration(),
+    };
+    (start, end)
+}
@@ -0,0 +1,396 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Number of samples per channel in each frame.
+const BLOCK_SIZE: usize = 4096;
+
+/// Highest order of the fixed predictors defined by FLAC.
+const MAX_FIXED_ORDER: usize = 4;
+
+/// Highest Rice parameter which can be coded with 5 bits, 31 being the escape code.
+const MAX_RICE_PARAMETER: u32 = 30;
+
+/// Write the channels to a FLAC file.
+/// Each channel is coded independently with the best fixed predictor and a single
+/// Rice partition, which gets most of the compression of a full encoder.
+/// `channels` holds the samples of each channel, already quantized to `bits_per_sample`.
+pub fn write_flac(
+    path: &Path,
+    channels: &[Vec<i32>],
+    sample_rate: u32,
+    bits_per_sample: u32,
+) -> Result<(), String> {
+    if channels.is_empty() || channels.len() > 8 {
+        return Err(format!(
+            "FLAC supports 1 to 8 channels, got {}.",
+            channels.len()
+        ));
+    }
+    let sample_size_code = match bits_per_sample {
+        16 => 0b100,
+        24 => 0b110,
+        _ => return Err(format!("Unsupported FLAC bit depth: {}.", bits_per_sample)),
+    };
+
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut file = BufWriter::new(file);
+    let total_samples = channels[0].len();
+
+    let header = stream_header(channels.len(), sample_rate, bits_per_sample, total_samples);
+    file.write_all(&header).map_err(|e| e.to_string())?;
+
+    for (frame_number, start) in (0..total_samples).step_by(BLOCK_SIZE).enumerate() {
+        let end = (start + BLOCK_SIZE).min(total_samples);
+        let blocks = channels
+            .iter()
+            .map(|channel| &channel[start..end])
+            .collect::<Vec<_>>();
+        let frame = encode_frame(
+            frame_number as u64,
+            &blocks,
+            bits_per_sample,
+            sample_size_code,
+        );
+        file.write_all(&frame).map_err(|e| e.to_string())?;
+    }
+
+    file.flush().map_err(|e| e.to_string())
+}
+
+/// The "fLaC" marker followed by the STREAMINFO block.
+fn stream_header(
+    channels: usize,
+    sample_rate: u32,
+    bits_per_sample: u32,
+    total_samples: usize,
+) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    for byte in b"fLaC" {
+        writer.write(*byte as u64, 8);
+    }
+
+    // Metadata block header: last block, STREAMINFO, 34 bytes long
+    writer.write(1, 1);
+    writer.write(0, 7);
+    writer.write(34, 24);
+
+    writer.write(BLOCK_SIZE as u64, 16);
+    writer.write(BLOCK_SIZE as u64, 16);
+    // Minimum and maximum frame sizes are unknown
+    writer.write(0, 24);
+    writer.write(0, 24);
+    writer.write(sample_rate as u64, 20);
+    writer.write(channels as u64 - 1, 3);
+    writer.write(bits_per_sample as u64 - 1, 5);
+    writer.write((total_samples as u64 >> 32) & 0xF, 4);
+    writer.write(total_samples as u64 & 0xFFFF_FFFF, 32);
+    // The MD5 signature is optional, zero means it wasn't computed
+    for _ in 0..4 {
+        writer.write(0, 32);
+    }
+
+    writer.into_bytes()
+}
+
+fn encode_frame(
+    frame_number: u64,
+    channels: &[&[i32]],
+    bits_per_sample: u32,
+    sample_size_code: u64,
+) -> Vec<u8> {
+    let block_size = channels[0].len();
+    let mut writer = BitWriter::new();
+
+    // Frame header
+    writer.write(0b11_1111_1111_1110, 14);
+    writer.write(0, 1);
+    // Fixed block size
+    writer.write(0, 1);
+    // Block size is stored as a 16-bit value at the end of the header
+    writer.write(0b0111, 4);
+    // Sample rate is taken from STREAMINFO
+    writer.write(0b0000, 4);
+    // Independent channels
+    writer.write(channels.len() as u64 - 1, 4);
+    writer.write(sample_size_code, 3);
+    writer.write(0, 1);
+    write_utf8_number(&mut writer, frame_number);
+    writer.write(block_size as u64 - 1, 16);
+    let header_crc = crc8(writer.bytes());
+    writer.write(header_crc as u64, 8);
+
+    for samples in channels {
+        encode_subframe(&mut writer, samples, bits_per_sample);
+    }
+
+    writer.align();
+    let frame_crc = crc16(writer.bytes());
+    writer.write(frame_crc as u64, 16);
+    writer.into_bytes()
+}
+
+fn encode_subframe(writer: &mut BitWriter, samples: &[i32], bits_per_sample: u32) {
+    // Silence and other constant blocks only need a single value
+    if samples.iter().all(|sample| *sample == samples[0]) {
+        writer.write(0, 1);
+        writer.write(0b000000, 6);
+        writer.write(0, 1);
+        writer.write_signed(samples[0] as i64, bits_per_sample);
+        return;
+    }
+
+    // Use the fixed predictor which leaves the smallest residual
+    let max_order = MAX_FIXED_ORDER.min(samples.len() - 1);
+    let (order, residual) = (0..=max_order)
+        .map(|order| (order, fixed_residual(samples, order)))
+        .min_by_key(|(_, residual)| residual.iter().map(|r| r.unsigned_abs()).sum::<u64>())
+        .unwrap();
+
+    writer.write(0, 1);
+    writer.write(0b001000 | order as u64, 6);
+    writer.write(0, 1);
+    for sample in &samples[..order] {
+        writer.write_signed(*sample as i64, bits_per_sample);
+    }
+    write_residual(writer, &residual);
+}
+
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |k: usize| samples[i - k] as i64;
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+fn write_residual(writer: &mut BitWriter, residual: &[i64]) {
+    // Rice coding with 5-bit parameters, in a single partition
+    writer.write(0b01, 2);
+    writer.write(0, 4);
+
+    // Fold the signed residual into unsigned values: 0, -1, 1, -2, 2...
+    let folded = residual
+        .iter()
+        .map(|r| ((r << 1) ^ (r >> 63)) as u64)
+        .collect::<Vec<_>>();
+    let parameter = best_rice_parameter(&folded);
+    writer.write(parameter as u64, 5);
+
+    for value in folded {
+        writer.write_unary(value >> parameter);
+        writer.write(value, parameter);
+    }
+}
+
+fn best_rice_parameter(folded: &[u64]) -> u32 {
+    (0..=MAX_RICE_PARAMETER)
+        .min_by_key(|parameter| {
+            folded
+                .iter()
+                .map(|value| (value >> parameter) + 1 + *parameter as u64)
+                .sum::<u64>()
+        })
+        .unwrap_or(0)
+}
+
+/// Write the frame number with the UTF-8 like coding used by FLAC.
+fn write_utf8_number(writer: &mut BitWriter, number: u64) {
+    if number < 0x80 {
+        writer.write(number, 8);
+        return;
+    }
+
+    // Each byte after the first carries 6 bits
+    let mut length = 2;
+    while number >> (5 * length + 1) != 0 {
+        length += 1;
+    }
+    let prefix = (0xFF00 >> length) & 0xFF;
+    writer.write(prefix | (number >> (6 * (length - 1))), 8);
+    for i in (0..length - 1).rev() {
+        writer.write(0x80 | ((number >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Writes values MSB first, as FLAC expects.
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            accumulator: 0,
+            pending_bits: 0,
+        }
+    }
+
+    /// Write the lowest `bits` bits of the value. `bits` must not exceed 32.
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        self.accumulator = (self.accumulator << bits) | (value & ((1 << bits) - 1));
+        self.pending_bits += bits;
+        while self.pending_bits >= 8 {
+            self.pending_bits -= 8;
+            self.bytes
+                .push((self.accumulator >> self.pending_bits) as u8);
+        }
+        self.accumulator &= (1 << self.pending_bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64, bits);
+    }
+
+    /// Write `value` zeros followed by a one.
+    fn write_unary(&mut self, mut value: u64) {
+        while value >= 32 {
+            self.write(0, 32);
+            value -= 32;
+        }
+        self.write(1, value as u32 + 1);
+    }
+
+    /// Pad with zeros up to the next byte boundary.
+    fn align(&mut self) {
+        if self.pending_bits > 0 {
+            self.write(0, 8 - self.pending_bits);
+        }
+    }
+
+    /// Bytes written so far, excluding any incomplete byte.
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_match_the_flac_polynomials() {
+        assert_eq!(crc8(b"123456789"), 0xF4);
+        assert_eq!(crc16(b"123456789"), 0xFEE8);
+    }
+
+    #[test]
+    fn bits_are_written_msb_first() {
+        let mut writer = BitWriter::new();
+        writer.write(0b101, 3);
+        writer.write(0b11111, 5);
+        writer.write_unary(3);
+        assert_eq!(writer.bytes(), &[0xBF]);
+        writer.write_signed(-1, 4);
+        assert_eq!(writer.into_bytes(), vec![0xBF, 0x1F]);
+    }
+
+    #[test]
+    fn frame_numbers_use_utf8_coding() {
+        let encode = |number| {
+            let mut writer = BitWriter::new();
+            write_utf8_number(&mut writer, number);
+            writer.into_bytes()
+        };
+        assert_eq!(encode(0x7F), vec![0x7F]);
+        assert_eq!(encode(0x80), vec![0xC2, 0x80]);
+        assert_eq!(encode(0x800), vec![0xE0, 0xA0, 0x80]);
+    }
+
+    #[test]
+    fn ramps_are_predicted_without_residual() {
+        let ramp = (0..16).map(|i| i * 3 - 20).collect::<Vec<_>>();
+        assert!(fixed_residual(&ramp, 2).iter().all(|r| *r == 0));
+        assert_eq!(fixed_residual(&ramp, 1), vec![3; 15]);
+    }
+
+    #[test]
+    fn stream_starts_with_the_marker_and_header() {
+        let path = std::env::temp_dir().join("knodiq_flac_test.flac");
+        let channels = vec![
+            (0..5000).map(|i| (i % 100) - 50).collect::<Vec<_>>(),
+            vec![0; 5000],
+        ];
+        write_flac(&path, &channels, 48000, 16).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..4], b"fLaC");
+        assert_eq!(&bytes[4..8], &[0x80, 0x00, 0x00, 34]);
+        // The first frame starts right after the 34 bytes of STREAMINFO
+        assert_eq!(&bytes[42..44], &[0xFF, 0xF8]);
+    }
+
+    #[test]
+    fn unsupported_streams_are_rejected() {
+        let path = std::env::temp_dir().join("knodiq_flac_rejected.flac");
+        assert!(write_flac(&path, &[], 48000, 16).is_err());
+        assert!(write_flac(&path, &[vec![0; 4]], 48000, 32).is_err());
+        assert!(!path.exists());
+    }
+}
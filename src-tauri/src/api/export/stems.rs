@@ -0,0 +1,259 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::data::{StemExportSettings, StemSource};
+use crate::api::export::audio_file::write_audio_file;
+use crate::api::export::export::{ExportProgress, render_range};
+use crate::api::mixing::cache::{MixCache, SegmentStream};
+use crate::api::mixing::console::Console;
+use crate::api::mixing::graph_processor::GraphProcessor;
+use crate::api::mixing::resample::resample;
+use crate::api::mixing::tempo::TempoMap;
+use knodiq_engine::{Mixer, Track};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, atomic::AtomicBool};
+use tauri::{AppHandle, Emitter};
+
+/// Render each track and bus track of the mixer to its own file.
+/// Bus tracks are rendered from the sends to them, so the stems sum up to the mix
+/// as long as the folders have no effects: their volume and pan are applied to the stems
+/// of the tracks inside them, but not their graphs.
+/// Returns `Ok(false)` if the export was cancelled.
+pub fn export_stems_to_files(
+    mut mixer: Mixer,
+    tempo_map: &TempoMap,
+    console: &Console,
+    master_track: Box<dyn Track>,
+    settings: &StemExportSettings,
+    should_stop: Arc<AtomicBool>,
+    app: &AppHandle,
+) -> Result<bool, String> {
+    let (start, end) = render_range(&mixer, tempo_map, settings.start, settings.end);
+
+    fs::create_dir_all(&settings.directory)
+        .map_err(|e| format!("Failed to create {}: {}", settings.directory, e))?;
+    mixer
+        .prepare()
+        .map_err(|e| format!("Error preparing mixer: {}", e))?;
+
+    // Folder tracks only sum the tracks inside them, so they get no stem
+    let tracks = mixer
+        .tracks
+        .iter()
+        .filter(|track| !console.is_folder(track.get_id()))
+        .filter(|track| settings.include_muted || console.is_audible(track.get_id()))
+        .map(|track| (track.get_id(), track.get_name().to_string()))
+        .collect::<Vec<_>>();
+    let track_names = tracks
+        .iter()
+        .map(|(_, name)| name.clone())
+        .collect::<Vec<_>>();
+    let paths = stem_paths(&track_names, settings);
+    // Each stem runs through its own master bus, so that no tail carries over to the next one
+    let mut masters = tracks
+        .iter()
+        .map(|_| match settings.source {
+            StemSource::PreMaster => None,
+            StemSource::PostMaster => Some(GraphProcessor::new(&mixer, master_track.clone())),
+        })
+        .collect::<Vec<_>>();
+
+    // Tracks which aren't heard are rendered as if they were, so their stems aren't silent
+    let mut console = console.clone();
+    if settings.include_muted {
+        console.clear_mute_and_solo();
+    }
+
+    // Tracks render the same way as for playback, so that the sidechains are fed
+    let cache = Arc::new(Mutex::new(MixCache::new(mixer.sample_rate)));
+    let mut stream = SegmentStream::new(
+        mixer,
+        master_track,
+        tempo_map.clone(),
+        cache,
+        Arc::new(Mutex::new(console)),
+        Arc::clone(&should_stop),
+    );
+    let sample_rate = stream.sample_rate();
+    let start_frame = stream.frame_at(start);
+    let end_frame = stream.frame_at(end).max(start_frame);
+
+    let mut stems = vec![vec![Vec::new(); stream.channels()]; tracks.len()];
+    let mut frame = start_frame;
+    while frame < end_frame {
+        let chunk_end = (frame + sample_rate).min(end_frame);
+        let Some(mut chunks) = stream.render_stems(frame, chunk_end) else {
+            return Ok(false);
+        };
+        for ((track_id, _), stem) in tracks.iter().zip(&mut stems) {
+            let Some(chunk) = chunks.remove(track_id) else {
+                continue;
+            };
+            for (buffer, chunk) in stem.iter_mut().zip(chunk) {
+                buffer.extend(chunk);
+            }
+        }
+        frame = chunk_end;
+
+        let progress = (frame - start_frame) as f32 / (end_frame - start_frame) as f32;
+        app.emit("export_progress", ExportProgress { progress })
+            .ok();
+    }
+
+    for ((buffers, path), master) in stems.into_iter().zip(paths).zip(&mut masters) {
+        let buffers = match master {
+            Some(master) => match master.process(buffers, start_frame, &should_stop) {
+                Some(buffers) => buffers,
+                None => return Ok(false),
+            },
+            None => buffers,
+        };
+        let buffers = resample(&buffers, sample_rate, settings.sample_rate as usize);
+        write_audio_file(
+            &path,
+            &buffers,
+            settings.sample_rate,
+            settings.bit_depth,
+            settings.format,
+        )?;
+    }
+
+    Ok(true)
+}
+
+/// File path of each stem, named after its track.
+/// Tracks with the same name get a number appended so that no stem overwrites another.
+fn stem_paths(track_names: &[String], settings: &StemExportSettings) -> Vec<PathBuf> {
+    let mut used_names = HashSet::new();
+    track_names
+        .iter()
+        .enumerate()
+        .map(|(index, track_name)| {
+            let mut base_name = sanitize_file_name(track_name);
+            if base_name.is_empty() {
+                base_name = format!("Track {}", index + 1);
+            }
+            if !settings.file_prefix.is_empty() {
+                base_name = format!(
+                    "{} - {}",
+                    sanitize_file_name(&settings.file_prefix),
+                    base_name
+                );
+            }
+            if settings.numbered {
+                base_name = format!("{:02} {}", index + 1, base_name);
+            }
+
+            let mut file_name = base_name.clone();
+            let mut duplicate = 2;
+            while !used_names.insert(file_name.to_lowercase()) {
+                file_name = format!("{} ({})", base_name, duplicate);
+                duplicate += 1;
+            }
+
+            // Track names may contain dots, which aren't an extension to replace
+            PathBuf::from(&settings.directory).join(format!(
+                "{}.{}",
+                file_name,
+                settings.format.extension()
+            ))
+        })
+        .collect()
+}
+
+/// Replace the characters which aren't allowed in file names on some platforms.
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::data::{BitDepth, ExportFormat};
+
+    fn settings(file_prefix: &str, numbered: bool) -> StemExportSettings {
+        StemExportSettings {
+            directory: "stems".to_string(),
+            file_prefix: file_prefix.to_string(),
+            numbered,
+            start: 0.0,
+            end: None,
+            sample_rate: 48000,
+            bit_depth: BitDepth::Int24,
+            format: ExportFormat::Wav,
+            source: StemSource::PreMaster,
+            include_muted: false,
+        }
+    }
+
+    fn file_names(track_names: &[&str], settings: &StemExportSettings) -> Vec<String> {
+        let track_names = track_names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        stem_paths(&track_names, settings)
+            .iter()
+            .map(|path| {
+                assert!(path.starts_with("stems"));
+                path.file_name().unwrap().to_string_lossy().into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn invalid_characters_are_replaced() {
+        assert_eq!(
+            sanitize_file_name("Drums/Kick: *Main*"),
+            "Drums_Kick_ _Main_"
+        );
+        assert_eq!(sanitize_file_name("  Bass\t"), "Bass_");
+        assert_eq!(sanitize_file_name("Vocals v1.2"), "Vocals v1.2");
+    }
+
+    #[test]
+    fn stems_are_named_after_their_tracks() {
+        let names = file_names(&["Drums", "Bass v1.2", " "], &settings("", false));
+        assert_eq!(names, vec!["Drums.wav", "Bass v1.2.wav", "Track 3.wav"]);
+    }
+
+    #[test]
+    fn duplicate_names_are_numbered() {
+        let names = file_names(&["Guitar", "guitar", "Guitar"], &settings("", false));
+        assert_eq!(
+            names,
+            vec!["Guitar.wav", "guitar (2).wav", "Guitar (3).wav"]
+        );
+    }
+
+    #[test]
+    fn prefix_and_numbers_are_added() {
+        let names = file_names(&["Drums", "Bass"], &settings("Song: Demo", true));
+        assert_eq!(
+            names,
+            vec!["01 Song_ Demo - Drums.wav", "02 Song_ Demo - Bass.wav"]
+        );
+    }
+}
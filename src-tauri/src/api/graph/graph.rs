@@ -17,7 +17,7 @@
 use crate::api::{
     AppState,
     data::NodeType,
-    mixing::{MixerCommand, MixerResult, send_mixer_command},
+    mixing::{MixerCommand, MixerResult, request_mixer_result, send_mixer_command},
 };
 use knodiq_engine::{NodeId, Value};
 use std::sync::Mutex;
@@ -53,6 +53,35 @@ pub fn disconnect_graph(
     );
 }
 
+#[command]
+pub fn connect_sidechain(
+    track_id: u32,
+    node_id: NodeId,
+    param: String,
+    source_track_id: u32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let command = MixerCommand::ConnectSidechain(track_id, node_id, param, source_track_id);
+    match request_mixer_result(command, &state)? {
+        MixerResult::RoutingChanged(result) => result,
+        _ => Err("Unexpected result type received.".to_string()),
+    }
+}
+
+#[command]
+pub fn disconnect_sidechain(
+    track_id: u32,
+    node_id: NodeId,
+    param: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let command = MixerCommand::DisconnectSidechain(track_id, node_id, param);
+    match request_mixer_result(command, &state)? {
+        MixerResult::RoutingChanged(result) => result,
+        _ => Err("Unexpected result type received.".to_string()),
+    }
+}
+
 #[command]
 pub fn add_node(
     track_id: u32,
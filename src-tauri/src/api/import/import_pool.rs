@@ -0,0 +1,151 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::MixerCommand;
+use crate::api::mixing::resample::resample_source;
+use knodiq_engine::AudioSource;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+/// Most audio files decoded at the same time.
+pub const MAX_IMPORT_WORKERS: usize = 4;
+
+/// Step an audio file being imported has reached.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ImportStage {
+    /// Waiting for a worker to be free.
+    Queued,
+    /// The file is being decoded.
+    Decoding,
+    /// The decoded audio is being converted to the sample rate of the project.
+    Resampling,
+    /// The audio has been given to the regions.
+    Finished,
+    /// The file couldn't be decoded.
+    Failed,
+}
+
+/// Payload of the `import_progress` event.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ImportProgress {
+    pub path: String,
+    pub track_index: usize,
+    pub stage: ImportStage,
+    /// Why the import failed, with the `Failed` stage.
+    pub error: Option<String>,
+}
+
+impl ImportProgress {
+    pub fn emit(app: &AppHandle, path: &str, track_index: usize, stage: ImportStage) {
+        send_progress(app, path, track_index, stage, None);
+    }
+
+    pub fn emit_failed(app: &AppHandle, path: &str, track_index: usize, error: String) {
+        send_progress(app, path, track_index, ImportStage::Failed, Some(error));
+    }
+}
+
+fn send_progress(
+    app: &AppHandle,
+    path: &str,
+    track_index: usize,
+    stage: ImportStage,
+    error: Option<String>,
+) {
+    let payload = ImportProgress {
+        path: path.to_string(),
+        track_index,
+        stage,
+        error,
+    };
+    app.emit("import_progress", payload).ok();
+}
+
+/// Audio file to be decoded by the import pool.
+pub struct ImportJob {
+    pub path: String,
+    pub track_index: usize,
+    /// Sample rate to convert the audio to.
+    pub sample_rate: usize,
+}
+
+/// Worker threads decoding the imported audio files, so that the mixer stays responsive.
+/// The decoded audio is sent back to the mixer with `MixerCommand::FinishImport`.
+pub struct ImportPool {
+    job_sender: Sender<ImportJob>,
+    app: AppHandle,
+}
+
+impl ImportPool {
+    /// Start the workers, as many as the processor can run at once up to `MAX_IMPORT_WORKERS`.
+    pub fn start(
+        command_sender: Sender<MixerCommand>,
+        app: AppHandle,
+    ) -> Result<Self, std::io::Error> {
+        let (job_sender, job_receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let workers = thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .min(MAX_IMPORT_WORKERS);
+        for index in 0..workers {
+            let job_receiver = Arc::clone(&job_receiver);
+            let command_sender = command_sender.clone();
+            let app = app.clone();
+            thread::Builder::new()
+                .name(format!("import_thread_{}", index))
+                .spawn(move || run_worker(&job_receiver, &command_sender, &app))?;
+        }
+        Ok(ImportPool { job_sender, app })
+    }
+
+    /// Queue the audio file to be decoded.
+    pub fn submit(&self, job: ImportJob) {
+        ImportProgress::emit(&self.app, &job.path, job.track_index, ImportStage::Queued);
+        if self.job_sender.send(job).is_err() {
+            eprintln!("The import workers have stopped.");
+        }
+    }
+}
+
+fn run_worker(
+    job_receiver: &Mutex<Receiver<ImportJob>>,
+    command_sender: &Sender<MixerCommand>,
+    app: &AppHandle,
+) {
+    loop {
+        // The lock is only held while waiting, so the other workers can take the next job
+        let job = match job_receiver.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+
+        ImportProgress::emit(app, &job.path, job.track_index, ImportStage::Decoding);
+        let result = AudioSource::from_path(&job.path, job.track_index)
+            .map_err(|e| format!("Failed to import {}: {}", job.path, e))
+            .map(|source| {
+                ImportProgress::emit(app, &job.path, job.track_index, ImportStage::Resampling);
+                resample_source(&source, job.sample_rate)
+            });
+
+        let command = MixerCommand::FinishImport(job.path, job.track_index, result);
+        if command_sender.send(command).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::{Beats, Track};
+
+/// Part of the timeline whose mix was changed by an edit, in musical beats.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DirtyRange {
+    /// The edit doesn't change the sound.
+    Nothing,
+    /// From the start beat to the end beat.
+    Range(Beats, Beats),
+    /// From the beat to the end of the project.
+    From(Beats),
+    /// The whole project.
+    All,
+}
+
+impl DirtyRange {
+    /// Range covering both ranges.
+    pub fn union(self, other: DirtyRange) -> DirtyRange {
+        match (self, other) {
+            (DirtyRange::Nothing, range) | (range, DirtyRange::Nothing) => range,
+            (DirtyRange::All, _) | (_, DirtyRange::All) => DirtyRange::All,
+            (DirtyRange::Range(start, end), DirtyRange::Range(other_start, other_end)) => {
+                DirtyRange::Range(start.min(other_start), end.max(other_end))
+            }
+            (DirtyRange::From(start), DirtyRange::Range(other_start, _))
+            | (DirtyRange::Range(other_start, _), DirtyRange::From(start))
+            | (DirtyRange::From(start), DirtyRange::From(other_start)) => {
+                DirtyRange::From(start.min(other_start))
+            }
+        }
+    }
+
+    /// Range of a region.
+    pub fn region(start: Beats, duration: Beats) -> DirtyRange {
+        DirtyRange::Range(start, start + duration)
+    }
+
+    /// Range affected by changing the whole track, e.g. its graph.
+    /// Nodes can keep sounding after the regions end, so it lasts until the end of the project.
+    pub fn track(track: &Box<dyn Track>) -> DirtyRange {
+        track
+            .regions()
+            .iter()
+            .map(|region| region.start_time())
+            .min_by(|a, b| a.total_cmp(b))
+            .map_or(DirtyRange::All, DirtyRange::From)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use knodiq_engine::mixing::region::BufferRegion;
+    use knodiq_engine::mixing::track::BufferTrack;
+
+    #[test]
+    fn union_covers_both_ranges() {
+        let range = DirtyRange::region(2.0, 2.0);
+        assert_eq!(range, DirtyRange::Range(2.0, 4.0));
+        assert_eq!(range.union(DirtyRange::Nothing), range);
+        assert_eq!(DirtyRange::Nothing.union(range), range);
+        assert_eq!(
+            range.union(DirtyRange::Range(1.0, 3.0)),
+            DirtyRange::Range(1.0, 4.0)
+        );
+        assert_eq!(range.union(DirtyRange::From(8.0)), DirtyRange::From(2.0));
+        assert_eq!(
+            DirtyRange::From(3.0).union(DirtyRange::From(5.0)),
+            DirtyRange::From(3.0)
+        );
+        assert_eq!(range.union(DirtyRange::All), DirtyRange::All);
+    }
+
+    #[test]
+    fn track_range_starts_at_its_first_region() {
+        let mut buffer_track = BufferTrack::new("Track", 2);
+        for start in [6.0, 2.0] {
+            let region = BufferRegion::empty("Region".to_string());
+            assert!(
+                buffer_track
+                    .add_region(Box::new(region), start, 1.0)
+                    .is_ok()
+            );
+        }
+        let track: Box<dyn Track> = Box::new(buffer_track);
+        assert_eq!(DirtyRange::track(&track), DirtyRange::From(2.0));
+
+        let empty: Box<dyn Track> = Box::new(BufferTrack::new("Empty", 2));
+        assert_eq!(DirtyRange::track(&empty), DirtyRange::All);
+    }
+}
@@ -0,0 +1,198 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::Sample;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Length of a cached segment, in seconds.
+const SEGMENT_SECONDS: usize = 2;
+
+/// Rendered output of each track, split into segments of equal length.
+///
+/// Segments are keyed by track ID and their index on the timeline of the mixer warped
+/// by the tempo map, and removed when an edit changes the part of the track they cover.
+/// The volume and pan of the tracks are applied when the segments are summed,
+/// and bus and folder tracks are run when mixing, so changing them keeps the cache.
+pub struct MixCache {
+    /// Number of frames in a segment.
+    segment_frames: usize,
+    /// Samples of each channel of the rendered segments, keyed by track ID and segment index.
+    segments: HashMap<(u32, usize), Arc<Vec<Vec<Sample>>>>,
+    /// Incremented on every invalidation, so that segments rendered from an outdated mixer
+    /// aren't stored.
+    generation: u64,
+    /// Generation of the last invalidation of every track.
+    cleared_generation: u64,
+    /// Generation of the last invalidation of each track, keyed by track ID.
+    track_generations: HashMap<u32, u64>,
+}
+
+impl MixCache {
+    pub fn new(sample_rate: usize) -> Self {
+        MixCache {
+            segment_frames: (sample_rate * SEGMENT_SECONDS).max(1),
+            segments: HashMap::new(),
+            generation: 0,
+            cleared_generation: 0,
+            track_generations: HashMap::new(),
+        }
+    }
+
+    pub fn segment_frames(&self) -> usize {
+        self.segment_frames
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn get(&self, track_id: u32, segment: usize) -> Option<Arc<Vec<Vec<Sample>>>> {
+        self.segments.get(&(track_id, segment)).cloned()
+    }
+
+    /// Store a rendered segment, unless the track was invalidated since `generation`.
+    pub fn insert(
+        &mut self,
+        track_id: u32,
+        segment: usize,
+        buffers: Arc<Vec<Vec<Sample>>>,
+        generation: u64,
+    ) {
+        let invalidated = self
+            .track_generations
+            .get(&track_id)
+            .copied()
+            .unwrap_or_default()
+            .max(self.cleared_generation);
+        if generation >= invalidated {
+            self.segments.insert((track_id, segment), buffers);
+        }
+    }
+
+    /// Remove the segments of the tracks overlapping the frames, or those of every track
+    /// if `track_ids` is `None`. `end_frame` is `None` for the end of the project.
+    pub fn invalidate(
+        &mut self,
+        track_ids: Option<&[u32]>,
+        start_frame: usize,
+        end_frame: Option<usize>,
+    ) {
+        let first_segment = start_frame / self.segment_frames;
+        let segment_frames = self.segment_frames;
+        self.segments.retain(|(track_id, segment), _| {
+            track_ids.is_some_and(|track_ids| !track_ids.contains(track_id))
+                || *segment < first_segment
+                || end_frame.is_some_and(|end_frame| segment * segment_frames >= end_frame)
+        });
+
+        self.generation += 1;
+        match track_ids {
+            Some(track_ids) => {
+                for track_id in track_ids {
+                    self.track_generations.insert(*track_id, self.generation);
+                }
+            }
+            None => {
+                self.cleared_generation = self.generation;
+                self.track_generations.clear();
+            }
+        }
+    }
+
+    /// Remove every segment, and change the segment length for the sample rate.
+    pub fn clear(&mut self, sample_rate: usize) {
+        self.segments.clear();
+        self.segment_frames = (sample_rate * SEGMENT_SECONDS).max(1);
+        self.generation += 1;
+        self.cleared_generation = self.generation;
+        self.track_generations.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A cache with segments of 16 frames.
+    fn cache() -> MixCache {
+        MixCache::new(8)
+    }
+
+    fn buffers() -> Arc<Vec<Vec<Sample>>> {
+        Arc::new(vec![vec![0.5; 16]])
+    }
+
+    fn cached(cache: &MixCache, track_id: u32) -> Vec<usize> {
+        (0..8)
+            .filter(|segment| cache.get(track_id, *segment).is_some())
+            .collect()
+    }
+
+    /// A cache holding 8 segments of each of the tracks 1 and 2.
+    fn filled_cache() -> MixCache {
+        let mut cache = cache();
+        for track_id in [1, 2] {
+            for segment in 0..8 {
+                cache.insert(track_id, segment, buffers(), cache.generation());
+            }
+        }
+        cache
+    }
+
+    #[test]
+    fn invalidation_removes_the_overlapping_segments() {
+        let mut cache = filled_cache();
+        cache.invalidate(None, 20, Some(48));
+        assert_eq!(cached(&cache, 1), vec![0, 3, 4, 5, 6, 7]);
+        cache.invalidate(None, 80, None);
+        assert_eq!(cached(&cache, 1), vec![0, 3, 4]);
+        assert_eq!(cached(&cache, 2), vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn invalidation_keeps_the_other_tracks() {
+        let mut cache = filled_cache();
+        cache.invalidate(Some(&[2][..]), 0, Some(32));
+        assert_eq!(cached(&cache, 1), (0..8).collect::<Vec<_>>());
+        assert_eq!(cached(&cache, 2), vec![2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn outdated_segments_of_the_invalidated_tracks_are_not_stored() {
+        let mut cache = cache();
+        let generation = cache.generation();
+        cache.invalidate(Some(&[1][..]), 0, None);
+        cache.insert(1, 0, buffers(), generation);
+        cache.insert(2, 0, buffers(), generation);
+        assert!(cache.get(1, 0).is_none());
+        assert!(cache.get(2, 0).is_some());
+
+        cache.invalidate(None, 0, None);
+        cache.insert(2, 0, buffers(), generation);
+        assert!(cache.get(2, 0).is_none());
+        cache.insert(1, 0, buffers(), cache.generation());
+        assert!(cache.get(1, 0).is_some());
+    }
+
+    #[test]
+    fn clearing_follows_the_sample_rate() {
+        let mut cache = filled_cache();
+        cache.clear(16);
+        assert!(cache.get(1, 0).is_none());
+        assert_eq!(cache.segment_frames(), 32);
+    }
+}
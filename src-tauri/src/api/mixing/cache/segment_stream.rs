@@ -0,0 +1,652 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::Sidechain;
+use crate::api::mixing::cache::MixCache;
+use crate::api::mixing::console::{Console, GainRamp};
+use crate::api::mixing::graph_processor::GraphProcessor;
+use crate::api::mixing::master_bus::MASTER_TRACK_ID;
+use crate::api::mixing::sidechain::feed_sidechain;
+use crate::api::mixing::tempo::TempoMap;
+use knodiq_engine::{Beats, Mixer, Sample, Track, Value};
+use std::collections::HashMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Number of frames summed between two reads of the console,
+/// so that volume changes are heard while playing.
+const CONSOLE_BLOCK_FRAMES: usize = 256;
+
+/// Range of frames summed with the same reading of the console.
+struct Block {
+    start: usize,
+    end: usize,
+    /// Destinations of each track, keyed by source track ID.
+    outputs: HashMap<u32, Vec<(u32, Vec<Sample>)>>,
+}
+
+/// Streams the mix of the project segment by segment, taking the segments of each track
+/// from the cache and rendering the missing ones.
+/// The tracks are summed with the settings of the console into the bus and folder tracks,
+/// and everything goes through the master bus at the end.
+/// The graphs of the buses and the master bus run continuously over the whole stream,
+/// so only the output of the tracks is cached.
+pub struct SegmentStream {
+    /// A mixer holding only the track, for each track playing regions with its ID.
+    tracks: Vec<(u32, Mixer)>,
+    /// Graph of each bus and folder track with its ID, in mixer order.
+    buses: Vec<(u32, GraphProcessor)>,
+    master: GraphProcessor,
+    /// Gains moving smoothly towards the console,
+    /// keyed by source track ID and destination (a bus track ID or `MASTER_TRACK_ID`).
+    ramps: HashMap<(u32, u32), GainRamp>,
+    cache: Arc<Mutex<MixCache>>,
+    console: Arc<Mutex<Console>>,
+    /// Converts the frames back to musical beats to read the automation.
+    tempo_map: TempoMap,
+    should_stop: Arc<AtomicBool>,
+    channels: usize,
+    sample_rate: usize,
+    tempo: f32,
+    samples_per_beat: f32,
+    duration: Beats,
+    segment_frames: usize,
+    /// Generation of the cache when the mixer was copied.
+    generation: u64,
+}
+
+impl SegmentStream {
+    /// `mixer` must be prepared before streaming.
+    pub fn new(
+        mut mixer: Mixer,
+        master_track: Box<dyn Track>,
+        tempo_map: TempoMap,
+        cache: Arc<Mutex<MixCache>>,
+        console: Arc<Mutex<Console>>,
+        should_stop: Arc<AtomicBool>,
+    ) -> Self {
+        let (segment_frames, generation) = {
+            let cache = cache.lock().unwrap();
+            (cache.segment_frames(), cache.generation())
+        };
+
+        // Render the tracks separately, they're summed with the settings of the console
+        let mut tracks = Vec::new();
+        let mut buses = Vec::new();
+        for track in std::mem::take(&mut mixer.tracks) {
+            let track_id = track.get_id();
+            if console.lock().unwrap().is_submix(track_id) {
+                buses.push((track_id, GraphProcessor::new(&mixer, track)));
+            } else {
+                let mut track_mixer = mixer.clone();
+                track_mixer.tracks.push(track);
+                tracks.push((track_id, track_mixer));
+            }
+        }
+        let master = GraphProcessor::new(&mixer, master_track);
+
+        SegmentStream {
+            tracks,
+            buses,
+            master,
+            ramps: HashMap::new(),
+            cache,
+            console,
+            tempo_map,
+            should_stop,
+            channels: mixer.channels.max(1),
+            sample_rate: mixer.sample_rate,
+            tempo: mixer.tempo,
+            samples_per_beat: mixer.samples_per_beat(),
+            duration: mixer.duration(),
+            segment_frames,
+            generation,
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    /// Frame at the beat of the mixer.
+    pub fn frame_at(&self, beat: Beats) -> usize {
+        (beat * self.samples_per_beat).round().max(0.0) as usize
+    }
+
+    /// Frame at the end of the project.
+    pub fn end_frame(&self) -> usize {
+        self.frame_at(self.duration)
+    }
+
+    /// Pass the interleaved samples from `start_frame` to `end_frame` to `on_sample`,
+    /// with the beat of the mixer they're at.
+    /// Returns `false` if the playback was stopped before reaching the end.
+    pub fn play(
+        &mut self,
+        start_frame: usize,
+        end_frame: usize,
+        on_sample: &mut dyn FnMut(Sample, Beats),
+    ) -> bool {
+        let has_graphs = !self.master.is_pass_through()
+            || self.buses.iter().any(|(_, bus)| !bus.is_pass_through());
+        let mut frame = start_frame;
+
+        while frame < end_frame {
+            let segment_end =
+                ((frame / self.segment_frames + 1) * self.segment_frames).min(end_frame);
+
+            // Running the graphs has a cost for each call, so they're run over whole segments.
+            // Otherwise each block is sent as soon as it's summed, so that console changes
+            // are heard early.
+            let chunk_end = match has_graphs {
+                true => segment_end,
+                false => (frame + CONSOLE_BLOCK_FRAMES).min(segment_end),
+            };
+            let Some(mixed) = self.mix(frame, chunk_end) else {
+                return false;
+            };
+            self.send(frame, &mixed, on_sample);
+            frame = chunk_end;
+
+            if self.should_stop.load(Ordering::Relaxed) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Render the stem of each track and bus track from `start_frame` to `end_frame`:
+    /// the audio it adds to the mix through its main output, with the volume and pan
+    /// of the folders it's in, but not their graphs. Bus tracks are fed by the sends to them,
+    /// so the stems sum up to the mix before the master bus when the folders have no effects.
+    /// Returns `None` if the rendering was stopped.
+    pub fn render_stems(
+        &mut self,
+        start_frame: usize,
+        end_frame: usize,
+    ) -> Option<HashMap<u32, Vec<Vec<Sample>>>> {
+        let frames = end_frame - start_frame;
+        let mut blocks = self.blocks(start_frame, end_frame);
+        let (bus_order, folder_gains) = {
+            let console = self.console.lock().unwrap();
+            let bus_ids = self
+                .buses
+                .iter()
+                .map(|(id, _)| *id)
+                .filter(|id| console.is_bus(*id))
+                .collect::<Vec<_>>();
+            // Combined volume and pan of the folders each source is in
+            let folder_gains = self
+                .tracks
+                .iter()
+                .map(|(id, _)| *id)
+                .chain(bus_ids.iter().copied())
+                .map(|id| {
+                    let mut gains = vec![1.0; self.channels];
+                    for folder_id in console.ancestors(id) {
+                        let folder = console.get(folder_id).channel_gains(self.channels, true);
+                        for (gain, folder_gain) in gains.iter_mut().zip(folder) {
+                            *gain *= folder_gain;
+                        }
+                    }
+                    (id, gains)
+                })
+                .collect::<HashMap<_, _>>();
+            (console.bus_order(&bus_ids), folder_gains)
+        };
+
+        // The output into a folder goes straight to the stem instead
+        for block in &mut blocks {
+            for (source_id, outputs) in block.outputs.iter_mut() {
+                let Some(folder_gains) = folder_gains.get(source_id) else {
+                    continue;
+                };
+                for (destination, gains) in outputs.iter_mut() {
+                    if *destination == MASTER_TRACK_ID || bus_order.contains(destination) {
+                        continue;
+                    }
+                    *destination = MASTER_TRACK_ID;
+                    for (gain, folder_gain) in gains.iter_mut().zip(folder_gains) {
+                        *gain *= folder_gain;
+                    }
+                }
+            }
+        }
+
+        let mut stems = HashMap::new();
+        let mut bus_inputs = bus_order
+            .iter()
+            .map(|id| (*id, vec![vec![0.0; frames]; self.channels]))
+            .collect::<HashMap<_, _>>();
+        for index in 0..self.tracks.len() {
+            let track_id = self.tracks[index].0;
+            let mut stem = vec![vec![0.0; frames]; self.channels];
+            if self.is_heard(track_id, &blocks) {
+                let source = self.track_range(index, start_frame, end_frame)?;
+                self.route(
+                    track_id,
+                    &source,
+                    start_frame,
+                    &blocks,
+                    &mut stem,
+                    &mut bus_inputs,
+                );
+            }
+            stems.insert(track_id, stem);
+        }
+
+        for bus_id in bus_order {
+            let Some(input) = bus_inputs.remove(&bus_id) else {
+                continue;
+            };
+            let output = self.process_bus(bus_id, input, start_frame, end_frame)?;
+            let mut stem = vec![vec![0.0; frames]; self.channels];
+            self.route(
+                bus_id,
+                &output,
+                start_frame,
+                &blocks,
+                &mut stem,
+                &mut bus_inputs,
+            );
+            stems.insert(bus_id, stem);
+        }
+        Some(stems)
+    }
+
+    /// Pass the buffers starting at `start_frame` to `on_sample`, interleaved.
+    fn send(
+        &self,
+        start_frame: usize,
+        buffers: &[Vec<Sample>],
+        on_sample: &mut dyn FnMut(Sample, Beats),
+    ) {
+        let frames = buffers.first().map_or(0, Vec::len);
+        for frame_index in 0..frames {
+            let beat = (start_frame + frame_index) as Beats / self.samples_per_beat;
+            for buffer in buffers {
+                on_sample(buffer[frame_index], beat);
+            }
+        }
+    }
+
+    /// Mix the frames from `start_frame` to `end_frame`, carrying on the graphs of the buses
+    /// and the master bus from the previous frames mixed.
+    /// Returns `None` if the playback was stopped while rendering.
+    fn mix(&mut self, start_frame: usize, end_frame: usize) -> Option<Vec<Vec<Sample>>> {
+        let frames = end_frame - start_frame;
+        let blocks = self.blocks(start_frame, end_frame);
+        let bus_ids = self.buses.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        let bus_order = self.console.lock().unwrap().bus_order(&bus_ids);
+
+        let mut master_input = vec![vec![0.0; frames]; self.channels];
+        let mut bus_inputs = bus_ids
+            .iter()
+            .map(|id| (*id, vec![vec![0.0; frames]; self.channels]))
+            .collect::<HashMap<_, _>>();
+
+        for index in 0..self.tracks.len() {
+            let track_id = self.tracks[index].0;
+            // Segments of the silent tracks are never rendered
+            if !self.is_heard(track_id, &blocks) {
+                continue;
+            }
+            let source = self.track_range(index, start_frame, end_frame)?;
+            self.route(
+                track_id,
+                &source,
+                start_frame,
+                &blocks,
+                &mut master_input,
+                &mut bus_inputs,
+            );
+        }
+
+        for bus_id in bus_order {
+            let Some(input) = bus_inputs.remove(&bus_id) else {
+                continue;
+            };
+            let output = self.process_bus(bus_id, input, start_frame, end_frame)?;
+            self.route(
+                bus_id,
+                &output,
+                start_frame,
+                &blocks,
+                &mut master_input,
+                &mut bus_inputs,
+            );
+        }
+
+        self.master
+            .process(master_input, start_frame, &self.should_stop)
+    }
+
+    /// Run the input of the bus or folder track through its graph,
+    /// with the sidechains routed into its nodes.
+    /// Returns `None` if the playback was stopped while rendering.
+    fn process_bus(
+        &mut self,
+        bus_id: u32,
+        input: Vec<Vec<Sample>>,
+        start_frame: usize,
+        end_frame: usize,
+    ) -> Option<Vec<Vec<Sample>>> {
+        let sidechains = self.sidechain_inputs(bus_id, start_frame, end_frame)?;
+        let Some(bus) = self.buses.iter_mut().find(|(id, _)| *id == bus_id) else {
+            return Some(input);
+        };
+        if let Some(graph) = bus.1.graph_mut() {
+            for (sidechain, channels) in sidechains {
+                feed_sidechain(graph, &sidechain, channels);
+            }
+        }
+        bus.1.process(input, start_frame, &self.should_stop)
+    }
+
+    /// Split the frames into blocks, and read the console for each of them.
+    fn blocks(&self, start_frame: usize, end_frame: usize) -> Vec<Block> {
+        let mut ranges = Vec::new();
+        let mut frame = start_frame;
+        while frame < end_frame {
+            let block_end = (frame + CONSOLE_BLOCK_FRAMES).min(end_frame);
+            ranges.push((frame, block_end));
+            frame = block_end;
+        }
+
+        let console = self.console.lock().unwrap();
+        let source_ids = self
+            .tracks
+            .iter()
+            .map(|(id, _)| *id)
+            .chain(self.buses.iter().map(|(id, _)| *id))
+            .collect::<Vec<_>>();
+        ranges
+            .into_iter()
+            .map(|(start, end)| {
+                let linear_beat = start as Beats / self.samples_per_beat;
+                let beat = self.tempo_map.from_linear(self.tempo, linear_beat);
+                let outputs = source_ids
+                    .iter()
+                    .map(|id| (*id, console.outputs(*id, beat, self.channels)))
+                    .collect();
+                Block {
+                    start,
+                    end,
+                    outputs,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether any output of the track is heard during the blocks.
+    fn is_heard(&self, track_id: u32, blocks: &[Block]) -> bool {
+        blocks.iter().any(|block| {
+            block.outputs.get(&track_id).is_some_and(|outputs| {
+                outputs.iter().any(|(destination, target)| {
+                    match self.ramps.get(&(track_id, *destination)) {
+                        Some(ramp) => !ramp.is_silent(target),
+                        None => target.iter().any(|gain| *gain != 0.0),
+                    }
+                })
+            })
+        })
+    }
+
+    /// Add the source, starting at `start_frame`, to the master bus and the buses
+    /// it's sent to, with the gains read for each block.
+    fn route(
+        &mut self,
+        source_id: u32,
+        source: &[Vec<Sample>],
+        start_frame: usize,
+        blocks: &[Block],
+        master_input: &mut [Vec<Sample>],
+        bus_inputs: &mut HashMap<u32, Vec<Vec<Sample>>>,
+    ) {
+        for block in blocks {
+            let Some(outputs) = block.outputs.get(&source_id) else {
+                continue;
+            };
+            let from = block.start - start_frame;
+            let to = block.end - start_frame;
+
+            for (destination, target) in outputs {
+                let input = match *destination {
+                    MASTER_TRACK_ID => &mut *master_input,
+                    bus_id => match bus_inputs.get_mut(&bus_id) {
+                        Some(input) => input.as_mut_slice(),
+                        None => continue,
+                    },
+                };
+                let ramp = self
+                    .ramps
+                    .entry((source_id, *destination))
+                    .or_insert_with(|| GainRamp::new(target.clone(), self.sample_rate));
+
+                for frame in from..to {
+                    let gains = ramp.next(target);
+                    for ((input, source), gain) in input.iter_mut().zip(source).zip(gains) {
+                        input[frame] += source[frame] * gain;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the frames of the track from `start_frame` to `end_frame`, from the cached segments.
+    /// Returns `None` if the playback was stopped while rendering.
+    fn track_range(
+        &mut self,
+        index: usize,
+        start_frame: usize,
+        end_frame: usize,
+    ) -> Option<Vec<Vec<Sample>>> {
+        self.convert_track_range(index, start_frame, end_frame, |sample| sample)
+    }
+
+    /// Get the frames of the track like `track_range`, converting each sample on the way.
+    fn convert_track_range<T>(
+        &mut self,
+        index: usize,
+        start_frame: usize,
+        end_frame: usize,
+        convert: impl Fn(Sample) -> T,
+    ) -> Option<Vec<Vec<T>>> {
+        let mut buffers = (0..self.channels)
+            .map(|_| Vec::with_capacity(end_frame - start_frame))
+            .collect::<Vec<_>>();
+        let mut frame = start_frame;
+        while frame < end_frame {
+            let segment = frame / self.segment_frames;
+            let segment_start = segment * self.segment_frames;
+            let segment_end = (segment_start + self.segment_frames).min(end_frame);
+            let segment_buffers = self.track_segment(index, segment)?;
+            for (buffer, segment_buffer) in buffers.iter_mut().zip(segment_buffers.iter()) {
+                buffer.extend(
+                    segment_buffer[frame - segment_start..segment_end - segment_start]
+                        .iter()
+                        .map(|sample| convert(*sample)),
+                );
+            }
+            frame = segment_end;
+        }
+        Some(buffers)
+    }
+
+    /// Get the segment of the track from the cache, or render it.
+    /// Returns `None` if the playback was stopped while rendering.
+    fn track_segment(&mut self, index: usize, segment: usize) -> Option<Arc<Vec<Vec<Sample>>>> {
+        let track_id = self.tracks[index].0;
+        if let Some(buffers) = self.cache.lock().unwrap().get(track_id, segment) {
+            return Some(buffers);
+        }
+
+        let buffers = Arc::new(self.render_segment(index, segment)?);
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(track_id, segment, Arc::clone(&buffers), self.generation);
+        Some(buffers)
+    }
+
+    /// Get the output of the tracks routed into the nodes of the track,
+    /// from `start_frame` to `end_frame`, as the values given to the nodes.
+    /// Returns `None` if the playback was stopped while rendering.
+    fn sidechain_inputs(
+        &mut self,
+        track_id: u32,
+        start_frame: usize,
+        end_frame: usize,
+    ) -> Option<Vec<(Sidechain, Vec<Value>)>> {
+        let sidechains = self.console.lock().unwrap().sidechains(track_id).to_vec();
+        let mut inputs = Vec::<(Sidechain, Vec<Value>)>::with_capacity(sidechains.len());
+        for sidechain in sidechains {
+            // The output of a source feeding several nodes is only converted once
+            if let Some((_, channels)) = inputs
+                .iter()
+                .find(|(other, _)| other.source_id == sidechain.source_id)
+            {
+                let channels = channels.clone();
+                inputs.push((sidechain, channels));
+                continue;
+            }
+            let Some(source_index) = self
+                .tracks
+                .iter()
+                .position(|(id, _)| *id == sidechain.source_id)
+            else {
+                continue;
+            };
+            // Converted straight from the cached segments, without copying the samples first
+            let channels = self
+                .convert_track_range(source_index, start_frame, end_frame, Value::Float)?
+                .into_iter()
+                .map(Value::Array)
+                .collect();
+            inputs.push((sidechain, channels));
+        }
+        Some(inputs)
+    }
+
+    fn render_segment(&mut self, index: usize, segment: usize) -> Option<Vec<Vec<Sample>>> {
+        let channels = self.channels;
+        let segment_start = segment * self.segment_frames;
+
+        // The sources of the sidechains are rendered first, the console rejects cycles
+        let track_id = self.tracks[index].0;
+        let sidechains =
+            self.sidechain_inputs(track_id, segment_start, segment_start + self.segment_frames)?;
+        if let Some(track) = self.tracks[index].1.tracks.first_mut() {
+            for (sidechain, channels) in sidechains {
+                feed_sidechain(track.graph_mut(), &sidechain, channels);
+            }
+        }
+        let total_samples = self.segment_frames * channels;
+
+        let samples = Arc::new(Mutex::new(Vec::with_capacity(total_samples)));
+        let samples_clone = Arc::clone(&samples);
+        let should_stop = Arc::clone(&self.should_stop);
+        let mix_callback = Box::new(move |sample: Sample, _current_beat: Beats| {
+            if should_stop.load(Ordering::Relaxed) {
+                return false;
+            }
+            let mut samples = samples_clone.lock().unwrap();
+            samples.push(sample);
+            samples.len() < total_samples
+        });
+
+        let start_beat = segment_start as Beats / self.samples_per_beat;
+        self.tracks[index].1.mix(start_beat, mix_callback);
+        if self.should_stop.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        // Anything after the end of the track stays silent
+        let samples = samples.lock().unwrap();
+        let mut buffers = vec![vec![0.0; self.segment_frames]; channels];
+        for (index, sample) in samples.iter().enumerate() {
+            buffers[index % channels][index / channels] = *sample;
+        }
+        Some(buffers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::master_bus::create_master_track;
+    use crate::api::mixing::mixer::create_track;
+    use crate::api::{TrackData, TrackSend, TrackType};
+
+    #[test]
+    fn buses_get_a_stem_but_folders_dont() {
+        let mut mixer = Mixer::new(60.0, 8, 1);
+        let mut console = Console::new();
+        for track_type in [
+            TrackType::BufferTrack,
+            TrackType::BusTrack,
+            TrackType::FolderTrack,
+        ] {
+            mixer.add_track(create_track(&TrackData {
+                name: "Track".to_string(),
+                channels: 1,
+                track_type,
+            }));
+        }
+        let ids = mixer
+            .tracks
+            .iter()
+            .map(|track| track.get_id())
+            .collect::<Vec<_>>();
+        console.set_track_type(ids[1], &TrackType::BusTrack);
+        console.set_track_type(ids[2], &TrackType::FolderTrack);
+        console.set_sends(ids[0], vec![TrackSend::new(ids[1], 0.0, false)]);
+        console.set_parent(ids[1], Some(ids[2]));
+        // The bus passes its input through, like the master bus
+        for track in mixer.tracks.iter_mut().skip(1) {
+            let input_node = track.graph().get_input_node_id();
+            let output_node = track.graph().get_output_node_id();
+            track.graph_mut().connect(
+                input_node,
+                "audio".to_string(),
+                output_node,
+                "audio".to_string(),
+            );
+        }
+        assert!(mixer.prepare().is_ok());
+
+        let cache = Arc::new(Mutex::new(MixCache::new(mixer.sample_rate)));
+        let mut stream = SegmentStream::new(
+            mixer,
+            create_master_track(1),
+            TempoMap::new(),
+            cache,
+            Arc::new(Mutex::new(console)),
+            Arc::new(AtomicBool::new(false)),
+        );
+        let stems = stream.render_stems(0, 12).unwrap();
+        let mut stem_ids = stems.keys().copied().collect::<Vec<_>>();
+        stem_ids.sort();
+        assert_eq!(stem_ids, vec![ids[0], ids[1]]);
+        assert!(stems.values().all(|stem| *stem == vec![vec![0.0; 12]]));
+    }
+}
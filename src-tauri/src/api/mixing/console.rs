@@ -0,0 +1,704 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::data::track_mix::db_to_gain;
+use crate::api::mixing::master_bus::MASTER_TRACK_ID;
+use crate::api::{Sidechain, TrackMix, TrackSend, TrackType};
+use knodiq_engine::{Beats, Sample, Track};
+use std::collections::{HashMap, HashSet};
+
+/// Time it takes a gain change to reach about two thirds of the way, in seconds.
+/// Changing the gain at once would make the audio click ("zipper noise").
+const GAIN_SMOOTHING_SECONDS: f32 = 0.01;
+
+/// Mixing console settings of every track, applied when the tracks are summed.
+#[derive(Clone, Default)]
+pub struct Console {
+    /// Settings of each track, keyed by track ID. Tracks not in the map use the defaults.
+    mixes: HashMap<u32, TrackMix>,
+    /// Sends of each track to the bus tracks, keyed by track ID.
+    sends: HashMap<u32, Vec<TrackSend>>,
+    /// IDs of the bus tracks. They're buffer tracks to the engine.
+    buses: HashSet<u32>,
+    /// Outputs of other tracks routed into the nodes of each track, keyed by track ID.
+    sidechains: HashMap<u32, Vec<Sidechain>>,
+    /// IDs of the folder tracks. They're buffer tracks to the engine too.
+    folders: HashSet<u32>,
+    /// Folder of each track inside one, keyed by track ID.
+    parents: HashMap<u32, u32>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            mixes: HashMap::new(),
+            sends: HashMap::new(),
+            buses: HashSet::new(),
+            sidechains: HashMap::new(),
+            folders: HashSet::new(),
+            parents: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, track_id: u32) -> TrackMix {
+        self.mixes.get(&track_id).copied().unwrap_or_default()
+    }
+
+    /// Change the settings of a track and return the previous ones.
+    pub fn set(&mut self, track_id: u32, mix: TrackMix) -> TrackMix {
+        self.mixes.insert(track_id, mix).unwrap_or_default()
+    }
+
+    pub fn remove(&mut self, track_id: u32) -> Option<TrackMix> {
+        self.mixes.remove(&track_id)
+    }
+
+    pub fn is_bus(&self, track_id: u32) -> bool {
+        self.buses.contains(&track_id)
+    }
+
+    /// Mark the track as a bus track, or as a regular one.
+    pub fn set_bus(&mut self, track_id: u32, is_bus: bool) {
+        match is_bus {
+            true => self.buses.insert(track_id),
+            false => self.buses.remove(&track_id),
+        };
+    }
+
+    pub fn is_folder(&self, track_id: u32) -> bool {
+        self.folders.contains(&track_id)
+    }
+
+    /// Mark the track as a folder track, or as a regular one.
+    pub fn set_folder(&mut self, track_id: u32, is_folder: bool) {
+        match is_folder {
+            true => self.folders.insert(track_id),
+            false => self.folders.remove(&track_id),
+        };
+    }
+
+    /// Type of the track shown to the user. Bus and folder tracks are buffer tracks to the engine.
+    /// Returns `None` if the engine reports a type the app doesn't know.
+    pub fn track_type(&self, track: &dyn Track) -> Option<TrackType> {
+        let track_id = track.get_id();
+        if self.is_bus(track_id) {
+            Some(TrackType::BusTrack)
+        } else if self.is_folder(track_id) {
+            Some(TrackType::FolderTrack)
+        } else {
+            TrackType::from_name(track.track_type().as_str())
+        }
+    }
+
+    /// Mark the track as a bus or folder track if the type is one of them.
+    pub fn set_track_type(&mut self, track_id: u32, track_type: &TrackType) {
+        self.set_bus(track_id, matches!(track_type, TrackType::BusTrack));
+        self.set_folder(track_id, matches!(track_type, TrackType::FolderTrack));
+    }
+
+    /// Whether the track sums other tracks instead of playing regions.
+    pub fn is_submix(&self, track_id: u32) -> bool {
+        self.is_bus(track_id) || self.is_folder(track_id)
+    }
+
+    /// Folder the track is in, if it's in one.
+    /// Tracks whose folder was removed are back at the top level.
+    pub fn parent(&self, track_id: u32) -> Option<u32> {
+        self.parents
+            .get(&track_id)
+            .copied()
+            .filter(|parent_id| self.is_folder(*parent_id))
+    }
+
+    /// Move the track into a folder, or out of it with `None`.
+    /// Returns the previous folder, even if it was removed since.
+    pub fn set_parent(&mut self, track_id: u32, parent_id: Option<u32>) -> Option<u32> {
+        match parent_id {
+            Some(parent_id) => self.parents.insert(track_id, parent_id),
+            None => self.parents.remove(&track_id),
+        }
+    }
+
+    /// Check that the track can be moved into the folder without containing itself.
+    pub fn validate_parent(&self, track_id: u32, parent_id: u32) -> Result<(), String> {
+        if !self.is_folder(parent_id) {
+            return Err(format!(
+                "Track with ID {} is not a folder track.",
+                parent_id
+            ));
+        }
+        if parent_id == track_id || self.feeds(parent_id, track_id) {
+            return Err("The folder would contain itself.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Where the main output of the track goes: its folder, or the master bus.
+    pub fn output_of(&self, track_id: u32) -> u32 {
+        self.parent(track_id).unwrap_or(MASTER_TRACK_ID)
+    }
+
+    /// The folders the track is in, from the innermost one.
+    pub fn ancestors(&self, track_id: u32) -> Vec<u32> {
+        let mut ancestors = Vec::new();
+        let mut current = track_id;
+        while let Some(parent_id) = self.parent(current) {
+            if ancestors.contains(&parent_id) {
+                break;
+            }
+            ancestors.push(parent_id);
+            current = parent_id;
+        }
+        ancestors
+    }
+
+    pub fn sends(&self, track_id: u32) -> &[TrackSend] {
+        self.sends.get(&track_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replace the sends of a track and return the previous ones.
+    pub fn set_sends(&mut self, track_id: u32, sends: Vec<TrackSend>) -> Vec<TrackSend> {
+        let previous = match sends.is_empty() {
+            true => self.sends.remove(&track_id),
+            false => self.sends.insert(track_id, sends),
+        };
+        previous.unwrap_or_default()
+    }
+
+    pub fn sidechains(&self, track_id: u32) -> &[Sidechain] {
+        self.sidechains.get(&track_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replace the sidechains routed into the nodes of a track and return the previous ones.
+    pub fn set_sidechains(&mut self, track_id: u32, sidechains: Vec<Sidechain>) -> Vec<Sidechain> {
+        let previous = match sidechains.is_empty() {
+            true => self.sidechains.remove(&track_id),
+            false => self.sidechains.insert(track_id, sidechains),
+        };
+        previous.unwrap_or_default()
+    }
+
+    /// Move the tracks in the folder out of it. Returns the IDs of the tracks.
+    pub fn remove_children(&mut self, folder_id: u32) -> Vec<u32> {
+        let children = self
+            .parents
+            .iter()
+            .filter(|(_, parent_id)| **parent_id == folder_id)
+            .map(|(track_id, _)| *track_id)
+            .collect::<Vec<_>>();
+        for track_id in &children {
+            self.parents.remove(track_id);
+        }
+        children
+    }
+
+    /// Remove the sends of every track to the bus.
+    /// Returns the previous sends of the tracks which had one, keyed by track ID.
+    pub fn remove_sends_to(&mut self, bus_id: u32) -> Vec<(u32, Vec<TrackSend>)> {
+        let track_ids = self
+            .sends
+            .iter()
+            .filter(|(_, sends)| sends.iter().any(|send| send.bus_id == bus_id))
+            .map(|(track_id, _)| *track_id)
+            .collect::<Vec<_>>();
+        track_ids
+            .into_iter()
+            .map(|track_id| {
+                let mut sends = self.sends(track_id).to_vec();
+                sends.retain(|send| send.bus_id != bus_id);
+                (track_id, self.set_sends(track_id, sends))
+            })
+            .collect()
+    }
+
+    /// Remove the sidechains of every track taking the output of the source track.
+    /// Returns the previous sidechains of the tracks which had one, keyed by track ID.
+    pub fn remove_sidechains_from(&mut self, source_id: u32) -> Vec<(u32, Vec<Sidechain>)> {
+        let track_ids = self
+            .sidechains
+            .iter()
+            .filter(|(_, sidechains)| {
+                sidechains
+                    .iter()
+                    .any(|sidechain| sidechain.source_id == source_id)
+            })
+            .map(|(track_id, _)| *track_id)
+            .collect::<Vec<_>>();
+        track_ids
+            .into_iter()
+            .map(|track_id| {
+                let mut sidechains = self.sidechains(track_id).to_vec();
+                sidechains.retain(|sidechain| sidechain.source_id != source_id);
+                (track_id, self.set_sidechains(track_id, sidechains))
+            })
+            .collect()
+    }
+
+    /// Check that the output of the source track can be routed into the track
+    /// without feeding back into the source.
+    pub fn validate_sidechain(&self, track_id: u32, source_id: u32) -> Result<(), String> {
+        if self.is_submix(source_id) {
+            return Err("Bus and folder tracks can't be used as a sidechain source.".to_string());
+        }
+        if source_id == track_id || self.feeds(track_id, source_id) {
+            return Err("The sidechain would feed the track back into itself.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Check that the track can send to the bus without feeding back into itself.
+    pub fn validate_send(&self, track_id: u32, bus_id: u32) -> Result<(), String> {
+        if !self.is_bus(bus_id) {
+            return Err(format!("Track with ID {} is not a bus track.", bus_id));
+        }
+        if self.feeds(bus_id, track_id) {
+            return Err("The send would feed the bus back into itself.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether the audio of `from` reaches `to` through the folders, the sends and the sidechains.
+    pub fn feeds(&self, from: u32, to: u32) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending = vec![from];
+        while let Some(track_id) = pending.pop() {
+            if track_id == to {
+                return true;
+            }
+            if visited.insert(track_id) {
+                pending.push(self.output_of(track_id));
+                pending.extend(self.sends(track_id).iter().map(|send| send.bus_id));
+                pending.extend(
+                    self.sidechains
+                        .iter()
+                        .filter(|(_, sidechains)| {
+                            sidechains
+                                .iter()
+                                .any(|sidechain| sidechain.source_id == track_id)
+                        })
+                        .map(|(destination, _)| *destination),
+                );
+            }
+        }
+        false
+    }
+
+    /// Order the bus and folder tracks so that each one comes after every other one
+    /// sending to it or inside it.
+    pub fn bus_order(&self, bus_ids: &[u32]) -> Vec<u32> {
+        let mut order = Vec::with_capacity(bus_ids.len());
+        let mut visited = HashSet::new();
+        for bus_id in bus_ids {
+            self.visit_bus(*bus_id, bus_ids, &mut visited, &mut order);
+        }
+        order
+    }
+
+    fn visit_bus(
+        &self,
+        bus_id: u32,
+        bus_ids: &[u32],
+        visited: &mut HashSet<u32>,
+        order: &mut Vec<u32>,
+    ) {
+        if !visited.insert(bus_id) {
+            return;
+        }
+        for source_id in bus_ids {
+            if self.output_of(*source_id) == bus_id
+                || self
+                    .sends(*source_id)
+                    .iter()
+                    .any(|send| send.bus_id == bus_id)
+            {
+                self.visit_bus(*source_id, bus_ids, visited, order);
+            }
+        }
+        order.push(bus_id);
+    }
+
+    /// Whether the track is heard in the mix.
+    /// A track is never heard when it or a folder it's in is muted. While any track is soloed,
+    /// only the soloed and the solo-safe tracks and the tracks in a soloed folder are heard,
+    /// together with the buses and folders the soloed tracks reach.
+    pub fn is_audible(&self, track_id: u32) -> bool {
+        let group = std::iter::once(track_id)
+            .chain(self.ancestors(track_id))
+            .map(|id| self.get(id))
+            .collect::<Vec<_>>();
+        if group.iter().any(|mix| mix.mute) {
+            return false;
+        }
+        let soloed = self
+            .mixes
+            .iter()
+            .filter(|(_, mix)| mix.solo)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        soloed.is_empty()
+            || group.iter().any(|mix| mix.solo || mix.solo_safe)
+            || (self.is_submix(track_id)
+                && soloed
+                    .iter()
+                    .any(|soloed_id| self.feeds(*soloed_id, track_id)))
+    }
+
+    /// Turn off the mute and the solo of every track, so that every track is heard.
+    pub fn clear_mute_and_solo(&mut self) {
+        for mix in self.mixes.values_mut() {
+            mix.mute = false;
+            mix.solo = false;
+        }
+    }
+
+    /// Gain of each output channel of the track.
+    pub fn channel_gains(&self, track_id: u32, channels: usize) -> Vec<Sample> {
+        self.get(track_id)
+            .channel_gains(channels, self.is_audible(track_id))
+    }
+
+    /// Where the output of the track goes at the beat: its folder or the master bus
+    /// (`MASTER_TRACK_ID`), and the buses it sends to, with the gain of each output channel.
+    pub fn outputs(&self, track_id: u32, beat: Beats, channels: usize) -> Vec<(u32, Vec<Sample>)> {
+        let audible = self.is_audible(track_id);
+        let fader = self.get(track_id).channel_gains(channels, audible);
+
+        let mut outputs = Vec::with_capacity(self.sends(track_id).len() + 1);
+        for send in self.sends(track_id) {
+            if !self.is_bus(send.bus_id) {
+                continue;
+            }
+            let level = db_to_gain(send.level_at(beat));
+            let gains = match send.pre_fader {
+                true => vec![if audible { level } else { 0.0 }; channels],
+                false => fader.iter().map(|gain| gain * level).collect(),
+            };
+            outputs.push((send.bus_id, gains));
+        }
+        outputs.push((self.output_of(track_id), fader));
+        outputs
+    }
+}
+
+/// Gains of a track moving smoothly towards the values set on the console.
+pub struct GainRamp {
+    current: Vec<Sample>,
+    /// Fraction of the distance to the target kept after each frame.
+    coefficient: Sample,
+}
+
+impl GainRamp {
+    /// Start at the target gains, so that the playback doesn't fade in.
+    pub fn new(target: Vec<Sample>, sample_rate: usize) -> Self {
+        GainRamp {
+            current: target,
+            coefficient: (-1.0 / (GAIN_SMOOTHING_SECONDS * sample_rate.max(1) as f32)).exp(),
+        }
+    }
+
+    /// Move one frame closer to the target and return the gains for the frame.
+    pub fn next(&mut self, target: &[Sample]) -> &[Sample] {
+        for (current, target) in self.current.iter_mut().zip(target) {
+            *current = target + (*current - target) * self.coefficient;
+        }
+        &self.current
+    }
+
+    /// Whether the gains reached silence, so the track can be skipped.
+    pub fn is_silent(&self, target: &[Sample]) -> bool {
+        self.current
+            .iter()
+            .chain(target)
+            .all(|gain| gain.abs() < 1e-6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Automation, AutomationPoint};
+    use knodiq_engine::Track;
+    use knodiq_engine::mixing::track::BufferTrack;
+
+    fn set_mix(console: &mut Console, track_id: u32, change: impl Fn(&mut TrackMix)) {
+        let mut mix = console.get(track_id);
+        change(&mut mix);
+        console.set(track_id, mix);
+    }
+
+    fn sidechain(source_id: u32) -> Sidechain {
+        Sidechain {
+            source_id,
+            node_id: BufferTrack::new("Track", 2).graph().get_input_node_id(),
+            param: "sidechain".to_string(),
+        }
+    }
+
+    fn assert_gains(actual: Vec<Sample>, expected: &[Sample]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-4,
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn muted_tracks_are_silent() {
+        let mut console = Console::new();
+        assert!(console.is_audible(1));
+        assert!(console.is_audible(2));
+
+        set_mix(&mut console, 2, |mix| mix.mute = true);
+        assert!(console.is_audible(1));
+        assert!(!console.is_audible(2));
+        assert_gains(console.channel_gains(2, 2), &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn solo_silences_the_other_tracks() {
+        let mut console = Console::new();
+        set_mix(&mut console, 1, |mix| mix.solo = true);
+        set_mix(&mut console, 3, |mix| mix.solo_safe = true);
+        assert!(console.is_audible(1));
+        assert!(!console.is_audible(2));
+        assert!(console.is_audible(3));
+
+        // Mute wins over solo
+        set_mix(&mut console, 1, |mix| mix.mute = true);
+        assert!(!console.is_audible(1));
+    }
+
+    #[test]
+    fn pan_balances_the_stereo_channels() {
+        let mut console = Console::new();
+        assert_gains(console.channel_gains(1, 2), &[1.0, 1.0]);
+        assert_gains(console.channel_gains(1, 1), &[1.0]);
+
+        set_mix(&mut console, 1, |mix| mix.pan = 1.0);
+        assert_gains(console.channel_gains(1, 2), &[0.0, 1.0]);
+        set_mix(&mut console, 1, |mix| {
+            mix.pan = -0.5;
+            mix.volume = -6.0;
+        });
+        let gain = 10.0_f32.powf(-6.0 / 20.0);
+        let right = (0.5 * std::f32::consts::FRAC_PI_2).cos();
+        assert_gains(console.channel_gains(1, 2), &[gain, gain * right]);
+    }
+
+    #[test]
+    fn gain_ramp_moves_smoothly_to_the_target() {
+        let mut ramp = GainRamp::new(vec![1.0, 1.0], 48000);
+        let first = ramp.next(&[0.0, 0.0])[0];
+        assert!(first > 0.99 && first < 1.0, "got {}", first);
+        assert!(!ramp.is_silent(&[0.0, 0.0]));
+
+        // A second is a hundred times the smoothing time
+        for _ in 0..48000 {
+            ramp.next(&[0.0, 0.0]);
+        }
+        assert!(ramp.is_silent(&[0.0, 0.0]));
+    }
+
+    #[test]
+    fn solo_keeps_the_buses_it_reaches() {
+        let mut console = Console::new();
+        console.set_bus(20, true);
+        console.set_bus(21, true);
+        console.set_sends(1, vec![TrackSend::new(20, 0.0, false)]);
+        set_mix(&mut console, 1, |mix| mix.solo = true);
+
+        assert!(console.is_audible(20));
+        assert!(!console.is_audible(21));
+        assert!(!console.is_audible(2));
+    }
+
+    #[test]
+    fn send_cycles_are_rejected() {
+        let mut console = Console::new();
+        console.set_bus(20, true);
+        console.set_bus(21, true);
+        console.set_sends(20, vec![TrackSend::new(21, 0.0, false)]);
+
+        assert!(console.feeds(20, 21));
+        assert!(!console.feeds(21, 20));
+        assert!(console.validate_send(1, 20).is_ok());
+        assert!(console.validate_send(21, 20).is_err());
+        assert!(console.validate_send(1, 2).is_err());
+    }
+
+    #[test]
+    fn buses_come_after_the_buses_sending_to_them() {
+        let mut console = Console::new();
+        for bus_id in [20, 21, 22] {
+            console.set_bus(bus_id, true);
+        }
+        console.set_sends(22, vec![TrackSend::new(21, 0.0, false)]);
+        console.set_sends(21, vec![TrackSend::new(20, 0.0, false)]);
+        assert_eq!(console.bus_order(&[20, 21, 22]), vec![22, 21, 20]);
+
+        // Once the middle bus is gone, the others don't depend on each other
+        console.set_sends(21, Vec::new());
+        console.set_bus(21, false);
+        assert_eq!(console.bus_order(&[20, 22]), vec![20, 22]);
+    }
+
+    #[test]
+    fn sends_follow_the_fader_unless_pre_fader() {
+        let mut console = Console::new();
+        console.set_bus(20, true);
+        console.set_bus(21, true);
+        console.set_sends(
+            1,
+            vec![
+                TrackSend::new(20, -6.0, false),
+                TrackSend::new(21, 0.0, true),
+                TrackSend::new(22, 0.0, false),
+            ],
+        );
+        set_mix(&mut console, 1, |mix| mix.volume = -6.0);
+        let gain = db_to_gain(-6.0);
+
+        let outputs = console.outputs(1, 0.0, 1);
+        let destinations = outputs.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        // Sends to tracks which aren't buses are skipped
+        assert_eq!(destinations, vec![20, 21, MASTER_TRACK_ID]);
+        assert_gains(outputs[0].1.clone(), &[gain * gain]);
+        assert_gains(outputs[1].1.clone(), &[1.0]);
+        assert_gains(outputs[2].1.clone(), &[gain]);
+
+        // A muted track sends nothing, even before the fader
+        set_mix(&mut console, 1, |mix| mix.mute = true);
+        assert!(
+            console
+                .outputs(1, 0.0, 1)
+                .iter()
+                .all(|(_, gains)| gains == &[0.0])
+        );
+    }
+
+    #[test]
+    fn send_level_follows_its_automation() {
+        let mut send = TrackSend::new(20, -6.0, false);
+        assert_eq!(send.level_at(3.0), -6.0);
+        send.automation = Automation {
+            points: vec![
+                AutomationPoint {
+                    beat: 4.0,
+                    value: -12.0,
+                },
+                AutomationPoint {
+                    beat: 8.0,
+                    value: 0.0,
+                },
+            ],
+        };
+        assert_eq!(send.level_at(0.0), -12.0);
+        assert_eq!(send.level_at(6.0), -6.0);
+        assert_eq!(send.level_at(10.0), 0.0);
+    }
+
+    #[test]
+    fn sidechains_feed_their_destination() {
+        let mut console = Console::new();
+        console.set_bus(20, true);
+        console.set_sends(2, vec![TrackSend::new(20, 0.0, false)]);
+        console.set_sidechains(3, vec![sidechain(1)]);
+
+        assert!(console.feeds(1, 3));
+        assert!(!console.feeds(3, 1));
+        assert!(!console.feeds(1, 20));
+
+        // The source would hear the track it feeds
+        assert!(console.validate_sidechain(1, 3).is_err());
+        assert!(console.validate_sidechain(1, 1).is_err());
+        assert!(console.validate_sidechain(1, 20).is_err());
+        assert!(console.validate_sidechain(4, 1).is_ok());
+        assert!(console.validate_sidechain(20, 3).is_ok());
+        // Loops through several sidechains are found too
+        console.set_sidechains(4, vec![sidechain(3)]);
+        assert!(console.feeds(1, 4));
+        assert!(console.validate_sidechain(1, 4).is_err());
+    }
+
+    #[test]
+    fn muted_folders_silence_their_tracks() {
+        let mut console = Console::new();
+        console.set_folder(10, true);
+        console.set_folder(11, true);
+        console.set_parent(11, Some(10));
+        console.set_parent(1, Some(11));
+        assert_eq!(console.ancestors(1), vec![11, 10]);
+        assert_eq!(console.output_of(1), 11);
+
+        set_mix(&mut console, 10, |mix| mix.mute = true);
+        assert!(!console.is_audible(1));
+        assert!(!console.is_audible(11));
+        assert!(console.is_audible(2));
+    }
+
+    #[test]
+    fn solo_follows_the_folders() {
+        let mut console = Console::new();
+        console.set_folder(10, true);
+        console.set_parent(1, Some(10));
+        console.set_parent(2, Some(10));
+        set_mix(&mut console, 1, |mix| mix.solo = true);
+
+        // The folder of a soloed track is heard, but not the other tracks in it
+        assert!(console.is_audible(10));
+        assert!(!console.is_audible(2));
+
+        // Tracks in a soloed folder are heard with it
+        set_mix(&mut console, 10, |mix| mix.solo = true);
+        assert!(console.is_audible(2));
+        assert!(!console.is_audible(3));
+    }
+
+    #[test]
+    fn folders_cant_contain_themselves() {
+        let mut console = Console::new();
+        console.set_folder(10, true);
+        console.set_folder(11, true);
+        console.set_parent(11, Some(10));
+
+        assert!(console.feeds(11, 10));
+        assert!(console.validate_parent(10, 11).is_err());
+        assert!(console.validate_parent(10, 10).is_err());
+        assert!(console.validate_parent(1, 11).is_ok());
+        assert!(console.validate_parent(1, 2).is_err());
+
+        // Removing the folder moves its tracks back to the top level
+        console.set_folder(10, false);
+        assert_eq!(console.parent(11), None);
+        assert_eq!(console.output_of(11), MASTER_TRACK_ID);
+    }
+
+    #[test]
+    fn clearing_mute_and_solo_makes_every_track_heard() {
+        let mut console = Console::new();
+        set_mix(&mut console, 1, |mix| mix.mute = true);
+        set_mix(&mut console, 2, |mix| mix.solo = true);
+        assert!(!console.is_audible(1));
+        assert!(!console.is_audible(3));
+
+        console.clear_mute_and_solo();
+        assert!((1..=3).all(|track_id| console.is_audible(track_id)));
+    }
+}
@@ -0,0 +1,202 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::mixing::track::BufferTrack;
+use knodiq_engine::{AudioSource, Beats, Graph, Mixer, Sample, Track};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Whether the graph only connects its input to its output.
+pub fn is_pass_through(graph: &Graph) -> bool {
+    let input_node = graph.get_input_node_id();
+    let output_node = graph.get_output_node_id();
+    let connections = graph.get_connections();
+    graph.get_nodes().len() == 2
+        && connections.len() == 1
+        && connections[0].from == input_node
+        && connections[0].to == output_node
+        && connections[0].from_param == connections[0].to_param
+}
+
+/// Runs audio through the graph of a track which isn't fed by regions:
+/// the master bus, and the bus tracks.
+///
+/// The graph belongs to a buffer track, so the audio is given to it as the audio of a region
+/// and rendered with a mixer holding only that track. The mixer is prepared once, so that
+/// the nodes keep their state (e.g. the tail of a reverb) from one call of `process`
+/// to the next, and a processor is made for each playback or export.
+pub struct GraphProcessor {
+    /// `None` if the graph passes the audio through, so it doesn't need to be rendered.
+    mixer: Option<Mixer>,
+    /// Region of the track holding the audio being processed.
+    region_id: Option<u32>,
+}
+
+impl GraphProcessor {
+    /// `mixer` gives the format and tempo to render at. Its tracks aren't used.
+    /// A graph which can't be prepared passes the audio through.
+    pub fn new(mixer: &Mixer, track: Box<dyn Track>) -> Self {
+        let pass_through = GraphProcessor {
+            mixer: None,
+            region_id: None,
+        };
+        if is_pass_through(track.graph()) {
+            return pass_through;
+        }
+
+        let mut graph_mixer = mixer.clone();
+        graph_mixer.tracks.clear();
+        graph_mixer.tracks.push(track);
+        if let Err(e) = graph_mixer.prepare() {
+            eprintln!("Error preparing the graph of a bus: {}", e);
+            return pass_through;
+        }
+        GraphProcessor {
+            mixer: Some(graph_mixer),
+            region_id: None,
+        }
+    }
+
+    pub fn is_pass_through(&self) -> bool {
+        self.mixer.is_none()
+    }
+
+    /// Graph the audio is run through, or `None` if it passes the audio through.
+    pub fn graph_mut(&mut self) -> Option<&mut Graph> {
+        self.mixer
+            .as_mut()
+            .and_then(|mixer| mixer.tracks.first_mut())
+            .map(|track| track.graph_mut())
+    }
+
+    /// Process the buffers of each channel, starting at `start_frame` on the timeline.
+    /// The buffers are expected to follow the ones of the previous call.
+    /// Returns `None` if `should_stop` was set while processing.
+    pub fn process(
+        &mut self,
+        buffers: Vec<Vec<Sample>>,
+        start_frame: usize,
+        should_stop: &Arc<AtomicBool>,
+    ) -> Option<Vec<Vec<Sample>>> {
+        let Some(mixer) = &mut self.mixer else {
+            return Some(buffers);
+        };
+
+        let channels = mixer.channels.max(1);
+        let frames = buffers.first().map_or(0, Vec::len);
+        let samples_per_beat = mixer.samples_per_beat();
+        let start_beat = start_frame as Beats / samples_per_beat;
+        let tempo = mixer.tempo;
+        let sample_rate = mixer.sample_rate;
+
+        // Replace the audio of the track with the buffers
+        let buffer_track = mixer
+            .tracks
+            .first_mut()?
+            .as_any_mut()
+            .downcast_mut::<BufferTrack>()?;
+        if let Some(region_id) = self.region_id.take() {
+            buffer_track.remove_region(region_id);
+        }
+        let region_id = match buffer_track.add_region(
+            Box::new(BufferRegion::empty("Bus input".to_string())),
+            start_beat,
+            frames as Beats / samples_per_beat,
+        ) {
+            Ok(region_id) => region_id,
+            Err(e) => {
+                eprintln!("Error adding the audio to the graph of a bus: {}", e);
+                return Some(buffers);
+            }
+        };
+        self.region_id = Some(region_id);
+        let mut source = AudioSource::new(sample_rate, channels);
+        source.data = buffers;
+        if let Some(region) = buffer_track
+            .get_region_mut(region_id)
+            .and_then(|region| region.as_any_mut().downcast_mut::<BufferRegion>())
+        {
+            region.set_audio_source(Some(source), tempo);
+        }
+
+        let total_samples = frames * channels;
+        let samples = Arc::new(Mutex::new(Vec::with_capacity(total_samples)));
+        let samples_clone = Arc::clone(&samples);
+        let should_stop_clone = Arc::clone(should_stop);
+        if total_samples > 0 {
+            mixer.mix(
+                start_beat,
+                Box::new(move |sample: Sample, _current_beat: Beats| {
+                    if should_stop_clone.load(Ordering::Relaxed) {
+                        return false;
+                    }
+                    let mut samples = samples_clone.lock().unwrap();
+                    samples.push(sample);
+                    samples.len() < total_samples
+                }),
+            );
+        }
+        if should_stop.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let samples = samples.lock().unwrap();
+        let mut output = vec![vec![0.0; frames]; channels];
+        for (index, sample) in samples.iter().enumerate() {
+            output[index % channels][index / channels] = *sample;
+        }
+        Some(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::master_bus::create_master_track;
+
+    #[test]
+    fn only_a_direct_connection_passes_through() {
+        let mut track = create_master_track(2);
+        assert!(is_pass_through(track.graph()));
+
+        let input_node = track.graph().get_input_node_id();
+        let output_node = track.graph().get_output_node_id();
+        track.graph_mut().disconnect(
+            input_node,
+            "audio".to_string(),
+            output_node,
+            "audio".to_string(),
+        );
+        assert!(!is_pass_through(track.graph()));
+    }
+
+    #[test]
+    fn pass_through_keeps_the_audio() {
+        let mixer = Mixer::new(120.0, 48000, 2);
+        let mut processor = GraphProcessor::new(&mixer, create_master_track(2));
+        assert!(processor.is_pass_through());
+
+        let buffers = vec![vec![1.0, 2.0, 3.0, 4.0], vec![5.0, 6.0, 7.0, 8.0]];
+        let should_stop = Arc::new(AtomicBool::new(false));
+        assert_eq!(
+            processor.process(buffers.clone(), 10, &should_stop),
+            Some(buffers)
+        );
+    }
+}
@@ -0,0 +1,1382 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::MixerContext;
+use crate::api::mixing::cache::DirtyRange;
+use crate::api::mixing::history::CoalesceKey;
+use crate::api::mixing::mixer::set_region_source;
+use crate::api::mixing::mixer_context::TrackSideData;
+use crate::api::mixing::region::{
+    RegionOperation, RegionSource, add_note_with_id, rotate_notes, shift_notes, split_notes,
+};
+use crate::api::mixing::tempo::TempoEvent;
+use crate::api::state::NoteState;
+use crate::api::{
+    AudioSettings, Fade, RegionSettings, Sidechain, TimeSignature, TrackMix, TrackSend,
+};
+use kash::AudioShaderNode;
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::mixing::track::BufferTrack;
+use knodiq_engine::{Beats, Graph, Node, NodeId, Region, Track, Value};
+use knodiq_note::{NoteRegion, NoteTrack};
+use std::collections::HashMap;
+
+/// A connection between two nodes: from, from_param, to, to_param.
+pub type Connection = (NodeId, String, NodeId, String);
+
+/// A reversible change to the mixer.
+/// Applying an edit returns the edit which reverts it.
+pub enum Edit {
+    /// Insert a track at the index, together with its side table entries.
+    InsertTrack {
+        index: usize,
+        track: Box<dyn Track>,
+        data: TrackSideData,
+    },
+    /// Remove a track from the mixer.
+    /// - track_id: `u32`
+    RemoveTrack(u32),
+    /// Replace a track with a snapshot of it.
+    RestoreTrack(TrackSnapshot),
+    /// Rename a track.
+    /// - track_id: `u32`
+    /// - name: `String`
+    RenameTrack(u32, String),
+    /// Move a track to another index in the mixer.
+    /// - track_id: `u32`
+    /// - index: `usize`
+    MoveTrack(u32, usize),
+    /// Move a track into a folder track, or out of it with `None`.
+    /// - track_id: `u32`
+    /// - parent_id: `Option<u32>`
+    SetTrackParent(u32, Option<u32>),
+    /// Hide or show the children of a folder track.
+    /// - track_id: `u32`
+    /// - collapsed: `bool`
+    SetFolderCollapsed(u32, bool),
+    /// Set the color of a track, or reset it with `None`.
+    /// - track_id: `u32`
+    /// - color: `Option<String>`
+    SetTrackColor(u32, Option<String>),
+    /// Set the volume, pan, mute and solo of a track.
+    /// - track_id: `u32`
+    /// - mix: `TrackMix`
+    SetTrackMix(u32, TrackMix),
+    /// Replace the sends of a track to the bus tracks.
+    /// - track_id: `u32`
+    /// - sends: `Vec<TrackSend>`
+    SetSends(u32, Vec<TrackSend>),
+    /// Replace the outputs of other tracks routed into the nodes of a track.
+    /// - track_id: `u32`
+    /// - sidechains: `Vec<Sidechain>`
+    SetSidechains(u32, Vec<Sidechain>),
+
+    /// Remove a region from a track.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    RemoveRegion(u32, u32),
+    /// Apply an operation to a region.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - operation: `RegionOperation`
+    ApplyRegionOp(u32, u32, RegionOperation),
+    /// Split a region in two at the beat. The region keeps the first half.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - at_beat: `Beats`
+    /// - cut_notes: `bool` (whether notes crossing the split are cut in two)
+    SplitRegion(u32, u32, Beats, bool),
+    /// Move the start of a region, keeping its audio or notes in place on the timeline.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - start: `Beats`
+    TrimRegionStart(u32, u32, Beats),
+    /// Move the end of a region, keeping its start.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - end: `Beats`
+    TrimRegionEnd(u32, u32, Beats),
+    /// Move the audio or notes of a region by the beats, keeping the region in place.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - delta: `Beats`
+    SlipRegion(u32, u32, Beats),
+    /// Add a note to a `NoteRegion`, keeping the ID of the note.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - note: `NoteState`
+    InsertNote(u32, u32, NoteState),
+
+    /// Connect two nodes in the graph of a track.
+    ConnectGraph(u32, NodeId, String, NodeId, String),
+    /// Disconnect two nodes in the graph of a track.
+    DisconnectGraph(u32, NodeId, String, NodeId, String),
+    /// Add a node to a track, together with its connections and side table entries.
+    InsertNode {
+        track_id: u32,
+        node: Box<dyn Node>,
+        position: (f32, f32),
+        inputs: HashMap<String, Value>,
+        connections: Vec<Connection>,
+    },
+    /// Remove a node from a track.
+    /// - track_id: `u32`
+    /// - node_id: `NodeId`
+    RemoveNode(u32, NodeId),
+    /// Update the position of a node.
+    /// - track_id: `u32`
+    /// - node_id: `NodeId`
+    /// - position: `(f32, f32)`
+    MoveNode(u32, NodeId, (f32, f32)),
+    /// Set an input property of a node.
+    /// - track_id: `u32`
+    /// - node_id: `NodeId`
+    /// - key: `String`
+    /// - value: `Value`
+    SetInputProperties(u32, NodeId, String, Value),
+    /// Replace a node with a snapshot of it, keeping its connections.
+    /// `inputs` replaces the recorded input properties of the node.
+    RestoreNode {
+        track_id: u32,
+        node: Box<dyn Node>,
+        inputs: Option<HashMap<String, Value>>,
+    },
+    /// Set the shader of an audio shader node.
+    /// - track_id: `u32`
+    /// - node_id: `NodeId`
+    /// - shader: `String`
+    SetAudioShader(u32, NodeId, String),
+
+    /// Set the tempo of the project.
+    /// - tempo: `f32` (BPM)
+    SetTempo(f32),
+    /// Set the time signature of the project.
+    /// - time_signature: `TimeSignature`
+    SetTimeSignature(TimeSignature),
+    /// Set the sample rate and channel layout of the project.
+    SetAudioSettings(AudioSettings),
+    /// Add a tempo event to the tempo map, keeping its ID.
+    InsertTempoEvent(TempoEvent),
+    /// Move a tempo event to another beat.
+    /// - event_id: `u32`
+    /// - beat: `Beats`
+    MoveTempoEvent(u32, Beats),
+    /// Remove a tempo event from the tempo map.
+    /// - event_id: `u32`
+    RemoveTempoEvent(u32),
+}
+
+/// A copy of a track, its audio sources and region settings, used to revert changes
+/// which can't be expressed as a single operation.
+pub struct TrackSnapshot {
+    pub track: Box<dyn Track>,
+    /// Audio file of each buffer region, keyed by region ID.
+    pub region_sources: HashMap<u32, RegionSource>,
+    /// Settings of each region, keyed by region ID.
+    pub region_settings: HashMap<u32, RegionSettings>,
+}
+
+impl TrackSnapshot {
+    pub fn take(context: &MixerContext, track_id: u32) -> Option<Self> {
+        let Some(track) = context
+            .mixer
+            .tracks
+            .iter()
+            .find(|track| track.get_id() == track_id)
+        else {
+            eprintln!("Track with ID {} not found.", track_id);
+            return None;
+        };
+
+        let region_sources = context
+            .region_sources
+            .iter()
+            .filter(|((id, _), _)| *id == track_id)
+            .map(|((_, region_id), source)| (*region_id, source.clone()))
+            .collect();
+        let region_settings = context
+            .region_settings
+            .iter()
+            .filter(|((id, _), _)| *id == track_id)
+            .map(|((_, region_id), settings)| (*region_id, *settings))
+            .collect();
+
+        Some(TrackSnapshot {
+            track: track.clone(),
+            region_sources,
+            region_settings,
+        })
+    }
+}
+
+impl Edit {
+    /// Key used to merge runs of this edit into a single undo step.
+    pub fn coalesce_key(&self) -> Option<CoalesceKey> {
+        match self {
+            Edit::MoveNode(track_id, node_id, _) => {
+                Some(CoalesceKey::MoveNode(*track_id, *node_id))
+            }
+            Edit::ApplyRegionOp(track_id, region_id, RegionOperation::SetStartTime(_)) => {
+                Some(CoalesceKey::MoveRegion(*track_id, *region_id))
+            }
+            Edit::ApplyRegionOp(track_id, region_id, RegionOperation::SetDuration(_))
+            | Edit::TrimRegionEnd(track_id, region_id, _) => {
+                Some(CoalesceKey::ResizeRegion(*track_id, *region_id))
+            }
+            Edit::TrimRegionStart(track_id, region_id, _) => {
+                Some(CoalesceKey::TrimRegionStart(*track_id, *region_id))
+            }
+            Edit::ApplyRegionOp(track_id, region_id, RegionOperation::SetSourceOffset(_))
+            | Edit::SlipRegion(track_id, region_id, _) => {
+                Some(CoalesceKey::SlipRegion(*track_id, *region_id))
+            }
+            Edit::ApplyRegionOp(
+                track_id,
+                region_id,
+                RegionOperation::SetFadeIn(_) | RegionOperation::SetFadeOut(_),
+            ) => Some(CoalesceKey::FadeRegion(*track_id, *region_id)),
+            Edit::ApplyRegionOp(track_id, region_id, RegionOperation::SetGain(_)) => {
+                Some(CoalesceKey::SetRegionGain(*track_id, *region_id))
+            }
+            Edit::SetTempo(_) => Some(CoalesceKey::SetTempo),
+            Edit::MoveTempoEvent(event_id, _) => Some(CoalesceKey::MoveTempoEvent(*event_id)),
+            _ => None,
+        }
+    }
+
+    /// Apply the edit to the mixer, and invalidate the part of the mix cache it changed.
+    /// Returns the edit reverting it, or `None` if nothing was changed.
+    pub fn apply(self, context: &mut MixerContext) -> Option<Edit> {
+        let dirty_track = self.dirty_track();
+        let dirty_range = self.dirty_range(context);
+        let inverse = self.apply_change(context)?;
+
+        // The inverse covers where the edit moved things to
+        context.invalidate(dirty_track, dirty_range.union(inverse.dirty_range(context)));
+        Some(inverse)
+    }
+
+    /// Track whose sound the edit changes, or `None` if it changes every track.
+    pub fn dirty_track(&self) -> Option<u32> {
+        match self {
+            Edit::InsertTrack { track, .. } => Some(track.get_id()),
+            Edit::RestoreTrack(snapshot) => Some(snapshot.track.get_id()),
+            Edit::RemoveTrack(track_id)
+            | Edit::RenameTrack(track_id, _)
+            | Edit::MoveTrack(track_id, _)
+            | Edit::SetTrackParent(track_id, _)
+            | Edit::SetFolderCollapsed(track_id, _)
+            | Edit::SetTrackColor(track_id, _)
+            | Edit::SetTrackMix(track_id, _)
+            | Edit::SetSends(track_id, _)
+            | Edit::SetSidechains(track_id, _)
+            | Edit::RemoveRegion(track_id, _)
+            | Edit::ApplyRegionOp(track_id, ..)
+            | Edit::SplitRegion(track_id, ..)
+            | Edit::TrimRegionStart(track_id, ..)
+            | Edit::TrimRegionEnd(track_id, ..)
+            | Edit::SlipRegion(track_id, ..)
+            | Edit::InsertNote(track_id, ..)
+            | Edit::ConnectGraph(track_id, ..)
+            | Edit::DisconnectGraph(track_id, ..)
+            | Edit::InsertNode { track_id, .. }
+            | Edit::RemoveNode(track_id, _)
+            | Edit::MoveNode(track_id, ..)
+            | Edit::SetInputProperties(track_id, ..)
+            | Edit::RestoreNode { track_id, .. }
+            | Edit::SetAudioShader(track_id, ..) => Some(*track_id),
+            Edit::SetTempo(_)
+            | Edit::SetTimeSignature(_)
+            | Edit::SetAudioSettings(_)
+            | Edit::InsertTempoEvent(_)
+            | Edit::MoveTempoEvent(..)
+            | Edit::RemoveTempoEvent(_) => None,
+        }
+    }
+
+    /// Part of the timeline whose sound the edit changes, given the current state of the mixer.
+    pub fn dirty_range(&self, context: &MixerContext) -> DirtyRange {
+        match self {
+            Edit::InsertTrack { track, .. } => DirtyRange::track(track),
+            Edit::RemoveTrack(track_id) => track_dirty_range(context, *track_id),
+            Edit::RestoreTrack(snapshot) => DirtyRange::track(&snapshot.track)
+                .union(track_dirty_range(context, snapshot.track.get_id())),
+            // The order of the tracks doesn't change the sum
+            Edit::RenameTrack(..) | Edit::MoveTrack(..) | Edit::SetFolderCollapsed(..) => {
+                DirtyRange::Nothing
+            }
+            // The console is applied when the cached segments are summed
+            Edit::SetTrackColor(..)
+            | Edit::SetTrackMix(..)
+            | Edit::SetSends(..)
+            | Edit::SetTrackParent(..) => DirtyRange::Nothing,
+
+            Edit::RemoveRegion(track_id, region_id)
+            | Edit::SplitRegion(track_id, region_id, ..)
+            | Edit::SlipRegion(track_id, region_id, _)
+            | Edit::InsertNote(track_id, region_id, _) => {
+                region_dirty_range(context, *track_id, *region_id)
+            }
+            Edit::TrimRegionStart(track_id, region_id, new_start) => region_position(
+                context, *track_id, *region_id,
+            )
+            .map_or(DirtyRange::Nothing, |(start, duration)| {
+                let end = start + duration;
+                DirtyRange::region(start.min(*new_start), end - start.min(*new_start))
+            }),
+            Edit::TrimRegionEnd(track_id, region_id, new_end) => {
+                region_position(context, *track_id, *region_id)
+                    .map_or(DirtyRange::Nothing, |(start, duration)| {
+                        DirtyRange::region(start, duration.max(new_end - start))
+                    })
+            }
+            Edit::ApplyRegionOp(track_id, region_id, operation) => {
+                let Some((start, duration)) = region_position(context, *track_id, *region_id)
+                else {
+                    return DirtyRange::Nothing;
+                };
+                let current = DirtyRange::region(start, duration);
+                match operation {
+                    RegionOperation::SetName(_) => DirtyRange::Nothing,
+                    RegionOperation::SetStartTime(new_start) => {
+                        current.union(DirtyRange::region(*new_start, duration))
+                    }
+                    RegionOperation::SetDuration(new_duration)
+                    | RegionOperation::Scale(new_duration) => {
+                        current.union(DirtyRange::region(start, *new_duration))
+                    }
+                    _ => current,
+                }
+            }
+
+            Edit::ConnectGraph(track_id, ..)
+            | Edit::DisconnectGraph(track_id, ..)
+            | Edit::InsertNode { track_id, .. }
+            | Edit::RemoveNode(track_id, _)
+            | Edit::SetInputProperties(track_id, ..)
+            | Edit::RestoreNode { track_id, .. }
+            | Edit::SetAudioShader(track_id, ..)
+            | Edit::SetSidechains(track_id, _) => track_dirty_range(context, *track_id),
+            Edit::MoveNode(..) => DirtyRange::Nothing,
+
+            Edit::SetTempo(_)
+            | Edit::SetAudioSettings(_)
+            | Edit::InsertTempoEvent(_)
+            | Edit::MoveTempoEvent(..)
+            | Edit::RemoveTempoEvent(_) => DirtyRange::All,
+            Edit::SetTimeSignature(_) => DirtyRange::Nothing,
+        }
+    }
+
+    /// Make the change to the mixer and return the edit reverting it.
+    fn apply_change(self, context: &mut MixerContext) -> Option<Edit> {
+        match self {
+            Edit::InsertTrack { index, track, data } => {
+                let track_id = track.get_id();
+                let index = index.min(context.mixer.tracks.len());
+                context.mixer.tracks.insert(index, track);
+                context.restore_track_data(track_id, data);
+                context.request_track_audio(track_id);
+                Some(Edit::RemoveTrack(track_id))
+            }
+
+            Edit::RemoveTrack(track_id) => {
+                let Some(index) = track_index(context, track_id) else {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                };
+                // The tracks taking its output as a sidechain aren't found once it's removed
+                context.invalidate(Some(track_id), track_dirty_range(context, track_id));
+                let track = context.mixer.tracks.remove(index);
+                let data = context.take_track_data(track_id);
+                Some(Edit::InsertTrack { index, track, data })
+            }
+
+            Edit::RestoreTrack(snapshot) => {
+                let track_id = snapshot.track.get_id();
+                let Some(index) = track_index(context, track_id) else {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                };
+                let current = TrackSnapshot::take(context, track_id)?;
+
+                context.mixer.tracks[index] = snapshot.track;
+                context.region_sources.retain(|(id, _), _| *id != track_id);
+                for (region_id, source) in snapshot.region_sources {
+                    context.region_sources.insert((track_id, region_id), source);
+                }
+                context.region_settings.retain(|(id, _), _| *id != track_id);
+                context.region_status.retain(|(id, _), _| *id != track_id);
+                for (region_id, settings) in snapshot.region_settings {
+                    context
+                        .region_settings
+                        .insert((track_id, region_id), settings);
+                }
+                context.request_track_audio(track_id);
+                Some(Edit::RestoreTrack(current))
+            }
+
+            Edit::RenameTrack(track_id, name) => {
+                let Some(track) = context.mixer.get_track_by_id_mut(track_id) else {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                };
+                let previous = track.get_name().to_string();
+                if previous == name {
+                    return None;
+                }
+                track.set_name(name);
+                Some(Edit::RenameTrack(track_id, previous))
+            }
+
+            Edit::MoveTrack(track_id, index) => {
+                let Some(previous) = track_index(context, track_id) else {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                };
+                let index = index.min(context.mixer.tracks.len() - 1);
+                if index == previous {
+                    return None;
+                }
+                let track = context.mixer.tracks.remove(previous);
+                context.mixer.tracks.insert(index, track);
+                Some(Edit::MoveTrack(track_id, previous))
+            }
+
+            Edit::SetTrackParent(track_id, parent_id) => {
+                if track_index(context, track_id).is_none() {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                }
+                let mut console = context.console.lock().unwrap();
+                let previous = console.set_parent(track_id, parent_id);
+                Some(Edit::SetTrackParent(track_id, previous))
+            }
+
+            Edit::SetFolderCollapsed(track_id, collapsed) => {
+                let previous = match collapsed {
+                    true => !context.collapsed_folders.insert(track_id),
+                    false => context.collapsed_folders.remove(&track_id),
+                };
+                if previous == collapsed {
+                    return None;
+                }
+                Some(Edit::SetFolderCollapsed(track_id, previous))
+            }
+
+            Edit::SetTrackColor(track_id, color) => {
+                if context.track_colors.get(&track_id) == color.as_ref() {
+                    return None;
+                }
+                let previous = match color {
+                    Some(color) => context.track_colors.insert(track_id, color),
+                    None => context.track_colors.remove(&track_id),
+                };
+                Some(Edit::SetTrackColor(track_id, previous))
+            }
+
+            Edit::SetTrackMix(track_id, mix) => {
+                if track_index(context, track_id).is_none() {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                }
+                let previous = context.console.lock().unwrap().set(track_id, mix);
+                if previous == mix {
+                    return None;
+                }
+                Some(Edit::SetTrackMix(track_id, previous))
+            }
+
+            Edit::SetSends(track_id, sends) => {
+                if track_index(context, track_id).is_none() {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                }
+                let previous = context.console.lock().unwrap().set_sends(track_id, sends);
+                Some(Edit::SetSends(track_id, previous))
+            }
+
+            Edit::SetSidechains(track_id, sidechains) => {
+                if track_index(context, track_id).is_none() {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                }
+                let mut console = context.console.lock().unwrap();
+                let previous = console.set_sidechains(track_id, sidechains);
+                Some(Edit::SetSidechains(track_id, previous))
+            }
+
+            Edit::RemoveRegion(track_id, region_id) => {
+                if region_position(context, track_id, region_id).is_none() {
+                    eprintln!(
+                        "Region with ID {} not found in track {}.",
+                        region_id, track_id
+                    );
+                    return None;
+                }
+                let snapshot = TrackSnapshot::take(context, track_id)?;
+                let track = context.mixer.get_track_by_id_mut(track_id)?;
+                track.remove_region(region_id);
+                context.region_sources.remove(&(track_id, region_id));
+                context.region_settings.remove(&(track_id, region_id));
+                context.region_status.remove(&(track_id, region_id));
+                Some(Edit::RestoreTrack(snapshot))
+            }
+
+            Edit::ApplyRegionOp(track_id, region_id, operation) => {
+                apply_region_op(context, track_id, region_id, operation)
+            }
+
+            Edit::SplitRegion(track_id, region_id, at_beat, cut_notes) => {
+                split_region(context, track_id, region_id, at_beat, cut_notes)
+            }
+
+            Edit::TrimRegionStart(track_id, region_id, start) => {
+                trim_region_start(context, track_id, region_id, start)
+            }
+
+            Edit::TrimRegionEnd(track_id, region_id, end) => {
+                let Some((start, duration)) = region_position(context, track_id, region_id) else {
+                    eprintln!(
+                        "Region with ID {} not found in track {}.",
+                        region_id, track_id
+                    );
+                    return None;
+                };
+                if end.is_nan() || end <= start {
+                    eprintln!("The end of the region must be after its start.");
+                    return None;
+                }
+                let region = context
+                    .mixer
+                    .get_track_by_id_mut(track_id)?
+                    .get_region_mut(region_id)?;
+                region.set_duration(end - start);
+                Some(Edit::TrimRegionEnd(track_id, region_id, start + duration))
+            }
+
+            Edit::SlipRegion(track_id, region_id, delta) => {
+                slip_region(context, track_id, region_id, delta)
+            }
+
+            Edit::InsertNote(track_id, region_id, note) => {
+                let Some(track) = context.mixer.get_track_by_id_mut(track_id) else {
+                    eprintln!("Track with ID {} not found.", track_id);
+                    return None;
+                };
+                let Some(region) = track.get_region_mut(region_id) else {
+                    eprintln!(
+                        "Region with ID {} not found in track {}.",
+                        region_id, track_id
+                    );
+                    return None;
+                };
+                let Some(note_region) = region.as_any_mut().downcast_mut::<NoteRegion>() else {
+                    eprintln!("Cannot add note to a non-note region");
+                    return None;
+                };
+
+                if let Err(e) = add_note_with_id(
+                    note_region,
+                    note.id,
+                    note.pitch,
+                    note.velocity,
+                    note.start_time,
+                    note.duration,
+                ) {
+                    eprintln!("{}", e);
+                    return None;
+                }
+                Some(Edit::ApplyRegionOp(
+                    track_id,
+                    region_id,
+                    RegionOperation::RemoveNote { id: note.id },
+                ))
+            }
+
+            Edit::ConnectGraph(track_id, from, from_param, to, to_param) => {
+                let graph = graph_mut(context, track_id)?;
+                graph.connect(from, from_param.clone(), to, to_param.clone());
+                Some(Edit::DisconnectGraph(
+                    track_id, from, from_param, to, to_param,
+                ))
+            }
+
+            Edit::DisconnectGraph(track_id, from, from_param, to, to_param) => {
+                let graph = graph_mut(context, track_id)?;
+                graph.disconnect(from, from_param.clone(), to, to_param.clone());
+                Some(Edit::ConnectGraph(track_id, from, from_param, to, to_param))
+            }
+
+            Edit::InsertNode {
+                track_id,
+                node,
+                position,
+                inputs,
+                connections,
+            } => {
+                let node_id = node.get_id();
+                let graph = graph_mut(context, track_id)?;
+                graph.add_node(node);
+                for (from, from_param, to, to_param) in connections {
+                    graph.connect(from, from_param, to, to_param);
+                }
+
+                context
+                    .node_positions
+                    .entry(track_id)
+                    .or_default()
+                    .insert(node_id, position);
+                if !inputs.is_empty() {
+                    context
+                        .node_inputs
+                        .entry(track_id)
+                        .or_default()
+                        .insert(node_id, inputs);
+                }
+                Some(Edit::RemoveNode(track_id, node_id))
+            }
+
+            Edit::RemoveNode(track_id, node_id) => {
+                let graph = graph_mut(context, track_id)?;
+                let Some(node) = find_node(graph, node_id) else {
+                    eprintln!("Node with ID {} not found in track {}.", node_id, track_id);
+                    return None;
+                };
+                let connections = connections_of(graph, node_id);
+                graph.remove_node(node_id);
+
+                let position = context
+                    .node_positions
+                    .get_mut(&track_id)
+                    .and_then(|positions| positions.remove(&node_id))
+                    .unwrap_or((0.0, 0.0));
+                let inputs = context
+                    .node_inputs
+                    .get_mut(&track_id)
+                    .and_then(|inputs| inputs.remove(&node_id))
+                    .unwrap_or_default();
+                Some(Edit::InsertNode {
+                    track_id,
+                    node,
+                    position,
+                    inputs,
+                    connections,
+                })
+            }
+
+            Edit::MoveNode(track_id, node_id, position) => {
+                let previous = context
+                    .node_positions
+                    .entry(track_id)
+                    .or_default()
+                    .insert(node_id, position)
+                    .unwrap_or((0.0, 0.0));
+                Some(Edit::MoveNode(track_id, node_id, previous))
+            }
+
+            Edit::SetInputProperties(track_id, node_id, key, value) => {
+                let graph = graph_mut(context, track_id)?;
+                let Some(snapshot) = find_node(graph, node_id) else {
+                    eprintln!("Node with ID {} not found in track {}.", node_id, track_id);
+                    return None;
+                };
+                graph
+                    .get_node_mut(node_id)?
+                    .set_input(key.as_str(), value.clone());
+
+                let node_inputs = context.node_inputs.entry(track_id).or_default();
+                let previous = node_inputs.get(&node_id).cloned();
+                node_inputs.entry(node_id).or_default().insert(key, value);
+                Some(Edit::RestoreNode {
+                    track_id,
+                    node: snapshot,
+                    inputs: previous,
+                })
+            }
+
+            Edit::RestoreNode {
+                track_id,
+                node,
+                inputs,
+            } => {
+                let node_id = node.get_id();
+                let graph = graph_mut(context, track_id)?;
+                let Some(current) = find_node(graph, node_id) else {
+                    eprintln!("Node with ID {} not found in track {}.", node_id, track_id);
+                    return None;
+                };
+
+                // Swap the node, then put its connections back
+                let connections = connections_of(graph, node_id);
+                graph.remove_node(node_id);
+                graph.add_node(node);
+                for (from, from_param, to, to_param) in connections {
+                    graph.connect(from, from_param, to, to_param);
+                }
+
+                let node_inputs = context.node_inputs.entry(track_id).or_default();
+                let current_inputs = match inputs {
+                    Some(inputs) => node_inputs.insert(node_id, inputs),
+                    None => node_inputs.remove(&node_id),
+                };
+                Some(Edit::RestoreNode {
+                    track_id,
+                    node: current,
+                    inputs: current_inputs,
+                })
+            }
+
+            Edit::SetAudioShader(track_id, node_id, shader) => {
+                let graph = graph_mut(context, track_id)?;
+                let Some(audio_shader_node) = graph
+                    .get_node_mut(node_id)
+                    .and_then(|node| node.as_any_mut().downcast_mut::<AudioShaderNode>())
+                else {
+                    eprintln!(
+                        "Node with ID {} is not an AudioShaderNode in track {}.",
+                        node_id, track_id
+                    );
+                    return None;
+                };
+
+                let previous = audio_shader_node.get_shader().to_string();
+                if let Err(errors) = audio_shader_node.set_shader(shader) {
+                    eprintln!("Error compiling AudioShader: {}", errors.join("\n"));
+                }
+                Some(Edit::SetAudioShader(track_id, node_id, previous))
+            }
+
+            Edit::SetTempo(tempo) => {
+                let previous = context.mixer.tempo;
+                context.set_tempo(tempo);
+                Some(Edit::SetTempo(previous))
+            }
+
+            Edit::SetTimeSignature(time_signature) => {
+                let previous = context.time_signature;
+                context.time_signature = time_signature;
+                Some(Edit::SetTimeSignature(previous))
+            }
+
+            Edit::SetAudioSettings(settings) => {
+                let previous = AudioSettings::from_mixer(&context.mixer);
+                context.set_audio_settings(settings);
+                Some(Edit::SetAudioSettings(previous))
+            }
+
+            Edit::InsertTempoEvent(event) => {
+                context.tempo_map.insert_event(event);
+                Some(Edit::RemoveTempoEvent(event.id))
+            }
+
+            Edit::MoveTempoEvent(event_id, beat) => {
+                let Some(previous) = context.tempo_map.move_event(event_id, beat) else {
+                    eprintln!("Tempo event with ID {} not found.", event_id);
+                    return None;
+                };
+                Some(Edit::MoveTempoEvent(event_id, previous))
+            }
+
+            Edit::RemoveTempoEvent(event_id) => {
+                let Some(event) = context.tempo_map.remove_event(event_id) else {
+                    eprintln!("Tempo event with ID {} not found.", event_id);
+                    return None;
+                };
+                Some(Edit::InsertTempoEvent(event))
+            }
+        }
+    }
+}
+
+/// Apply an operation to a region and return the edit reverting it.
+fn apply_region_op(
+    context: &mut MixerContext,
+    track_id: u32,
+    region_id: u32,
+    operation: RegionOperation,
+) -> Option<Edit> {
+    // The audio source is kept by the context rather than the region
+    if let RegionOperation::SetSourceOffset(offset) = operation {
+        return set_source_offset(context, track_id, region_id, offset);
+    }
+
+    // Scaling moves every note in the region, so keep a snapshot of the whole track
+    let snapshot = match operation {
+        RegionOperation::Scale(_) => Some(TrackSnapshot::take(context, track_id)?),
+        _ => None,
+    };
+
+    let Some(track) = context.mixer.get_track_by_id_mut(track_id) else {
+        eprintln!("Track with ID {} not found.", track_id);
+        return None;
+    };
+    let Some(region) = track.get_region_mut(region_id) else {
+        eprintln!(
+            "Region with ID {} not found in track {}.",
+            region_id, track_id
+        );
+        return None;
+    };
+
+    let mut settings = context
+        .region_settings
+        .get(&(track_id, region_id))
+        .copied()
+        .unwrap_or_default();
+
+    let inverse = match &operation {
+        RegionOperation::SetStartTime(_) => {
+            Some(RegionOperation::SetStartTime(region.start_time()))
+        }
+        RegionOperation::SetDuration(_) => Some(RegionOperation::SetDuration(region.duration())),
+        RegionOperation::SetName(_) => {
+            Some(RegionOperation::SetName(region.get_name().to_string()))
+        }
+        RegionOperation::SetFadeIn(_) => Some(RegionOperation::SetFadeIn(settings.fade_in)),
+        RegionOperation::SetFadeOut(_) => Some(RegionOperation::SetFadeOut(settings.fade_out)),
+        RegionOperation::SetGain(_) => Some(RegionOperation::SetGain(settings.gain)),
+        RegionOperation::SetInvert(_) => Some(RegionOperation::SetInvert(settings.invert)),
+        RegionOperation::SetPitchShift(_) => {
+            Some(RegionOperation::SetPitchShift(settings.pitch_shift))
+        }
+        RegionOperation::SetLoopLength(_) => {
+            Some(RegionOperation::SetLoopLength(settings.loop_length))
+        }
+        RegionOperation::ModifyNote { id, .. } => {
+            find_note(region, *id).map(|note| RegionOperation::ModifyNote {
+                id: note.id,
+                pitch: note.pitch,
+                velocity: note.velocity,
+                start_beat: note.start_time,
+                duration: note.duration,
+            })
+        }
+        RegionOperation::Scale(_)
+        | RegionOperation::SetSourceOffset(_)
+        | RegionOperation::AddNote { .. }
+        | RegionOperation::RemoveNote { .. } => None,
+    };
+    let removed_note = match &operation {
+        RegionOperation::RemoveNote { id } => find_note(region, *id),
+        _ => None,
+    };
+    let existing_note_ids = note_ids(region);
+
+    operation.apply(region, &mut settings);
+    if settings.is_default() {
+        context.region_settings.remove(&(track_id, region_id));
+    } else {
+        context
+            .region_settings
+            .insert((track_id, region_id), settings);
+    }
+
+    match operation {
+        RegionOperation::Scale(_) => snapshot.map(Edit::RestoreTrack),
+        RegionOperation::AddNote { .. } => {
+            // The note ID is assigned by the region, so look for the one which was added
+            let id = note_ids(region)
+                .into_iter()
+                .find(|id| !existing_note_ids.contains(id))?;
+            Some(Edit::ApplyRegionOp(
+                track_id,
+                region_id,
+                RegionOperation::RemoveNote { id },
+            ))
+        }
+        RegionOperation::RemoveNote { .. } => {
+            removed_note.map(|note| Edit::InsertNote(track_id, region_id, note))
+        }
+        _ => inverse.map(|inverse| Edit::ApplyRegionOp(track_id, region_id, inverse)),
+    }
+}
+
+/// Split a region in two at the beat and return the edit reverting it.
+fn split_region(
+    context: &mut MixerContext,
+    track_id: u32,
+    region_id: u32,
+    at_beat: Beats,
+    cut_notes: bool,
+) -> Option<Edit> {
+    let Some((start, duration)) = region_position(context, track_id, region_id) else {
+        eprintln!(
+            "Region with ID {} not found in track {}.",
+            region_id, track_id
+        );
+        return None;
+    };
+    if at_beat <= start || at_beat >= start + duration {
+        eprintln!("Can't split the region outside of it.");
+        return None;
+    }
+    let snapshot = TrackSnapshot::take(context, track_id)?;
+    let split = at_beat - start;
+    let loop_length = context
+        .region_settings
+        .get(&(track_id, region_id))
+        .and_then(|settings| settings.loop_length);
+    // Where the split falls in the content of the region
+    let phase = loop_length.map_or(split, |length| split % length);
+
+    let track = context.mixer.get_track_by_id_mut(track_id)?;
+    let region = track.get_region_mut(region_id)?;
+    let name = region.get_name().to_string();
+    region.set_duration(split);
+    // A looping region keeps its notes, and the second half loops them from the split
+    let notes =
+        region
+            .as_any_mut()
+            .downcast_mut::<NoteRegion>()
+            .map(|note_region| match loop_length {
+                Some(length) => rotate_notes(note_region, length, phase),
+                None => split_notes(note_region, split, cut_notes),
+            });
+
+    let added = if let Some(notes) = notes {
+        let mut second_half = NoteRegion::new(name, at_beat, duration - split);
+        for note in notes {
+            second_half.add_note(note.pitch, note.velocity, note.start_time, note.duration);
+        }
+        track
+            .as_any_mut()
+            .downcast_mut::<NoteTrack>()
+            .map(|note_track| {
+                note_track.add_region(Box::new(second_half), at_beat, duration - split)
+            })
+    } else {
+        track
+            .as_any_mut()
+            .downcast_mut::<BufferTrack>()
+            .map(|buffer_track| {
+                let second_half = BufferRegion::empty(name);
+                buffer_track.add_region(Box::new(second_half), at_beat, duration - split)
+            })
+    };
+    let new_region_id = match added {
+        Some(Ok(id)) => id,
+        Some(Err(e)) => {
+            eprintln!("Error adding region: {}", e);
+            Edit::RestoreTrack(snapshot).apply_change(context);
+            return None;
+        }
+        None => {
+            eprintln!("Unknown region type.");
+            Edit::RestoreTrack(snapshot).apply_change(context);
+            return None;
+        }
+    };
+
+    // The first half keeps the fade in and the second half the fade out
+    if let Some(settings) = context.region_settings.get(&(track_id, region_id)).copied() {
+        let first_half = RegionSettings {
+            fade_out: Fade::default(),
+            ..settings
+        };
+        let second_half = RegionSettings {
+            fade_in: Fade::default(),
+            ..settings
+        };
+        context
+            .region_settings
+            .insert((track_id, region_id), first_half);
+        if !second_half.is_default() {
+            context
+                .region_settings
+                .insert((track_id, new_region_id), second_half);
+        }
+    }
+
+    // The second half keeps playing the audio from where the split is.
+    // Audio can't wrap around like notes, so the loop of the second half starts there.
+    if let Some((mut source, ratio)) = stretched_source(context, track_id, region_id) {
+        source.offset += source_seconds(context, start, start + phase, ratio);
+        set_region_source(context, track_id, new_region_id, source);
+    }
+    Some(Edit::RestoreTrack(snapshot))
+}
+
+fn trim_region_start(
+    context: &mut MixerContext,
+    track_id: u32,
+    region_id: u32,
+    new_start: Beats,
+) -> Option<Edit> {
+    let Some((start, duration)) = region_position(context, track_id, region_id) else {
+        eprintln!(
+            "Region with ID {} not found in track {}.",
+            region_id, track_id
+        );
+        return None;
+    };
+    if new_start.is_nan() {
+        eprintln!("{} is not a valid start for the region.", new_start);
+        return None;
+    }
+    let end = start + duration;
+    let source = stretched_source(context, track_id, region_id);
+    // The region can't reveal anything before the beginning of its audio
+    let earliest = source.as_ref().map_or(0.0, |(source, ratio)| {
+        let tempo = context.mixer.tempo;
+        let start_seconds = context.tempo_map.beat_to_seconds(tempo, start);
+        context
+            .tempo_map
+            .seconds_to_beat(tempo, start_seconds - source.offset * ratio)
+    });
+    let new_start = new_start.max(earliest).max(0.0);
+    if new_start >= end {
+        eprintln!("The start of the region must be before its end.");
+        return None;
+    }
+
+    let region = context
+        .mixer
+        .get_track_by_id_mut(track_id)?
+        .get_region_mut(region_id)?;
+    region.set_start_time(new_start);
+    region.set_duration(end - new_start);
+    if let Some(note_region) = region.as_any_mut().downcast_mut::<NoteRegion>() {
+        shift_notes(note_region, start - new_start);
+    }
+
+    if let Some((mut source, ratio)) = source {
+        source.offset = (source.offset + source_seconds(context, start, new_start, ratio)).max(0.0);
+        set_region_source(context, track_id, region_id, source);
+    }
+    Some(Edit::TrimRegionStart(track_id, region_id, start))
+}
+
+fn slip_region(
+    context: &mut MixerContext,
+    track_id: u32,
+    region_id: u32,
+    delta: Beats,
+) -> Option<Edit> {
+    let Some((start, _)) = region_position(context, track_id, region_id) else {
+        eprintln!(
+            "Region with ID {} not found in track {}.",
+            region_id, track_id
+        );
+        return None;
+    };
+
+    // Moving the audio later starts the region from an earlier part of it
+    if let Some((source, ratio)) = stretched_source(context, track_id, region_id) {
+        let offset = source.offset - source_seconds(context, start, start + delta, ratio);
+        return set_source_offset(context, track_id, region_id, offset);
+    }
+
+    let snapshot = TrackSnapshot::take(context, track_id)?;
+    let region = context
+        .mixer
+        .get_track_by_id_mut(track_id)?
+        .get_region_mut(region_id)?;
+    let Some(note_region) = region.as_any_mut().downcast_mut::<NoteRegion>() else {
+        eprintln!("Region with ID {} has nothing to slip.", region_id);
+        return None;
+    };
+    shift_notes(note_region, delta);
+    Some(Edit::RestoreTrack(snapshot))
+}
+
+fn set_source_offset(
+    context: &mut MixerContext,
+    track_id: u32,
+    region_id: u32,
+    offset: f64,
+) -> Option<Edit> {
+    let Some(mut source) = context.region_sources.get(&(track_id, region_id)).cloned() else {
+        eprintln!(
+            "Region with ID {} in track {} has no audio source.",
+            region_id, track_id
+        );
+        return None;
+    };
+    let previous = source.offset;
+    source.offset = offset.max(0.0);
+    set_region_source(context, track_id, region_id, source);
+    Some(Edit::ApplyRegionOp(
+        track_id,
+        region_id,
+        RegionOperation::SetSourceOffset(previous),
+    ))
+}
+
+/// Audio source of the buffer region, together with how much it's stretched.
+fn stretched_source(
+    context: &MixerContext,
+    track_id: u32,
+    region_id: u32,
+) -> Option<(RegionSource, f64)> {
+    let source = context.region_sources.get(&(track_id, region_id))?;
+    let stretch = context
+        .region_settings
+        .get(&(track_id, region_id))
+        .map_or(1.0, |settings| settings.stretch);
+    Some((
+        source.clone(),
+        source.stretch_ratio(context.mixer.tempo, stretch),
+    ))
+}
+
+/// Seconds of audio stretched by the ratio played between the two beats.
+fn source_seconds(context: &MixerContext, from: Beats, to: Beats, ratio: f64) -> f64 {
+    let tempo = context.mixer.tempo;
+    (context.tempo_map.beat_to_seconds(tempo, to) - context.tempo_map.beat_to_seconds(tempo, from))
+        / ratio
+}
+
+/// Part of the timeline affected by changing the whole track.
+pub fn track_dirty_range(context: &MixerContext, track_id: u32) -> DirtyRange {
+    context
+        .mixer
+        .tracks
+        .iter()
+        .find(|track| track.get_id() == track_id)
+        .map_or(DirtyRange::Nothing, DirtyRange::track)
+}
+
+/// Part of the timeline covered by the region.
+pub fn region_dirty_range(context: &MixerContext, track_id: u32, region_id: u32) -> DirtyRange {
+    region_position(context, track_id, region_id)
+        .map_or(DirtyRange::Nothing, |(start, duration)| {
+            DirtyRange::region(start, duration)
+        })
+}
+
+/// Start time and duration of the region.
+pub fn region_position(
+    context: &MixerContext,
+    track_id: u32,
+    region_id: u32,
+) -> Option<(Beats, Beats)> {
+    let track = context
+        .mixer
+        .tracks
+        .iter()
+        .find(|track| track.get_id() == track_id)?;
+    track
+        .regions()
+        .into_iter()
+        .find(|region| *region.get_id() == region_id)
+        .map(|region| (region.start_time(), region.duration()))
+}
+
+fn track_index(context: &MixerContext, track_id: u32) -> Option<usize> {
+    context
+        .mixer
+        .tracks
+        .iter()
+        .position(|track| track.get_id() == track_id)
+}
+
+fn graph_mut(context: &mut MixerContext, track_id: u32) -> Option<&mut Graph> {
+    match context.graph_mut(track_id) {
+        Some(graph) => Some(graph),
+        None => {
+            eprintln!("Track with ID {} not found.", track_id);
+            None
+        }
+    }
+}
+
+fn find_node(graph: &Graph, node_id: NodeId) -> Option<Box<dyn Node>> {
+    graph
+        .get_nodes()
+        .iter()
+        .find(|node| node.get_id() == node_id)
+        .cloned()
+}
+
+fn connections_of(graph: &Graph, node_id: NodeId) -> Vec<Connection> {
+    graph
+        .get_connections()
+        .iter()
+        .filter(|connection| connection.from == node_id || connection.to == node_id)
+        .map(|connection| {
+            (
+                connection.from,
+                connection.from_param.clone(),
+                connection.to,
+                connection.to_param.clone(),
+            )
+        })
+        .collect()
+}
+
+fn find_note(region: &dyn Region, id: u32) -> Option<NoteState> {
+    region
+        .as_any()
+        .downcast_ref::<NoteRegion>()?
+        .notes()
+        .iter()
+        .find(|note| note.id == id)
+        .map(NoteState::from_note)
+}
+
+fn note_ids(region: &dyn Region) -> Vec<u32> {
+    match region.as_any().downcast_ref::<NoteRegion>() {
+        Some(note_region) => note_region.notes().iter().map(|note| note.id).collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::mixer::create_track;
+    use crate::api::{TrackData, TrackType};
+    use knodiq_engine::Mixer;
+
+    /// Create a context with a buffer track holding a region from beat 4 to 8,
+    /// which starts playing its audio one second in.
+    fn context() -> (MixerContext, u32, u32) {
+        // A beat lasts half a second
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        context.mixer.add_track(create_track(&TrackData {
+            name: "Audio".to_string(),
+            channels: 2,
+            track_type: TrackType::BufferTrack,
+        }));
+        let track_id = context.mixer.tracks[0].get_id();
+        let region_id = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.as_any_mut().downcast_mut::<BufferTrack>())
+            .unwrap()
+            .add_region(Box::new(BufferRegion::empty("Take".to_string())), 4.0, 4.0)
+            .unwrap();
+        let mut source = RegionSource::new("missing.wav".to_string(), 0, 120.0);
+        source.offset = 1.0;
+        context.region_sources.insert((track_id, region_id), source);
+        (context, track_id, region_id)
+    }
+
+    fn offset(context: &MixerContext, track_id: u32, region_id: u32) -> f64 {
+        context.region_sources[&(track_id, region_id)].offset
+    }
+
+    #[test]
+    fn start_is_trimmed_back_to_the_start_of_the_audio() {
+        let (mut context, track_id, region_id) = context();
+        let inverse = Edit::TrimRegionStart(track_id, region_id, 0.0)
+            .apply(&mut context)
+            .unwrap();
+        assert_eq!(
+            region_position(&context, track_id, region_id),
+            Some((2.0, 6.0))
+        );
+        assert_eq!(offset(&context, track_id, region_id), 0.0);
+
+        assert!(matches!(
+            inverse,
+            Edit::TrimRegionStart(_, _, start) if start == 4.0
+        ));
+        inverse.apply(&mut context).unwrap();
+        assert_eq!(
+            region_position(&context, track_id, region_id),
+            Some((4.0, 4.0))
+        );
+        assert_eq!(offset(&context, track_id, region_id), 1.0);
+    }
+
+    #[test]
+    fn trims_keep_the_region_from_collapsing() {
+        let (mut context, track_id, region_id) = context();
+        assert!(
+            Edit::TrimRegionStart(track_id, region_id, 8.0)
+                .apply(&mut context)
+                .is_none()
+        );
+        assert!(
+            Edit::TrimRegionEnd(track_id, region_id, 4.0)
+                .apply(&mut context)
+                .is_none()
+        );
+        assert_eq!(
+            region_position(&context, track_id, region_id),
+            Some((4.0, 4.0))
+        );
+
+        let inverse = Edit::TrimRegionEnd(track_id, region_id, 10.0)
+            .apply(&mut context)
+            .unwrap();
+        assert_eq!(
+            region_position(&context, track_id, region_id),
+            Some((4.0, 6.0))
+        );
+        assert!(matches!(inverse, Edit::TrimRegionEnd(_, _, end) if end == 8.0));
+    }
+
+    #[test]
+    fn slipping_moves_the_audio_within_the_region() {
+        let (mut context, track_id, region_id) = context();
+        let inverse = Edit::SlipRegion(track_id, region_id, 1.0)
+            .apply(&mut context)
+            .unwrap();
+        assert_eq!(offset(&context, track_id, region_id), 0.5);
+        assert_eq!(
+            region_position(&context, track_id, region_id),
+            Some((4.0, 4.0))
+        );
+
+        // The audio can't be moved past its own beginning
+        Edit::SlipRegion(track_id, region_id, 4.0)
+            .apply(&mut context)
+            .unwrap();
+        assert_eq!(offset(&context, track_id, region_id), 0.0);
+
+        inverse.apply(&mut context).unwrap();
+        assert_eq!(offset(&context, track_id, region_id), 1.0);
+    }
+
+    #[test]
+    fn slipping_a_note_region_moves_its_notes() {
+        let (mut context, _, _) = context();
+        context.mixer.add_track(create_track(&TrackData {
+            name: "Lead".to_string(),
+            channels: 2,
+            track_type: TrackType::NoteTrack,
+        }));
+        let track_id = context.mixer.tracks[1].get_id();
+        let mut region = NoteRegion::new("Melody".to_string(), 0.0, 4.0);
+        region.add_note(60, 100, 1.0, 1.0);
+        let region_id = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.as_any_mut().downcast_mut::<NoteTrack>())
+            .unwrap()
+            .add_region(Box::new(region), 0.0, 4.0)
+            .unwrap();
+
+        let note_start = |context: &mut MixerContext| {
+            context
+                .mixer
+                .get_track_by_id_mut(track_id)
+                .and_then(|track| track.get_region_mut(region_id))
+                .and_then(|region| region.as_any_mut().downcast_mut::<NoteRegion>())
+                .map(|region| region.notes()[0].start_beat)
+                .unwrap()
+        };
+        let inverse = Edit::SlipRegion(track_id, region_id, 0.5)
+            .apply(&mut context)
+            .unwrap();
+        assert_eq!(note_start(&mut context), 1.5);
+        inverse.apply(&mut context).unwrap();
+        assert_eq!(note_start(&mut context), 1.0);
+    }
+}
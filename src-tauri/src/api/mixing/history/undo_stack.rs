@@ -0,0 +1,460 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::MixerContext;
+use crate::api::mixing::history::Edit;
+use knodiq_engine::NodeId;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Maximum number of undo steps kept in the history.
+const MAX_HISTORY: usize = 256;
+
+/// Edits with the same coalesce key made within this interval are merged into one undo step.
+const COALESCE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Identifies edits which are sent continuously while dragging.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CoalesceKey {
+    /// Dragging a node in the graph editor.
+    /// - track_id: `u32`
+    /// - node_id: `NodeId`
+    MoveNode(u32, NodeId),
+    /// Dragging a region along the timeline.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    MoveRegion(u32, u32),
+    /// Dragging the edge of a region.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    ResizeRegion(u32, u32),
+    /// Dragging the start of a region, keeping its content in place.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    TrimRegionStart(u32, u32),
+    /// Dragging the content of a region inside it.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    SlipRegion(u32, u32),
+    /// Dragging a fade handle of a region.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    FadeRegion(u32, u32),
+    /// Dragging the gain of a region.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    SetRegionGain(u32, u32),
+    /// Dragging the volume fader of a track.
+    /// - track_id: `u32`
+    SetTrackVolume(u32),
+    /// Dragging the pan knob of a track.
+    /// - track_id: `u32`
+    SetTrackPan(u32),
+    /// Dragging the level of a send.
+    /// - track_id: `u32`
+    /// - bus_id: `u32`
+    SetSendLevel(u32, u32),
+    /// Dragging the tempo control.
+    SetTempo,
+    /// Dragging a tempo event along the timeline.
+    /// - event_id: `u32`
+    MoveTempoEvent(u32),
+}
+
+/// A single undo step.
+struct HistoryEntry {
+    /// Edit reverting the step.
+    edit: Edit,
+    coalesce_key: Option<CoalesceKey>,
+    updated_at: Instant,
+}
+
+/// Undo and redo stacks of the edits made to the mixer.
+pub struct History {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Apply the edit to the mixer and record how to revert it.
+    /// Edits which don't change anything aren't recorded.
+    pub fn perform(&mut self, context: &mut MixerContext, edit: Edit) {
+        let coalesce_key = edit.coalesce_key();
+        if let Some(inverse) = edit.apply(context) {
+            self.record(inverse, coalesce_key);
+        }
+    }
+
+    /// Record an edit reverting a change which has already been made.
+    pub fn record(&mut self, inverse: Edit, coalesce_key: Option<CoalesceKey>) {
+        self.redo_stack.clear();
+        let now = Instant::now();
+
+        if let (Some(key), Some(last)) = (coalesce_key, self.undo_stack.back_mut()) {
+            if last.coalesce_key == Some(key)
+                && now.duration_since(last.updated_at) < COALESCE_INTERVAL
+            {
+                // The first inverse of the run already reverts the whole drag
+                last.updated_at = now;
+                return;
+            }
+        }
+
+        self.undo_stack.push_back(HistoryEntry {
+            edit: inverse,
+            coalesce_key,
+            updated_at: now,
+        });
+        if self.undo_stack.len() > MAX_HISTORY {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Revert the last undo step. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self, context: &mut MixerContext) -> bool {
+        match self.undo_stack.pop_back() {
+            Some(entry) => {
+                self.redo_stack.extend(revert(entry, context));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reapply the last undone step. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self, context: &mut MixerContext) -> bool {
+        match self.redo_stack.pop() {
+            Some(entry) => {
+                self.undo_stack.extend(revert(entry, context));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget every recorded step.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+/// Apply the edit of the entry and return the entry reverting it,
+/// or `None` if it couldn't be applied.
+fn revert(entry: HistoryEntry, context: &mut MixerContext) -> Option<HistoryEntry> {
+    let edit = entry.edit.apply(context)?;
+    Some(HistoryEntry {
+        edit,
+        coalesce_key: None,
+        updated_at: Instant::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::mixer::create_track;
+    use crate::api::mixing::region::RegionOperation;
+    use crate::api::{Sidechain, TimeSignature, TrackData, TrackSend, TrackType};
+    use knodiq_engine::{Beats, Mixer, Track};
+    use knodiq_note::{NoteRegion, NoteTrack};
+
+    /// Create a context with two note tracks, the first holding a single region.
+    /// Returns the IDs of the first track and its region.
+    fn context() -> (MixerContext, u32, u32) {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        for name in ["Lead", "Bass"] {
+            context.mixer.add_track(create_track(&TrackData {
+                name: name.to_string(),
+                channels: 2,
+                track_type: TrackType::NoteTrack,
+            }));
+        }
+        let track_id = context.mixer.tracks[0].get_id();
+        let region = NoteRegion::new("Melody".to_string(), 0.0, 4.0);
+        let region_id = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.as_any_mut().downcast_mut::<NoteTrack>())
+            .unwrap()
+            .add_region(Box::new(region), 0.0, 4.0)
+            .unwrap();
+        (context, track_id, region_id)
+    }
+
+    fn track_names(context: &MixerContext) -> Vec<String> {
+        context
+            .mixer
+            .tracks
+            .iter()
+            .map(|track| track.get_name().to_string())
+            .collect()
+    }
+
+    fn region_start(context: &mut MixerContext, track_id: u32, region_id: u32) -> Beats {
+        context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.get_region_mut(region_id))
+            .map(|region| region.start_time())
+            .unwrap()
+    }
+
+    #[test]
+    fn removed_track_is_restored_in_place() {
+        let (mut context, track_id, region_id) = context();
+        let mut history = History::new();
+        history.perform(
+            &mut context,
+            Edit::SetTrackColor(track_id, Some("#00ff00".to_string())),
+        );
+        history.perform(&mut context, Edit::RemoveTrack(track_id));
+        assert_eq!(track_names(&context), vec!["Bass"]);
+        assert!(!context.track_colors.contains_key(&track_id));
+
+        assert!(history.undo(&mut context));
+        assert_eq!(track_names(&context), vec!["Lead", "Bass"]);
+        assert_eq!(context.mixer.tracks[0].get_id(), track_id);
+        assert_eq!(
+            context.track_colors.get(&track_id).map(String::as_str),
+            Some("#00ff00")
+        );
+        assert_eq!(region_start(&mut context, track_id, region_id), 0.0);
+
+        assert!(history.redo(&mut context));
+        assert_eq!(track_names(&context), vec!["Bass"]);
+    }
+
+    #[test]
+    fn removed_track_is_routed_again_on_undo() {
+        let (mut context, lead_id, _) = context();
+        let bass_id = context.mixer.tracks[1].get_id();
+        let mut submix_ids = Vec::new();
+        for track_type in [TrackType::FolderTrack, TrackType::BusTrack] {
+            context.mixer.add_track(create_track(&TrackData {
+                name: "Submix".to_string(),
+                channels: 2,
+                track_type: TrackType::BufferTrack,
+            }));
+            let track_id = context.mixer.tracks.last().unwrap().get_id();
+            let mut console = context.console.lock().unwrap();
+            console.set_track_type(track_id, &track_type);
+            submix_ids.push(track_id);
+        }
+        let (folder_id, bus_id) = (submix_ids[0], submix_ids[1]);
+
+        // The lead is in the folder, sends to the bus and is a sidechain of the bass
+        let sends = vec![TrackSend::new(bus_id, -6.0, false)];
+        let sidechains = vec![Sidechain {
+            source_id: lead_id,
+            node_id: context.mixer.tracks[1].graph().get_input_node_id(),
+            param: "sidechain".to_string(),
+        }];
+        {
+            let mut console = context.console.lock().unwrap();
+            console.set_parent(lead_id, Some(folder_id));
+            console.set_sends(lead_id, sends.clone());
+            console.set_sidechains(bass_id, sidechains.clone());
+        }
+
+        let mut history = History::new();
+        history.perform(&mut context, Edit::RemoveTrack(folder_id));
+        history.perform(&mut context, Edit::RemoveTrack(bus_id));
+        assert!(context.console.lock().unwrap().sends(lead_id).is_empty());
+        history.perform(&mut context, Edit::RemoveTrack(lead_id));
+        assert!(
+            context
+                .console
+                .lock()
+                .unwrap()
+                .sidechains(bass_id)
+                .is_empty()
+        );
+
+        for _ in 0..3 {
+            assert!(history.undo(&mut context));
+        }
+        let console = context.console.lock().unwrap();
+        assert_eq!(console.parent(lead_id), Some(folder_id));
+        assert_eq!(console.sends(lead_id), sends.as_slice());
+        assert_eq!(console.sidechains(bass_id), sidechains.as_slice());
+    }
+
+    #[test]
+    fn edits_changing_nothing_are_not_recorded() {
+        let (mut context, track_id, region_id) = context();
+        let mut history = History::new();
+        history.perform(&mut context, Edit::RemoveRegion(track_id, region_id + 1));
+        history.perform(
+            &mut context,
+            Edit::RenameTrack(track_id, "Lead".to_string()),
+        );
+        history.perform(&mut context, Edit::MoveTrack(track_id, 0));
+        history.perform(&mut context, Edit::SetTrackColor(track_id, None));
+        assert!(!history.undo(&mut context));
+    }
+
+    #[test]
+    fn undo_and_redo_walk_through_the_steps() {
+        let (mut context, track_id, region_id) = context();
+        let mut history = History::new();
+        history.perform(
+            &mut context,
+            Edit::SetTrackColor(track_id, Some("#0000ff".to_string())),
+        );
+        history.perform(
+            &mut context,
+            Edit::ApplyRegionOp(
+                track_id,
+                region_id,
+                RegionOperation::SetName("Chorus".to_string()),
+            ),
+        );
+
+        assert!(history.undo(&mut context));
+        assert!(history.undo(&mut context));
+        assert!(!history.undo(&mut context));
+        assert!(!context.track_colors.contains_key(&track_id));
+
+        assert!(history.redo(&mut context));
+        assert!(context.track_colors.contains_key(&track_id));
+        assert!(history.redo(&mut context));
+        assert!(!history.redo(&mut context));
+        let name = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.get_region_mut(region_id))
+            .map(|region| region.get_name().to_string());
+        assert_eq!(name.as_deref(), Some("Chorus"));
+    }
+
+    #[test]
+    fn removed_notes_come_back_with_their_id() {
+        let (mut context, track_id, region_id) = context();
+        let mut history = History::new();
+        history.perform(
+            &mut context,
+            Edit::ApplyRegionOp(
+                track_id,
+                region_id,
+                RegionOperation::AddNote {
+                    pitch: 60,
+                    velocity: 100,
+                    start_beat: 1.0,
+                    duration: 0.5,
+                },
+            ),
+        );
+        let note_ids = |context: &mut MixerContext| {
+            context
+                .mixer
+                .get_track_by_id_mut(track_id)
+                .and_then(|track| track.get_region_mut(region_id))
+                .and_then(|region| region.as_any().downcast_ref::<NoteRegion>())
+                .map(|region| {
+                    region
+                        .notes()
+                        .iter()
+                        .map(|note| note.id)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap()
+        };
+        let added = note_ids(&mut context);
+        assert_eq!(added.len(), 1);
+
+        history.perform(
+            &mut context,
+            Edit::ApplyRegionOp(
+                track_id,
+                region_id,
+                RegionOperation::RemoveNote { id: added[0] },
+            ),
+        );
+        assert!(note_ids(&mut context).is_empty());
+        assert!(history.undo(&mut context));
+        assert_eq!(note_ids(&mut context), added);
+        assert!(history.undo(&mut context));
+        assert!(note_ids(&mut context).is_empty());
+        assert!(history.redo(&mut context));
+        assert_eq!(note_ids(&mut context), added);
+    }
+
+    #[test]
+    fn drags_are_undone_in_one_step() {
+        let (mut context, track_id, region_id) = context();
+        let mut history = History::new();
+        for start in [1.0, 2.0, 3.0] {
+            history.perform(
+                &mut context,
+                Edit::ApplyRegionOp(track_id, region_id, RegionOperation::SetStartTime(start)),
+            );
+        }
+        assert_eq!(region_start(&mut context, track_id, region_id), 3.0);
+
+        assert!(history.undo(&mut context));
+        assert_eq!(region_start(&mut context, track_id, region_id), 0.0);
+        assert!(!history.undo(&mut context));
+        assert!(history.redo(&mut context));
+        assert_eq!(region_start(&mut context, track_id, region_id), 3.0);
+    }
+
+    #[test]
+    fn new_edit_clears_the_redo_steps() {
+        let (mut context, track_id, _) = context();
+        let mut history = History::new();
+        history.perform(
+            &mut context,
+            Edit::SetTrackColor(track_id, Some("#ff0000".to_string())),
+        );
+        assert!(history.undo(&mut context));
+        history.perform(
+            &mut context,
+            Edit::SetTrackColor(track_id, Some("#ffff00".to_string())),
+        );
+        assert!(!history.redo(&mut context));
+    }
+
+    #[test]
+    fn tempo_and_time_signature_are_undone() {
+        let (mut context, _, _) = context();
+        let mut history = History::new();
+        for tempo in [100.0, 90.0] {
+            history.perform(&mut context, Edit::SetTempo(tempo));
+        }
+        let three_four = TimeSignature {
+            numerator: 3,
+            denominator: 4,
+        };
+        history.perform(&mut context, Edit::SetTimeSignature(three_four));
+
+        assert!(history.undo(&mut context));
+        assert_eq!(context.time_signature, TimeSignature::default());
+        assert!(history.undo(&mut context));
+        assert_eq!(context.mixer.tempo, 120.0);
+        assert!(history.redo(&mut context));
+        assert_eq!(context.mixer.tempo, 90.0);
+        assert!(history.redo(&mut context));
+        assert_eq!(context.time_signature, three_four);
+    }
+}
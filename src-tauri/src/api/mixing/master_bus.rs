@@ -0,0 +1,57 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::Track;
+use knodiq_engine::mixing::track::BufferTrack;
+
+/// Track ID used to address the master bus with the graph commands.
+pub const MASTER_TRACK_ID: u32 = u32::MAX;
+
+/// Create the track holding the graph of the master bus, passing the audio through.
+pub fn create_master_track(channels: usize) -> Box<dyn Track> {
+    let mut track = Box::new(BufferTrack::new("Master", channels)) as Box<dyn Track>;
+    let input_node = track.graph().get_input_node_id();
+    let output_node = track.graph().get_output_node_id();
+    track.graph_mut().connect(
+        input_node,
+        "audio".to_string(),
+        output_node,
+        "audio".to_string(),
+    );
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::graph_processor::is_pass_through;
+
+    #[test]
+    fn master_track_passes_the_audio_through() {
+        let mut track = create_master_track(2);
+        assert!(is_pass_through(track.graph()));
+
+        let input_node = track.graph().get_input_node_id();
+        let output_node = track.graph().get_output_node_id();
+        track.graph_mut().disconnect(
+            input_node,
+            "audio".to_string(),
+            output_node,
+            "audio".to_string(),
+        );
+        assert!(!is_pass_through(track.graph()));
+    }
+}
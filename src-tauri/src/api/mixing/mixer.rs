@@ -15,24 +15,45 @@
 //
 
 use crate::api::data::region_data::RegionDataContainer;
+use crate::api::import::{ImportPool, ImportProgress, ImportStage};
+use crate::api::mixing::cache::DirtyRange;
+use crate::api::mixing::history::edit::{region_dirty_range, track_dirty_range};
+use crate::api::mixing::history::{CoalesceKey, Edit, History, TrackSnapshot};
+use crate::api::mixing::master_bus::MASTER_TRACK_ID;
+use crate::api::mixing::mixer_context::MixerContext;
 use crate::api::mixing::mixing_thread::start_mixing_thread;
+use crate::api::mixing::region::{ClipboardRegion, RegionSource, RegionStatus, StretchQuality};
+use crate::api::mixing::resample::resample_source;
+use crate::api::mixing::tempo::TempoEvent;
 use crate::api::mixing::{MixerCommand, MixerResult, MixingThreadCommand};
-use crate::api::{AppState, MixerState, NodeType, RegionData, RegionType, TrackType};
+use crate::api::project::ProjectFile;
+use crate::api::project::project_file::TrackFile;
+use crate::api::waveform::{region_waveform, start_waveform_thread};
+use crate::api::{
+    AppState, AudioSettings, NodeType, RegionData, RegionType, Sidechain, TrackData, TrackMix,
+    TrackSend, TrackType,
+};
 use kash::AudioShaderNode;
 use knodiq_engine::graph::built_in::EmptyNode;
 use knodiq_engine::mixing::region::BufferRegion;
 use knodiq_engine::mixing::track::BufferTrack;
-use knodiq_engine::{AudioSource, Mixer, Node, NodeId, Track};
+use knodiq_engine::{AudioSource, Beats, Mixer, Node, NodeId, Track};
 use knodiq_note::{NoteInputNode, NoteRegion, NoteTrack};
 use std::collections::HashMap;
-use std::sync::{mpsc, Mutex};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+};
 use std::thread;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, State};
 
 pub fn start_mixer_thread(state: State<Mutex<AppState>>, app: AppHandle) {
     // Create a channel to communicate with the mixer
     let (command_sender, command_receiver) = mpsc::channel();
     let (result_sender, result_receiver) = mpsc::channel();
+    // The import pool and the mixing thread send their results back to the mixer thread
+    let result_command_sender = command_sender.clone();
 
     // Set the sender in the app state
     let mut state = state.lock().unwrap();
@@ -46,18 +67,18 @@ pub fn start_mixer_thread(state: State<Mutex<AppState>>, app: AppHandle) {
         .name("mixer_thread".into())
         .spawn(move || {
             let tempo = 120.0;
-            let sample_rate = 48000;
-            let channels = 2;
-            let mut mixer = Mixer::new(tempo, sample_rate, channels);
-
-            let mut node_positions: HashMap<u32, HashMap<NodeId, (f32, f32)>> = HashMap::new();
-            let mut track_colors: HashMap<u32, String> = HashMap::new();
+            let audio_settings = AudioSettings::default();
+            let mixer = Mixer::new(
+                tempo,
+                audio_settings.sample_rate as usize,
+                audio_settings.channels(),
+            );
+            let mut context = MixerContext::new(mixer);
 
             process_mixer(
-                &mut mixer,
-                &mut node_positions,
-                &mut track_colors,
+                &mut context,
                 &command_receiver,
+                result_command_sender,
                 &result_sender,
                 &app_handle,
             );
@@ -71,20 +92,23 @@ pub fn start_mixer_thread(state: State<Mutex<AppState>>, app: AppHandle) {
 }
 
 fn process_mixer(
-    mixer: &mut Mixer,
-    node_positions: &mut HashMap<u32, HashMap<NodeId, (f32, f32)>>,
-    track_colors: &mut HashMap<u32, String>,
+    context: &mut MixerContext,
     receiver: &mpsc::Receiver<MixerCommand>,
+    result_command_sender: mpsc::Sender<MixerCommand>,
     result_sender: &mpsc::Sender<MixerResult>,
     app: &AppHandle,
 ) {
     println!("Mixer thread started.");
 
-    // Check if the mixer needs to mix
-    let mut needs_mix = false;
+    // Undo and redo stacks of the edits
+    let mut history = History::new();
 
     let (mixing_sender, mixing_receiver) = mpsc::channel();
-    match start_mixing_thread(mixing_receiver) {
+    // Stop flag of the latest mix, shared with the mixing thread so that the mix can be stopped
+    // while it's busy mixing. Each mix gets its own, so a stop is never undone by a later mix.
+    let mut should_stop_mixing = Arc::new(AtomicBool::new(false));
+    // The mixing thread sends the audio it stretched back to be cached
+    match start_mixing_thread(mixing_receiver, result_command_sender.clone()) {
         Ok(_) => println!("Mixing thread started successfully."),
         Err(e) => {
             eprintln!("Failed to start mixing thread: {}", e);
@@ -92,34 +116,64 @@ fn process_mixer(
         }
     }
 
+    // Waveforms are summarized on their own thread, as long files take a while
+    let (waveform_sender, waveform_receiver) = mpsc::channel();
+    match start_waveform_thread(
+        waveform_receiver,
+        Arc::clone(&context.waveforms),
+        app.clone(),
+    ) {
+        Ok(_) => context.waveform_sender = Some(waveform_sender),
+        Err(e) => eprintln!("Failed to start waveform thread: {}", e),
+    }
+
+    // Imported audio files are decoded on worker threads, so that the mixer stays responsive
+    match ImportPool::start(result_command_sender, app.clone()) {
+        Ok(import_pool) => context.import_pool = Some(import_pool),
+        Err(e) => eprintln!("Failed to start import workers: {}", e),
+    }
+
     loop {
         match receiver.recv() {
             Ok(command) => match command {
                 MixerCommand::Mix(at, callback) => {
-                    let mixer_clone = mixer.clone();
+                    // Mix a copy warped by the tempo map, and report the beats in musical time.
+                    // The stretching is left to the mixing thread, so the mixer stays responsive.
+                    let render = context.prepare_render(StretchQuality::Fast);
+                    let tempo_map = context.tempo_map.clone();
+                    let base_tempo = context.mixer.tempo;
+                    let start = tempo_map.to_linear(base_tempo, at);
+                    let loop_range = context.loop_range.enabled.then(|| {
+                        (
+                            tempo_map.to_linear(base_tempo, context.loop_range.start),
+                            tempo_map.to_linear(base_tempo, context.loop_range.end),
+                        )
+                    });
+
+                    // Stop the previous mix first, a loop would never end by itself
+                    should_stop_mixing.store(true, Ordering::Release);
+                    should_stop_mixing = Arc::new(AtomicBool::new(false));
                     let _ = mixing_sender.send(MixingThreadCommand::StartMixing(
-                        mixer_clone,
-                        at,
-                        Box::new(callback),
+                        render,
+                        start,
+                        loop_range,
+                        context.master.clone(),
+                        tempo_map.clone(),
+                        Arc::clone(&context.mix_cache),
+                        Arc::clone(&context.console),
+                        Arc::clone(&should_stop_mixing),
+                        Box::new(move |sample, linear_beat| {
+                            callback(sample, tempo_map.from_linear(base_tempo, linear_beat))
+                        }),
                     ));
                 }
 
                 MixerCommand::StopMixing => {
-                    let _ = mixing_sender.send(MixingThreadCommand::StopMixing);
-                    needs_mix = false;
+                    should_stop_mixing.store(true, Ordering::Release);
                 }
 
                 MixerCommand::AddTrack(track_data) => {
-                    let mut track = match track_data.track_type {
-                        TrackType::BufferTrack => Box::new(BufferTrack::new(
-                            track_data.name.as_str(),
-                            track_data.channels,
-                        )) as Box<dyn Track>,
-                        TrackType::NoteTrack => Box::new(NoteTrack::new(
-                            track_data.name.as_str(),
-                            track_data.channels,
-                        )) as Box<dyn Track>,
-                    };
+                    let mut track = create_track(&track_data);
 
                     // Connect the input and output nodes of the track
                     let input_node = track.graph().get_input_node_id();
@@ -133,145 +187,300 @@ fn process_mixer(
                     );
 
                     // Add the track to the mixer
-                    mixer.add_track(track);
+                    context.mixer.add_track(track);
+                    if let Some(track) = context.mixer.tracks.last() {
+                        let track_id = track.get_id();
+                        context
+                            .console
+                            .lock()
+                            .unwrap()
+                            .set_track_type(track_id, &track_data.track_type);
+                        history.record(Edit::RemoveTrack(track_id), None);
+                    }
 
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    context.emit_state(app);
                 }
 
                 MixerCommand::RemoveTrack(track_id) => {
                     // Remove the track from the mixer
-                    mixer.remove_track(track_id);
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(context, Edit::RemoveTrack(track_id));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::RenameTrack(track_id, name) => {
+                    history.perform(context, Edit::RenameTrack(track_id, name));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::MoveTrack(track_id, new_index) => {
+                    history.perform(context, Edit::MoveTrack(track_id, new_index));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::DuplicateTrack(track_id, include_regions) => {
+                    match duplicate_track(context, track_id, include_regions) {
+                        Ok(duplicate_id) => {
+                            context.invalidate(
+                                Some(duplicate_id),
+                                track_dirty_range(context, duplicate_id),
+                            );
+                            history.record(Edit::RemoveTrack(duplicate_id), None);
+                        }
+                        Err(e) => eprintln!("Error duplicating track: {}", e),
+                    }
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTrackParent(track_id, parent_id) => {
+                    let validation = match parent_id {
+                        Some(parent_id) => context
+                            .console
+                            .lock()
+                            .unwrap()
+                            .validate_parent(track_id, parent_id),
+                        None => Ok(()),
+                    };
+                    let result = validation.and_then(|_| {
+                        let inverse = Edit::SetTrackParent(track_id, parent_id)
+                            .apply(context)
+                            .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+                        history.record(inverse, None);
+                        Ok(())
+                    });
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RoutingChanged(result));
+                }
+
+                MixerCommand::SetFolderCollapsed(track_id, collapsed) => {
+                    history.perform(context, Edit::SetFolderCollapsed(track_id, collapsed));
+                    context.emit_state(app);
                 }
 
                 MixerCommand::SetTrackColor(track_id, color) => {
-                    track_colors.insert(track_id, color);
-                    emit_state(mixer, node_positions, track_colors, app);
+                    history.perform(context, Edit::SetTrackColor(track_id, Some(color)));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTrackVolume(track_id, volume) => {
+                    let key = CoalesceKey::SetTrackVolume(track_id);
+                    set_track_mix(context, &mut history, track_id, Some(key), |mix| {
+                        mix.volume = volume
+                    });
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTrackPan(track_id, pan) => {
+                    let key = CoalesceKey::SetTrackPan(track_id);
+                    set_track_mix(context, &mut history, track_id, Some(key), |mix| {
+                        mix.pan = pan
+                    });
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTrackMute(track_id, mute) => {
+                    set_track_mix(context, &mut history, track_id, None, |mix| mix.mute = mute);
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTrackSolo(track_id, solo) => {
+                    set_track_mix(context, &mut history, track_id, None, |mix| mix.solo = solo);
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTrackSoloSafe(track_id, solo_safe) => {
+                    set_track_mix(context, &mut history, track_id, None, |mix| {
+                        mix.solo_safe = solo_safe
+                    });
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetSend(track_id, bus_id, level, pre_fader) => {
+                    let key = CoalesceKey::SetSendLevel(track_id, bus_id);
+                    let result =
+                        set_sends(
+                            context,
+                            &mut history,
+                            track_id,
+                            Some(key),
+                            |sends| match sends.iter_mut().find(|send| send.bus_id == bus_id) {
+                                Some(send) => {
+                                    send.level = level;
+                                    send.pre_fader = pre_fader;
+                                }
+                                None => sends.push(TrackSend::new(bus_id, level, pre_fader)),
+                            },
+                        );
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RoutingChanged(result));
+                }
+
+                MixerCommand::RemoveSend(track_id, bus_id) => {
+                    let result = set_sends(context, &mut history, track_id, None, |sends| {
+                        sends.retain(|send| send.bus_id != bus_id)
+                    });
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RoutingChanged(result));
+                }
+
+                MixerCommand::SetSendAutomation(track_id, bus_id, automation) => {
+                    let result = set_sends(context, &mut history, track_id, None, |sends| {
+                        if let Some(send) = sends.iter_mut().find(|send| send.bus_id == bus_id) {
+                            send.automation = automation;
+                        }
+                    });
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RoutingChanged(result));
                 }
 
                 MixerCommand::AddRegion(track_id, region_data) => {
-                    handle_add_region(
-                        mixer,
-                        node_positions,
-                        track_colors,
-                        track_id,
-                        region_data,
-                        app,
-                    );
+                    if let Some(region_id) = handle_add_region(context, track_id, region_data, app)
+                    {
+                        let range = region_dirty_range(context, track_id, region_id);
+                        context.invalidate(Some(track_id), range);
+                        history.record(Edit::RemoveRegion(track_id, region_id), None);
+                    }
                 }
 
                 MixerCommand::RemoveRegion(track_id, region_id) => {
                     // Remove the region from the specified track
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        track.remove_region(region_id);
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(context, Edit::RemoveRegion(track_id, region_id));
+                    context.emit_state(app);
                 }
 
                 MixerCommand::ApplyRegionOp(track_id, region_id, operation) => {
                     // Apply the operation to the specified region in the track
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        if let Some(region) = track.get_region_mut(region_id) {
-                            operation.apply(region);
-                        } else {
-                            eprintln!(
-                                "Region with ID {} not found in track {}.",
-                                region_id, track_id
-                            );
-                        }
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(context, Edit::ApplyRegionOp(track_id, region_id, operation));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::DuplicateRegion(track_id, region_id) => {
+                    let result = duplicate_region(context, &mut history, track_id, region_id);
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RegionsPasted(result));
+                }
+
+                MixerCommand::CopyRegions(track_id, region_ids) => {
+                    copy_regions(context, track_id, &region_ids);
+                }
+
+                MixerCommand::PasteRegions(track_id, at_beat) => {
+                    let clipboard = std::mem::take(&mut context.clipboard);
+                    let result =
+                        paste_regions(context, &mut history, track_id, at_beat, &clipboard);
+                    context.clipboard = clipboard;
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RegionsPasted(result));
+                }
+
+                MixerCommand::SplitRegion(track_id, region_id, at_beat, cut_notes) => {
+                    history.perform(
+                        context,
+                        Edit::SplitRegion(track_id, region_id, at_beat, cut_notes),
+                    );
+                    context.emit_state(app);
+                }
+
+                MixerCommand::TrimRegionStart(track_id, region_id, start) => {
+                    history.perform(context, Edit::TrimRegionStart(track_id, region_id, start));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::TrimRegionEnd(track_id, region_id, end) => {
+                    history.perform(context, Edit::TrimRegionEnd(track_id, region_id, end));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SlipRegion(track_id, region_id, delta) => {
+                    history.perform(context, Edit::SlipRegion(track_id, region_id, delta));
+                    context.emit_state(app);
                 }
 
                 MixerCommand::ConnectGraph(track_id, from, from_param, to, to_param) => {
                     // Connect the two nodes in the graph
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        track.graph_mut().connect(from, from_param, to, to_param);
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(
+                        context,
+                        Edit::ConnectGraph(track_id, from, from_param, to, to_param),
+                    );
+                    context.emit_state(app);
                 }
 
                 MixerCommand::DisconnectGraph(track_id, from, from_param, to, to_param) => {
                     // Disconnect the two nodes in the graph
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        track.graph_mut().disconnect(from, from_param, to, to_param);
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(
+                        context,
+                        Edit::DisconnectGraph(track_id, from, from_param, to, to_param),
+                    );
+                    context.emit_state(app);
                 }
 
-                MixerCommand::AddNode(track_id, node_data, position) => {
-                    // Create a new node based on the provided data
-                    let node: Box<dyn Node> = match node_data {
-                        NodeType::EmptyNode => Box::new(EmptyNode::new()),
-                        NodeType::AudioShaderNode => Box::new(AudioShaderNode::new()),
-                        NodeType::NoteInputNode => Box::new(NoteInputNode::new()),
-                    };
+                MixerCommand::ConnectSidechain(track_id, node_id, param, source_track_id) => {
+                    let result =
+                        connect_sidechain(context, track_id, node_id, &param, source_track_id)
+                            .and_then(|_| {
+                                set_sidechains(context, &mut history, track_id, |sidechains| {
+                                    sidechains.retain(|sidechain| {
+                                        sidechain.node_id != node_id || sidechain.param != param
+                                    });
+                                    sidechains.push(Sidechain {
+                                        source_id: source_track_id,
+                                        node_id,
+                                        param,
+                                    });
+                                })
+                            });
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RoutingChanged(result));
+                }
 
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        node_positions
-                            .entry(track_id)
-                            .or_default()
-                            .insert(node.get_id(), position);
+                MixerCommand::DisconnectSidechain(track_id, node_id, param) => {
+                    let result = set_sidechains(context, &mut history, track_id, |sidechains| {
+                        sidechains.retain(|sidechain| {
+                            sidechain.node_id != node_id || sidechain.param != param
+                        })
+                    });
+                    context.emit_state(app);
+                    let _ = result_sender.send(MixerResult::RoutingChanged(result));
+                }
 
-                        track.graph_mut().add_node(node);
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
+                MixerCommand::AddNode(track_id, node_data, position) => {
+                    // Create a new node based on the provided data
+                    let node = create_node(&node_data);
 
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(
+                        context,
+                        Edit::InsertNode {
+                            track_id,
+                            node,
+                            position,
+                            inputs: HashMap::new(),
+                            connections: Vec::new(),
+                        },
+                    );
+                    context.emit_state(app);
                 }
 
                 MixerCommand::RemoveNode(track_id, node_id) => {
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        track.graph_mut().remove_node(node_id);
-                        node_positions.entry(track_id).or_default().remove(&node_id);
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
-                    emit_state(mixer, node_positions, track_colors, app);
-                    needs_mix = true;
+                    history.perform(context, Edit::RemoveNode(track_id, node_id));
+                    context.emit_state(app);
                 }
 
                 MixerCommand::MoveNode(track_id, node_id, position) => {
-                    node_positions
-                        .entry(track_id)
-                        .or_default()
-                        .insert(node_id, position);
-                    emit_state(mixer, node_positions, track_colors, app);
+                    history.perform(context, Edit::MoveNode(track_id, node_id, position));
+                    context.emit_state(app);
                 }
 
                 MixerCommand::SetInputProperties(track_id, node_id, key, value) => {
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        if let Some(node) = track.graph_mut().get_node_mut(node_id) {
-                            node.set_input(key.as_str(), value);
-                        } else {
-                            eprintln!("Node with ID {} not found in track {}.", node_id, track_id);
-                        }
-                    } else {
-                        eprintln!("Track with ID {} not found.", track_id);
-                    }
-                    emit_state(mixer, node_positions, track_colors, app);
+                    history.perform(
+                        context,
+                        Edit::SetInputProperties(track_id, node_id, key, value),
+                    );
+                    context.emit_state(app);
                 }
 
                 MixerCommand::GetInputNode(track_id) => {
                     // Get the input nodes of the track
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        let input_node = &track.graph().get_input_node_id();
+                    if let Some(graph) = context.graph_mut(track_id) {
+                        let input_node = &graph.get_input_node_id();
                         let _ = result_sender.send(MixerResult::InputNode(input_node.clone()));
                     } else {
                         eprintln!("Track with ID {} not found.", track_id);
@@ -280,8 +489,8 @@ fn process_mixer(
 
                 MixerCommand::GetOutputNode(track_id) => {
                     // Get the output node of the track
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        let output_node = track.graph().get_output_node_id();
+                    if let Some(graph) = context.graph_mut(track_id) {
+                        let output_node = graph.get_output_node_id();
                         let _ = result_sender.send(MixerResult::OutputNode(output_node));
                     } else {
                         eprintln!("Track with ID {} not found.", track_id);
@@ -289,15 +498,24 @@ fn process_mixer(
                 }
 
                 MixerCommand::SetAudioShader(track_id, node_id, shader) => {
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        if let Some(node) = track.graph_mut().get_node_mut(node_id) {
+                    if let Some(graph) = context.graph_mut(track_id) {
+                        if let Some(node) = graph.get_node_mut(node_id) {
                             if let Some(audio_shader_node) =
                                 node.as_any_mut().downcast_mut::<AudioShaderNode>()
                             {
+                                let previous = audio_shader_node.get_shader().to_string();
                                 let errors = match audio_shader_node.set_shader(shader) {
                                     Ok(_) => vec![],
                                     Err(e) => e,
                                 };
+                                context.invalidate(
+                                    Some(track_id),
+                                    track_dirty_range(context, track_id),
+                                );
+                                history.record(
+                                    Edit::SetAudioShader(track_id, node_id, previous),
+                                    None,
+                                );
                                 let _ = result_sender.send(MixerResult::AudioShaderErrors(errors));
                             } else {
                                 eprintln!(
@@ -312,12 +530,134 @@ fn process_mixer(
                         eprintln!("Track with ID {} not found.", track_id);
                     }
 
-                    emit_state(mixer, node_positions, track_colors, app);
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTempo(tempo) => {
+                    history.perform(context, Edit::SetTempo(tempo));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetTimeSignature(time_signature) => {
+                    history.perform(context, Edit::SetTimeSignature(time_signature));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::AddTempoEvent(beat, tempo, curve) => {
+                    let event = TempoEvent {
+                        id: context.tempo_map.next_id(),
+                        beat,
+                        tempo,
+                        curve,
+                    };
+                    history.perform(context, Edit::InsertTempoEvent(event));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::MoveTempoEvent(event_id, beat) => {
+                    history.perform(context, Edit::MoveTempoEvent(event_id, beat));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::RemoveTempoEvent(event_id) => {
+                    history.perform(context, Edit::RemoveTempoEvent(event_id));
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetAudioSettings(settings) => {
+                    let result = context.validate_audio_settings(&settings);
+                    if result.is_ok() {
+                        history.perform(context, Edit::SetAudioSettings(settings));
+                        context.emit_state(app);
+                    }
+                    let _ = result_sender.send(MixerResult::AudioSettingsChanged(result));
+                }
+
+                MixerCommand::GetAudioSettings => {
+                    let settings = AudioSettings::from_mixer(&context.mixer);
+                    let _ = result_sender.send(MixerResult::AudioSettings(settings));
+                }
+
+                MixerCommand::SetLoopRange(start, end) => {
+                    context.loop_range.start = start;
+                    context.loop_range.end = end;
+                    context.emit_state(app);
+                }
+
+                MixerCommand::SetLoopEnabled(enabled) => {
+                    context.loop_range.enabled = enabled;
+                    context.emit_state(app);
+                }
+
+                MixerCommand::GetProject => {
+                    let project = ProjectFile::from_context(context);
+                    let _ = result_sender.send(MixerResult::Project(project));
+                }
+
+                MixerCommand::LoadProject(project) => {
+                    // Stop the playback of the old project before replacing it
+                    should_stop_mixing.store(true, Ordering::Release);
+
+                    // The clipboard outlives the project, so regions can be pasted into another one,
+                    // and so do the waveforms and the threads summarizing and decoding the audio
+                    let result = project.restore(context.mixer.clone()).map(|mut restored| {
+                        restored.clipboard = std::mem::take(&mut context.clipboard);
+                        restored.waveforms = Arc::clone(&context.waveforms);
+                        restored.waveform_sender = context.waveform_sender.take();
+                        restored.project_path = context.project_path.take();
+                        restored.import_pool = context.import_pool.take();
+                        restored.importing = std::mem::take(&mut context.importing);
+                        *context = restored;
+                        context.reload_audio_sources();
+                    });
+                    if result.is_ok() {
+                        history.clear();
+                        context.emit_state(app);
+                    }
+                    let _ = result_sender.send(MixerResult::ProjectLoaded(result));
+                }
+
+                MixerCommand::SetProjectPath(path) => {
+                    context.set_project_path(path);
+                }
+
+                MixerCommand::GetWaveform(track_id, region_id, start, end, pixels) => {
+                    let result = region_waveform(context, track_id, region_id, start, end, pixels);
+                    let _ = result_sender.send(MixerResult::Waveform(result));
                 }
 
-                MixerCommand::DoesNeedMix => {
-                    // Check if the mixer needs to mix again
-                    let _ = result_sender.send(MixerResult::NeedsMix(needs_mix));
+                MixerCommand::FinishImport(path, track_index, result) => {
+                    finish_import(context, path, track_index, result, app);
+                    context.emit_state(app);
+                }
+
+                MixerCommand::FinishStretch(stretched) => {
+                    context.stretch_cache.extend(stretched);
+                }
+
+                MixerCommand::GetMixer => {
+                    // Used for exporting, so the audio is stretched at the best quality.
+                    // The stretching is left to the export thread, as it takes a while.
+                    let render = context.prepare_render(StretchQuality::High);
+                    let console = context.console.lock().unwrap().clone();
+                    let _ = result_sender.send(MixerResult::Mixer(
+                        render,
+                        context.tempo_map.clone(),
+                        console,
+                        context.master.clone(),
+                    ));
+                }
+
+                MixerCommand::Undo => {
+                    if history.undo(context) {
+                        context.emit_state(app);
+                    }
+                }
+
+                MixerCommand::Redo => {
+                    if history.redo(context) {
+                        context.emit_state(app);
+                    }
                 }
             },
             Err(_) => {
@@ -329,24 +669,266 @@ fn process_mixer(
     }
 }
 
-fn emit_state(
-    mixer: &mut Mixer,
-    node_positions: &HashMap<u32, HashMap<NodeId, (f32, f32)>>,
-    track_colors: &HashMap<u32, String>,
-    app: &AppHandle,
+/// Change the console settings of a track as an undoable edit.
+fn set_track_mix(
+    context: &mut MixerContext,
+    history: &mut History,
+    track_id: u32,
+    coalesce_key: Option<CoalesceKey>,
+    change: impl FnOnce(&mut TrackMix),
 ) {
-    let state = MixerState::from_mixer(mixer, node_positions, track_colors);
-    app.emit("mixer_state", state).ok();
+    let mut mix = context.console.lock().unwrap().get(track_id);
+    change(&mut mix);
+    if let Some(inverse) = Edit::SetTrackMix(track_id, mix).apply(context) {
+        history.record(inverse, coalesce_key);
+    }
+}
+
+/// Add a deep copy of the track right after it, with fresh track, node and region IDs.
+/// The copy goes through the project file format, the same way a saved track is loaded.
+/// Returns the ID of the copy.
+fn duplicate_track(
+    context: &mut MixerContext,
+    track_id: u32,
+    include_regions: bool,
+) -> Result<u32, String> {
+    let index = context
+        .mixer
+        .tracks
+        .iter()
+        .position(|track| track.get_id() == track_id)
+        .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+
+    let mut track_file = TrackFile::from_track(
+        &mut context.mixer.tracks[index],
+        context.track_colors.get(&track_id).cloned(),
+        context.collapsed_folders.contains(&track_id),
+        &context.console.lock().unwrap(),
+        context.node_positions.get(&track_id),
+        context.node_inputs.get(&track_id),
+        &context.region_sources,
+        &context.region_settings,
+    )
+    .ok_or_else(|| format!("Track with ID {} can't be copied.", track_id))?;
+    track_file.name = format!("{} Copy", track_file.name);
+    if !include_regions {
+        track_file.regions.clear();
+    }
+
+    let (duplicate_id, node_ids) = track_file.restore(context)?;
+    // The other tracks keep their IDs, so the sends and sidechains point to the same tracks
+    let track_ids = context
+        .mixer
+        .tracks
+        .iter()
+        .map(|track| (track.get_id(), track.get_id()))
+        .collect::<HashMap<_, _>>();
+    track_file.restore_routing(context, duplicate_id, &track_ids, &node_ids)?;
+    context.request_track_audio(duplicate_id);
+
+    // Tracks are restored at the end of the mixer
+    if let Some(track) = context.mixer.tracks.pop() {
+        let index = (index + 1).min(context.mixer.tracks.len());
+        context.mixer.tracks.insert(index, track);
+    }
+    Ok(duplicate_id)
+}
+
+/// Change the sends of a track, checking that each of them goes to a bus track
+/// without feeding back into the track.
+fn set_sends(
+    context: &mut MixerContext,
+    history: &mut History,
+    track_id: u32,
+    coalesce_key: Option<CoalesceKey>,
+    change: impl FnOnce(&mut Vec<TrackSend>),
+) -> Result<(), String> {
+    let mut sends = context.console.lock().unwrap().sends(track_id).to_vec();
+    change(&mut sends);
+    {
+        let console = context.console.lock().unwrap();
+        for send in &sends {
+            send.validate()?;
+            console.validate_send(track_id, send.bus_id)?;
+        }
+    }
+
+    let inverse = Edit::SetSends(track_id, sends)
+        .apply(context)
+        .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+    history.record(inverse, coalesce_key);
+    Ok(())
+}
+
+/// Check that the output of the source track can be routed into the input of the node.
+fn connect_sidechain(
+    context: &mut MixerContext,
+    track_id: u32,
+    node_id: NodeId,
+    param: &str,
+    source_track_id: u32,
+) -> Result<(), String> {
+    if track_id == MASTER_TRACK_ID || source_track_id == MASTER_TRACK_ID {
+        return Err("The master bus can't be part of a sidechain.".to_string());
+    }
+    if context.mixer.get_track_by_id_mut(source_track_id).is_none() {
+        return Err(format!("Track with ID {} not found.", source_track_id));
+    }
+    let graph = context
+        .graph_mut(track_id)
+        .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+    let node = graph
+        .get_nodes()
+        .iter()
+        .find(|node| node.get_id() == node_id)
+        .ok_or_else(|| format!("Node with ID {} not found in track {}.", node_id, track_id))?;
+    if !node.get_input_list().iter().any(|input| input == param) {
+        return Err(format!("The node has no input named \"{}\".", param));
+    }
+    context
+        .console
+        .lock()
+        .unwrap()
+        .validate_sidechain(track_id, source_track_id)
+}
+
+/// Change the sidechains routed into the nodes of a track.
+fn set_sidechains(
+    context: &mut MixerContext,
+    history: &mut History,
+    track_id: u32,
+    change: impl FnOnce(&mut Vec<Sidechain>),
+) -> Result<(), String> {
+    let mut sidechains = context
+        .console
+        .lock()
+        .unwrap()
+        .sidechains(track_id)
+        .to_vec();
+    change(&mut sidechains);
+    let inverse = Edit::SetSidechains(track_id, sidechains)
+        .apply(context)
+        .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+    history.record(inverse, None);
+    Ok(())
+}
+
+/// Copy the regions of the track to the clipboard, placed relative to the first one.
+fn copy_regions(context: &mut MixerContext, track_id: u32, region_ids: &[u32]) {
+    let Some(track) = context
+        .mixer
+        .tracks
+        .iter()
+        .find(|track| track.get_id() == track_id)
+    else {
+        eprintln!("Track with ID {} not found.", track_id);
+        return;
+    };
+    let regions = track
+        .regions()
+        .into_iter()
+        .filter(|region| region_ids.contains(region.get_id()))
+        .collect::<Vec<_>>();
+    if regions.is_empty() {
+        eprintln!("No regions to copy.");
+        return;
+    }
+
+    let first_start = regions
+        .iter()
+        .map(|region| region.start_time())
+        .fold(Beats::INFINITY, Beats::min);
+    let clipboard = regions
+        .iter()
+        .filter_map(|region| {
+            let offset = region.start_time() - first_start;
+            ClipboardRegion::copy(context, track_id, *region, offset)
+        })
+        .collect();
+    context.clipboard = clipboard;
+}
+
+/// Add copies of the regions to the track, with the first one starting at the beat,
+/// as a single undo step. Returns the IDs of the new regions.
+fn paste_regions(
+    context: &mut MixerContext,
+    history: &mut History,
+    track_id: u32,
+    at_beat: Beats,
+    regions: &[ClipboardRegion],
+) -> Result<Vec<u32>, String> {
+    if regions.is_empty() {
+        return Err("There are no regions to paste.".to_string());
+    }
+    if !at_beat.is_finite() || at_beat < 0.0 {
+        return Err(format!("{} is not a valid beat to paste at.", at_beat));
+    }
+    for region in regions {
+        region.validate(context, track_id)?;
+    }
+
+    let snapshot = TrackSnapshot::take(context, track_id)
+        .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+    let mut region_ids = Vec::with_capacity(regions.len());
+    for region in regions {
+        let start = at_beat + region.offset;
+        match region.paste(context, track_id, start) {
+            Ok(region_id) => region_ids.push(region_id),
+            Err(e) => {
+                Edit::RestoreTrack(snapshot).apply(context);
+                return Err(e);
+            }
+        }
+        context.invalidate(Some(track_id), DirtyRange::region(start, region.duration));
+    }
+    history.record(Edit::RestoreTrack(snapshot), None);
+    Ok(region_ids)
 }
 
+/// Paste a copy of the region right after it. Returns the ID of the new region.
+fn duplicate_region(
+    context: &mut MixerContext,
+    history: &mut History,
+    track_id: u32,
+    region_id: u32,
+) -> Result<Vec<u32>, String> {
+    let track = context
+        .mixer
+        .tracks
+        .iter()
+        .find(|track| track.get_id() == track_id)
+        .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+    let region = track
+        .regions()
+        .into_iter()
+        .find(|region| *region.get_id() == region_id)
+        .ok_or_else(|| {
+            format!(
+                "Region with ID {} not found in track {}.",
+                region_id, track_id
+            )
+        })?;
+
+    let end = region.start_time() + region.duration();
+    let copy = ClipboardRegion::copy(context, track_id, region, 0.0)
+        .ok_or_else(|| "Unknown region type.".to_string())?;
+    paste_regions(context, history, track_id, end, &[copy])
+}
+
+/// Add a region to the track. Returns the ID of the added region.
 fn handle_add_region(
-    mixer: &mut Mixer,
-    node_positions: &mut HashMap<u32, HashMap<NodeId, (f32, f32)>>,
-    track_colors: &mut HashMap<u32, String>,
+    context: &mut MixerContext,
     track_id: u32,
     region_data: RegionData,
     app: &AppHandle,
-) {
+) -> Option<u32> {
+    let mut region_id = None;
+
+    if context.console.lock().unwrap().is_submix(track_id) {
+        eprintln!("Bus and folder tracks can't have regions.");
+        return None;
+    }
+
     match region_data.region_type {
         RegionType::BufferRegion => {
             match region_data.data {
@@ -360,10 +942,9 @@ fn handle_add_region(
                     // };
 
                     // let duration = duration_secs / (60.0 / mixer.tempo);
-                    let mut region_id = 0;
 
                     // Add region
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
+                    if let Some(track) = context.mixer.get_track_by_id_mut(track_id) {
                         if let Some(buffer_track) = track.as_any_mut().downcast_mut::<BufferTrack>()
                         {
                             let region = BufferRegion::empty(region_data.name.clone());
@@ -372,48 +953,31 @@ fn handle_add_region(
                                 region_data.start_time,
                                 region_data.duration,
                             ) {
-                                Ok(id) => id,
+                                Ok(id) => Some(id),
                                 Err(e) => {
                                     eprintln!("Error adding region: {}", e);
-                                    return;
+                                    return None;
                                 }
                             };
                         }
                     }
-                    emit_state(mixer, node_positions, track_colors, app);
 
-                    // Set audio source
-                    let source = match AudioSource::from_path(&path, track_index) {
-                        Ok(source) => Some(source),
-                        Err(e) => {
-                            eprintln!("Error loading audio source: {}", e);
-                            None
-                        }
-                    };
-                    let tempo = mixer.tempo;
-                    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
-                        if let Some(buffer_track) = track.as_any_mut().downcast_mut::<BufferTrack>()
-                        {
-                            if let Some(region) = buffer_track.get_region_mut(region_id) {
-                                if let Some(region) =
-                                    region.as_any_mut().downcast_mut::<BufferRegion>()
-                                {
-                                    region.set_audio_source(source, tempo);
-                                }
-                            }
-                        }
+                    // Set audio source, which is decoded by the import pool
+                    if let Some(region_id) = region_id {
+                        let source = RegionSource::new(path, track_index, context.mixer.tempo);
+                        set_region_source(context, track_id, region_id, source);
                     }
                 }
                 _ => {
                     eprintln!("Invalid data for BufferRegion.");
-                    return;
+                    return None;
                 }
             }
         }
 
         RegionType::NoteRegion => {
             // Create a new note region
-            if let Some(track) = mixer.get_track_by_id_mut(track_id) {
+            if let Some(track) = context.mixer.get_track_by_id_mut(track_id) {
                 if let Some(note_track) = track.as_any_mut().downcast_mut::<NoteTrack>() {
                     let region = NoteRegion::new(
                         region_data.name.clone(),
@@ -425,16 +989,199 @@ fn handle_add_region(
                         region_data.start_time,
                         region_data.duration,
                     ) {
-                        Ok(_) => (),
+                        Ok(id) => region_id = Some(id),
                         Err(e) => {
                             eprintln!("Error adding note region: {}", e);
-                            return;
+                            return None;
                         }
                     }
                 }
             }
-            emit_state(mixer, node_positions, track_colors, app);
+            context.emit_state(app);
+        }
+    }
+    context.emit_state(app);
+    region_id
+}
+
+/// Set the audio file as the source of the buffer region and give the region its audio,
+/// once it's been decoded.
+pub fn set_region_source(
+    context: &mut MixerContext,
+    track_id: u32,
+    region_id: u32,
+    region_source: RegionSource,
+) {
+    // Remember where the audio came from so the project can be saved
+    context
+        .region_sources
+        .insert((track_id, region_id), region_source);
+    context.request_region_audio(track_id, region_id);
+}
+
+/// Give the audio decoded by the import pool to the regions waiting for it.
+/// If it couldn't be decoded, the regions are marked as failed and keep no audio.
+fn finish_import(
+    context: &mut MixerContext,
+    path: String,
+    track_index: usize,
+    result: Result<AudioSource, String>,
+    app: &AppHandle,
+) {
+    let key = (path, track_index);
+    context.importing.remove(&key);
+    let waiting = context
+        .region_status
+        .iter()
+        .filter(|(ids, status)| {
+            **status == RegionStatus::Loading
+                && context
+                    .region_sources
+                    .get(ids)
+                    .is_some_and(|source| source.path == key.0 && source.track_index == key.1)
+        })
+        .map(|(ids, _)| *ids)
+        .collect::<Vec<_>>();
+
+    match result {
+        Ok(source) => {
+            // The sample rate may have changed while the file was being decoded
+            let source = if source.sample_rate == context.mixer.sample_rate {
+                source
+            } else {
+                resample_source(&source, context.mixer.sample_rate)
+            };
+            context.request_waveform(&key.0, key.1, &source);
+            context.audio_cache.insert(key.clone(), source);
+            for (track_id, region_id) in waiting {
+                context.request_region_audio(track_id, region_id);
+                let range = region_dirty_range(context, track_id, region_id);
+                context.invalidate(Some(track_id), range);
+            }
+            ImportProgress::emit(app, &key.0, key.1, ImportStage::Finished);
         }
+        Err(e) => {
+            eprintln!("Error loading audio source: {}", e);
+            // The regions stay, so that undoing and redoing their import keeps working
+            for ids in waiting {
+                context
+                    .region_status
+                    .insert(ids, RegionStatus::Failed(e.clone()));
+            }
+            ImportProgress::emit_failed(app, &key.0, key.1, e);
+        }
+    }
+}
+
+/// Create an empty track of the given type.
+pub fn create_track(track_data: &TrackData) -> Box<dyn Track> {
+    match track_data.track_type {
+        // Bus and folder tracks are buffer tracks without regions, the console feeds them
+        TrackType::BufferTrack | TrackType::BusTrack | TrackType::FolderTrack => Box::new(
+            BufferTrack::new(track_data.name.as_str(), track_data.channels),
+        )
+            as Box<dyn Track>,
+        TrackType::NoteTrack => Box::new(NoteTrack::new(
+            track_data.name.as_str(),
+            track_data.channels,
+        )) as Box<dyn Track>,
+    }
+}
+
+/// Create a new node of the given type.
+pub fn create_node(node_type: &NodeType) -> Box<dyn Node> {
+    match node_type {
+        NodeType::EmptyNode => Box::new(EmptyNode::new()),
+        NodeType::AudioShaderNode => Box::new(AudioShaderNode::new()),
+        NodeType::NoteInputNode => Box::new(NoteInputNode::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Add a note track holding a single region to the context and return its ID.
+    fn add_note_track(context: &mut MixerContext, name: &str) -> u32 {
+        context.mixer.add_track(create_track(&TrackData {
+            name: name.to_string(),
+            channels: 2,
+            track_type: TrackType::NoteTrack,
+        }));
+        let track = context.mixer.tracks.last_mut().unwrap();
+        let track_id = track.get_id();
+        let note_track = track.as_any_mut().downcast_mut::<NoteTrack>().unwrap();
+        let region = NoteRegion::new("Melody".to_string(), 2.0, 4.0);
+        assert!(note_track.add_region(Box::new(region), 2.0, 4.0).is_ok());
+        track_id
+    }
+
+    fn track_names(context: &MixerContext) -> Vec<String> {
+        context
+            .mixer
+            .tracks
+            .iter()
+            .map(|track| track.get_name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_goes_right_after_the_track() {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        let lead_id = add_note_track(&mut context, "Lead");
+        add_note_track(&mut context, "Bass");
+        context.track_colors.insert(lead_id, "#123456".to_string());
+        let mix = TrackMix {
+            volume: -6.0,
+            mute: true,
+            ..TrackMix::default()
+        };
+        context.console.lock().unwrap().set(lead_id, mix);
+
+        let duplicate_id = duplicate_track(&mut context, lead_id, true).unwrap();
+        assert_ne!(duplicate_id, lead_id);
+        assert_eq!(track_names(&context), vec!["Lead", "Lead Copy", "Bass"]);
+        assert_eq!(context.mixer.tracks[1].get_id(), duplicate_id);
+        assert_eq!(context.mixer.tracks[1].regions().len(), 1);
+        assert_eq!(
+            context.track_colors.get(&duplicate_id).map(String::as_str),
+            Some("#123456")
+        );
+        assert_eq!(context.console.lock().unwrap().get(duplicate_id), mix);
+    }
+
+    #[test]
+    fn duplicate_can_leave_out_the_regions() {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        let lead_id = add_note_track(&mut context, "Lead");
+
+        let duplicate_id = duplicate_track(&mut context, lead_id, false).unwrap();
+        assert_eq!(track_names(&context), vec!["Lead", "Lead Copy"]);
+        assert!(context.mixer.tracks[1].regions().is_empty());
+        assert!(duplicate_track(&mut context, duplicate_id + 100, false).is_err());
+    }
+
+    #[test]
+    fn rename_and_move_are_undone() {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        let lead_id = add_note_track(&mut context, "Lead");
+        add_note_track(&mut context, "Bass");
+        add_note_track(&mut context, "Drums");
+        let mut history = History::new();
+
+        history.perform(
+            &mut context,
+            Edit::RenameTrack(lead_id, "Vocals".to_string()),
+        );
+        history.perform(&mut context, Edit::MoveTrack(lead_id, 2));
+        assert_eq!(track_names(&context), vec!["Bass", "Drums", "Vocals"]);
+
+        assert!(history.undo(&mut context));
+        assert_eq!(track_names(&context), vec!["Vocals", "Bass", "Drums"]);
+        assert!(history.undo(&mut context));
+        assert_eq!(track_names(&context), vec!["Lead", "Bass", "Drums"]);
+        assert!(history.redo(&mut context));
+        assert!(history.redo(&mut context));
+        assert_eq!(track_names(&context), vec!["Bass", "Drums", "Vocals"]);
     }
-    emit_state(mixer, node_positions, track_colors, app);
 }
@@ -14,11 +14,21 @@
 // limitations under the License.
 //
 
+use crate::api::mixing::cache::MixCache;
+use crate::api::mixing::console::Console;
+use crate::api::mixing::mixer_context::{MixerRender, StretchKey};
 use crate::api::mixing::region::RegionOperation;
-use crate::api::{AppState, NodeType, RegionData, TrackData};
+use crate::api::mixing::tempo::{TempoCurve, TempoMap};
+use crate::api::project::ProjectFile;
+use crate::api::waveform::Waveform;
+use crate::api::{
+    AppState, AudioSettings, Automation, LoopRange, NodeType, RegionData, TimeSignature, TrackData,
+};
 use knodiq_engine::audio_utils::Beats;
-use knodiq_engine::{Mixer, NodeId, Sample, Value};
-use std::sync::{Mutex, MutexGuard};
+use knodiq_engine::{AudioSource, NodeId, Sample, Track, Value};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, MutexGuard};
 use tauri::State;
 
 pub enum MixerCommand {
@@ -37,11 +47,76 @@ pub enum MixerCommand {
     /// - track_id: `u32`
     RemoveTrack(u32),
 
+    /// Rename a track.
+    /// - track_id: `u32`
+    /// - name: `String`
+    RenameTrack(u32, String),
+
+    /// Move a track to another index in the mixer.
+    /// - track_id: `u32`
+    /// - new_index: `usize`
+    MoveTrack(u32, usize),
+
+    /// Add a copy of a track right after it, with its graph, color and mixing settings.
+    /// - track_id: `u32`
+    /// - include_regions: `bool`
+    DuplicateTrack(u32, bool),
+
+    /// Move a track into a folder track, or out of it with `None`.
+    /// - track_id: `u32`
+    /// - parent_id: `Option<u32>`
+    SetTrackParent(u32, Option<u32>),
+
+    /// Hide or show the children of a folder track.
+    /// - track_id: `u32`
+    /// - collapsed: `bool`
+    SetFolderCollapsed(u32, bool),
+
     /// Set a track color.
     /// - track_id: `u32`
     /// - color: `String`
     SetTrackColor(u32, String),
 
+    /// Set the volume of a track.
+    /// - track_id: `u32`
+    /// - volume: `f32` (dB)
+    SetTrackVolume(u32, f32),
+
+    /// Set the pan of a track.
+    /// - track_id: `u32`
+    /// - pan: `f32` (-1 to 1)
+    SetTrackPan(u32, f32),
+
+    /// Mute or unmute a track.
+    /// - track_id: `u32`
+    /// - mute: `bool`
+    SetTrackMute(u32, bool),
+
+    /// Solo or unsolo a track.
+    /// - track_id: `u32`
+    /// - solo: `bool`
+    SetTrackSolo(u32, bool),
+
+    /// Set whether a track keeps playing when other tracks are soloed.
+    /// - track_id: `u32`
+    /// - solo_safe: `bool`
+    SetTrackSoloSafe(u32, bool),
+    /// Send a track to a bus track, or change the existing send.
+    /// - track_id: `u32`
+    /// - bus_id: `u32`
+    /// - level: `f32` (dB)
+    /// - pre_fader: `bool`
+    SetSend(u32, u32, f32, bool),
+    /// Stop sending a track to a bus track.
+    /// - track_id: `u32`
+    /// - bus_id: `u32`
+    RemoveSend(u32, u32),
+    /// Set the automation of the level of a send, or clear it with an empty automation.
+    /// - track_id: `u32`
+    /// - bus_id: `u32`
+    /// - automation: `Automation`
+    SetSendAutomation(u32, u32, Automation),
+
     /// Add a region to the specified track.
     /// - track_id: `u32`
     /// - region_data: `RegionData`
@@ -57,6 +132,39 @@ pub enum MixerCommand {
     /// - region_id: `u32`
     /// - operation: `RegionOperation`
     ApplyRegionOp(u32, u32, RegionOperation),
+    /// Split a region in two at the beat.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - at_beat: `Beats`
+    /// - cut_notes: `bool` (whether notes crossing the split are cut in two)
+    SplitRegion(u32, u32, Beats, bool),
+    /// Move the start of a region, keeping its audio or notes in place on the timeline.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - start: `Beats`
+    TrimRegionStart(u32, u32, Beats),
+    /// Move the end of a region, keeping its start.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - end: `Beats`
+    TrimRegionEnd(u32, u32, Beats),
+    /// Move the audio or notes of a region by the beats, keeping the region in place.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - delta: `Beats`
+    SlipRegion(u32, u32, Beats),
+    /// Add a copy of a region right after it.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    DuplicateRegion(u32, u32),
+    /// Copy regions of a track to the clipboard.
+    /// - track_id: `u32`
+    /// - region_ids: `Vec<u32>`
+    CopyRegions(u32, Vec<u32>),
+    /// Paste the regions on the clipboard onto a track.
+    /// - track_id: `u32`
+    /// - at_beat: `Beats` (where the first copied region starts)
+    PasteRegions(u32, Beats),
 
     /// Connect two nodes in the graph.
     /// - track_id: `u32`
@@ -86,6 +194,20 @@ pub enum MixerCommand {
         String,
     ),
 
+    /// Route the output of another track into an input of a node,
+    /// replacing the track already routed into it.
+    /// - track_id: `u32`
+    /// - node_id: `knodiq_engine::NodeId`
+    /// - param: `String`
+    /// - source_track_id: `u32`
+    ConnectSidechain(u32, knodiq_engine::NodeId, String, u32),
+
+    /// Stop routing another track into an input of a node.
+    /// - track_id: `u32`
+    /// - node_id: `knodiq_engine::NodeId`
+    /// - param: `String`
+    DisconnectSidechain(u32, knodiq_engine::NodeId, String),
+
     /// Add a node to a track.
     /// - track_id: `u32`
     /// - node_type: `NodeType`
@@ -122,8 +244,82 @@ pub enum MixerCommand {
     /// - shader: `String`
     SetAudioShader(u32, NodeId, String),
 
-    /// Check if the mixer needs to mix.
-    DoesNeedMix,
+    /// Set the tempo of the project.
+    /// - tempo: `f32` (BPM)
+    SetTempo(f32),
+
+    /// Set the time signature of the project.
+    /// - time_signature: `TimeSignature`
+    SetTimeSignature(TimeSignature),
+
+    /// Add a tempo event to the tempo map.
+    /// - beat: `Beats`
+    /// - tempo: `f32` (BPM)
+    /// - curve: `TempoCurve`
+    AddTempoEvent(Beats, f32, TempoCurve),
+
+    /// Move a tempo event to another beat.
+    /// - event_id: `u32`
+    /// - beat: `Beats`
+    MoveTempoEvent(u32, Beats),
+
+    /// Remove a tempo event from the tempo map.
+    /// - event_id: `u32`
+    RemoveTempoEvent(u32),
+
+    /// Set the sample rate and channel layout of the project.
+    /// - settings: `AudioSettings`
+    SetAudioSettings(AudioSettings),
+
+    /// Get the sample rate and channel layout of the project.
+    GetAudioSettings,
+
+    /// Set the section of the timeline to loop over.
+    /// - start: `Beats`
+    /// - end: `Beats`
+    SetLoopRange(Beats, Beats),
+
+    /// Enable or disable looping.
+    /// - enabled: `bool`
+    SetLoopEnabled(bool),
+
+    /// Get the whole project in its on-disk representation.
+    GetProject,
+
+    /// Replace the current project with the given one.
+    /// - project: `ProjectFile`
+    LoadProject(ProjectFile),
+
+    /// Revert the last edit.
+    Undo,
+
+    /// Reapply the last reverted edit.
+    Redo,
+
+    /// Get a copy of the mixer warped by the tempo map, for offline rendering.
+    GetMixer,
+
+    /// Set the path of the project file, next to which the waveforms are cached.
+    /// - path: `PathBuf`
+    SetProjectPath(PathBuf),
+
+    /// Get the waveform of a buffer region between the beats relative to its start.
+    /// - track_id: `u32`
+    /// - region_id: `u32`
+    /// - start: `Beats`
+    /// - end: `Beats`
+    /// - pixels: `usize`
+    GetWaveform(u32, u32, Beats, Beats, usize),
+
+    /// Give the audio decoded by the import pool to the regions waiting for it.
+    /// - path: `String`
+    /// - track_index: `usize`
+    /// - result: `Result<AudioSource, String>` (the decoded audio, or why it couldn't be)
+    FinishImport(String, usize, Result<AudioSource, String>),
+
+    /// Keep the audio stretched by the mixing thread, so that the next mix doesn't stretch it again.
+    /// - stretched: `Vec<(StretchKey, AudioSource)>`
+    FinishStretch(Vec<(StretchKey, AudioSource)>),
 }
 
 pub enum MixerResult {
@@ -131,19 +327,51 @@ pub enum MixerResult {
     InputNode(NodeId),
     /// Result of the `GetOutputNode` command.
     OutputNode(NodeId),
-    /// Result of the `DoesNeedMix` command.
-    NeedsMix(bool),
     /// Result of the `SetAudioShader` command.
     AudioShaderErrors(Vec<String>),
+    /// Result of the `GetAudioSettings` command.
+    AudioSettings(AudioSettings),
+    /// Result of the `SetAudioSettings` command.
+    AudioSettingsChanged(Result<(), String>),
+    /// Result of the `GetProject` command.
+    Project(ProjectFile),
+    /// Result of the `LoadProject` command.
+    ProjectLoaded(Result<(), String>),
+    /// Result of the commands changing the routing between tracks.
+    RoutingChanged(Result<(), String>),
+    /// Result of the `DuplicateRegion` and `PasteRegions` commands, with the IDs of the new regions.
+    RegionsPasted(Result<Vec<u32>, String>),
+    /// Result of the `GetMixer` command, with the audio of the regions still to be rendered.
+    /// The tempo map converts beats to the timeline of the warped mixer,
+    /// the console holds the volume, pan, mute, solo and sends of the tracks,
+    /// and the track holds the graph of the master bus.
+    Mixer(MixerRender, TempoMap, Console, Box<dyn Track>),
+    /// Result of the `GetWaveform` command, `None` while the audio is being summarized.
+    Waveform(Result<Option<Waveform>, String>),
 }
 
 pub enum MixingThreadCommand {
     /// Command to mix audio.
+    /// - `render`: The mixer to be mixed, with the audio of its regions still to be stretched.
     /// - `start_beat`: The beat at which to start mixing.
+    /// - `loop_range`: The start and end beats to loop over, if looping is enabled.
+    /// - `master_track`: The track holding the graph of the master bus.
+    /// - `tempo_map`: The tempo map the mixer was warped with, to read the automation at.
+    /// - `cache`: The cache to take the rendered segments from and store them in.
+    /// - `console`: The volume, pan, mute and solo of the tracks, read while mixing.
+    /// - `should_stop`: Stops this mix when set. Every mix gets its own.
     /// - `callback`: A callback function that takes a sample and the current beat.
-    StartMixing(Mixer, Beats, Box<dyn Fn(Sample, Beats) + Send>),
-    /// Stop any active mixing process.
-    StopMixing,
+    StartMixing(
+        MixerRender,
+        Beats,
+        Option<(Beats, Beats)>,
+        Box<dyn Track>,
+        TempoMap,
+        Arc<Mutex<MixCache>>,
+        Arc<Mutex<Console>>,
+        Arc<AtomicBool>,
+        Box<dyn Fn(Sample, Beats) + Send>,
+    ),
 }
 
 pub fn send_mixer_command(command: MixerCommand, state: &State<'_, Mutex<AppState>>) {
@@ -166,3 +394,26 @@ pub fn send_mixer_command_locked(command: MixerCommand, locked_state: &MutexGuar
         eprintln!("Mixer command sender not initialized.");
     }
 }
+
+/// Send a command to the mixer and wait for its result.
+pub fn request_mixer_result(
+    command: MixerCommand,
+    state: &State<'_, Mutex<AppState>>,
+) -> Result<MixerResult, String> {
+    let locked_state = state.lock().map_err(|e| e.to_string())?;
+    let mixer_command_sender = locked_state
+        .mixer_command_sender
+        .as_ref()
+        .ok_or("Mixer command sender not initialized.")?;
+    let mixer_result_receiver = locked_state
+        .mixer_result_receiver
+        .as_ref()
+        .ok_or("Mixer result receiver not initialized.")?;
+
+    mixer_command_sender
+        .send(command)
+        .map_err(|e| format!("Failed to send mixer command: {}", e))?;
+    mixer_result_receiver
+        .recv()
+        .map_err(|e| format!("Error receiving from mixer: {}", e))
+}
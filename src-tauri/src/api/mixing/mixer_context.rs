@@ -0,0 +1,866 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::data::track_mix::db_to_gain;
+use crate::api::import::{ImportJob, ImportPool};
+use crate::api::mixing::cache::{DirtyRange, MixCache};
+use crate::api::mixing::console::Console;
+use crate::api::mixing::graph_processor::is_pass_through;
+use crate::api::mixing::master_bus::{MASTER_TRACK_ID, create_master_track};
+use crate::api::mixing::region::{
+    ClipboardRegion, RegionSource, RegionStatus, StretchQuality, apply_fades, crossfades,
+    effective_fades, loop_audio, loop_notes, repeat_starts, scale_velocities, stretch_source,
+};
+use crate::api::mixing::resample::resample_source;
+use crate::api::mixing::tempo::TempoMap;
+use crate::api::waveform::{WaveformJob, WaveformStore, waveform_cache_dir};
+use crate::api::{
+    AudioSettings, FadeCurve, LoopRange, MixerState, RegionSettings, Sidechain, TimeSignature,
+    TrackMix, TrackSend,
+};
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::mixing::track::BufferTrack;
+use knodiq_engine::{AudioSource, Beats, Graph, Mixer, NodeId, Track, Value};
+use knodiq_note::NoteRegion;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// Time the mix of a region is assumed to keep changing after its end,
+/// e.g. because of a reverb.
+const EFFECT_TAIL_SECONDS: usize = 5;
+
+/// Everything the mixer thread owns: the mixer itself, plus the side tables
+/// holding the data the engine doesn't keep track of.
+pub struct MixerContext {
+    /// The mixer containing the tracks of the project.
+    pub mixer: Mixer,
+    /// Track holding the graph of the master bus, which the summed tracks go through.
+    /// It's addressed with `MASTER_TRACK_ID`, and isn't part of the mixer.
+    pub master: Box<dyn Track>,
+    /// Position of each node in the graph editor, keyed by track ID.
+    pub node_positions: HashMap<u32, HashMap<NodeId, (f32, f32)>>,
+    /// Color of each track, keyed by track ID.
+    pub track_colors: HashMap<u32, String>,
+    /// IDs of the folder tracks whose children are hidden.
+    pub collapsed_folders: HashSet<u32>,
+    /// Audio file each buffer region was loaded from, keyed by track ID and region ID.
+    pub region_sources: HashMap<(u32, u32), RegionSource>,
+    /// Settings of the regions which differ from the defaults, keyed by track ID and region ID.
+    pub region_settings: HashMap<(u32, u32), RegionSettings>,
+    /// Buffer regions whose audio is being decoded or couldn't be, keyed by track ID and region ID.
+    pub region_status: HashMap<(u32, u32), RegionStatus>,
+    /// Input property values set on the nodes, keyed by track ID.
+    pub node_inputs: HashMap<u32, HashMap<NodeId, HashMap<String, Value>>>,
+    /// Time signature of the project.
+    pub time_signature: TimeSignature,
+    /// Tempo changes on top of the tempo of the mixer.
+    pub tempo_map: TempoMap,
+    /// Section of the timeline the playback loops over.
+    pub loop_range: LoopRange,
+    /// Decoded audio files at the sample rate of the mixer, keyed by path and track index.
+    /// Buffer regions are given the audio together with the tempo,
+    /// so it's kept here to give it to them again when the tempo changes.
+    pub audio_cache: HashMap<(String, usize), AudioSource>,
+    /// Time-stretched and pitch-shifted audio of the buffer regions, from the last render.
+    pub stretch_cache: HashMap<StretchKey, AudioSource>,
+    /// Regions copied with `copy_regions`, waiting to be pasted.
+    pub clipboard: Vec<ClipboardRegion>,
+    /// Rendered segments of the mix, shared with the mixing thread.
+    pub mix_cache: Arc<Mutex<MixCache>>,
+    /// Volume, pan, mute, solo and routing of the tracks, shared with the mixing thread
+    /// so that changes are heard while playing.
+    pub console: Arc<Mutex<Console>>,
+    /// Summaries of the audio files for drawing their waveforms,
+    /// filled by the waveform thread.
+    pub waveforms: Arc<Mutex<WaveformStore>>,
+    /// Sends the decoded audio files to the waveform thread to be summarized.
+    pub waveform_sender: Option<Sender<WaveformJob>>,
+    /// Path of the project file, once it has been saved or opened.
+    pub project_path: Option<PathBuf>,
+    /// Workers decoding the imported audio files.
+    /// Without them, the files are decoded on the mixer thread.
+    pub import_pool: Option<ImportPool>,
+    /// Audio files queued in the import pool, keyed by path and track index.
+    pub importing: HashSet<(String, usize)>,
+}
+
+/// Everything the stretched audio of a buffer region depends on.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct StretchKey {
+    pub path: String,
+    pub track_index: usize,
+    /// Bits of the offset of the region into the audio.
+    pub offset: u64,
+    /// Bits of the stretch ratio.
+    pub ratio: u64,
+    /// Bits of the pitch shift in semitones.
+    pub semitones: u32,
+    /// Number of frames the region plays.
+    pub frames: usize,
+    pub quality: StretchQuality,
+}
+
+/// Placement of the audio of a buffer region in the rendered mixer, in frames.
+struct BufferRegionRender {
+    track_id: u32,
+    region_id: u32,
+    source: RegionSource,
+    settings: RegionSettings,
+    /// How much longer the audio plays than at its own speed.
+    ratio: f64,
+    fade_in: (usize, FadeCurve),
+    fade_out: (usize, FadeCurve),
+    /// Starts of the repeats of the content after the first one.
+    repeats: Vec<usize>,
+    /// Length of the content played before it repeats.
+    content_length: usize,
+    length: usize,
+}
+
+/// Audio of a buffer region to be rendered.
+enum RegionAudio {
+    /// Audio of the file, played at its own speed.
+    Unstretched(AudioSource),
+    /// Audio of the file, still to be stretched and stored with the key.
+    ToStretch(AudioSource, StretchKey),
+    /// Stretched audio of the region, taken from the cache.
+    Stretched(AudioSource),
+}
+
+/// Copy of the mixer waiting for the audio of its buffer regions to be rendered.
+/// It doesn't need the context, so the stretching can run on another thread.
+pub struct MixerRender {
+    mixer: Mixer,
+    tempo: f32,
+    quality: StretchQuality,
+    regions: Vec<(BufferRegionRender, RegionAudio)>,
+}
+
+impl MixerRender {
+    /// Apply the stretch, pitch shift, repeats, fades, gain and polarity of the regions
+    /// to their audio. Returns the mixer and the audio which was stretched, to be cached.
+    pub fn finish(self) -> (Mixer, Vec<(StretchKey, AudioSource)>) {
+        let MixerRender {
+            mut mixer,
+            tempo,
+            quality,
+            regions,
+        } = self;
+        let mut stretched = Vec::new();
+        for (render, audio) in regions {
+            let BufferRegionRender {
+                source: region_source,
+                settings,
+                ratio,
+                ..
+            } = &render;
+            let mut source = match audio {
+                RegionAudio::Unstretched(mut source) => {
+                    region_source.trim(&mut source);
+                    source
+                }
+                RegionAudio::ToStretch(mut source, key) => {
+                    region_source.trim(&mut source);
+                    // Only the audio the region plays is stretched
+                    let input_frames = (render.content_length as f64 / ratio).ceil() as usize
+                        + quality.margin(source.sample_rate);
+                    for channel in source.data.iter_mut() {
+                        channel.truncate(input_frames);
+                    }
+                    let source = stretch_source(&source, *ratio, settings.pitch_shift, quality);
+                    stretched.push((key, source.clone()));
+                    source
+                }
+                RegionAudio::Stretched(source) => source,
+            };
+            if !render.repeats.is_empty() {
+                loop_audio(
+                    &mut source,
+                    &render.repeats,
+                    render.content_length,
+                    render.length,
+                );
+            }
+            apply_fades(&mut source, render.fade_in, render.fade_out, render.length);
+            let amplitude = settings.amplitude();
+            if amplitude != 1.0 {
+                for channel in source.data.iter_mut() {
+                    channel.iter_mut().for_each(|sample| *sample *= amplitude);
+                }
+            }
+            set_buffer_audio(
+                &mut mixer,
+                render.track_id,
+                render.region_id,
+                Some(source),
+                tempo,
+            );
+        }
+        (mixer, stretched)
+    }
+}
+
+/// Side table entries of a single track.
+#[derive(Default)]
+pub struct TrackSideData {
+    pub color: Option<String>,
+    pub mix: Option<TrackMix>,
+    pub sends: Vec<TrackSend>,
+    pub sidechains: Vec<Sidechain>,
+    pub is_bus: bool,
+    pub is_folder: bool,
+    pub collapsed: bool,
+    /// Folder the track is in.
+    pub parent: Option<u32>,
+    /// Tracks which were in the folder.
+    pub children: Vec<u32>,
+    /// Sends of the tracks which sent to the bus, as they were before, keyed by track ID.
+    pub sends_to: Vec<(u32, Vec<TrackSend>)>,
+    /// Sidechains of the tracks which took the output of the track, as they were before,
+    /// keyed by track ID.
+    pub sidechains_from: Vec<(u32, Vec<Sidechain>)>,
+    pub node_positions: HashMap<NodeId, (f32, f32)>,
+    pub node_inputs: HashMap<NodeId, HashMap<String, Value>>,
+    /// Audio file of each buffer region, keyed by region ID.
+    pub region_sources: HashMap<u32, RegionSource>,
+    /// Settings of each region, keyed by region ID.
+    pub region_settings: HashMap<u32, RegionSettings>,
+}
+
+impl MixerContext {
+    pub fn new(mixer: Mixer) -> Self {
+        let mix_cache = Arc::new(Mutex::new(MixCache::new(mixer.sample_rate)));
+        let master = create_master_track(mixer.channels);
+        MixerContext {
+            mixer,
+            master,
+            node_positions: HashMap::new(),
+            track_colors: HashMap::new(),
+            collapsed_folders: HashSet::new(),
+            region_sources: HashMap::new(),
+            region_settings: HashMap::new(),
+            region_status: HashMap::new(),
+            node_inputs: HashMap::new(),
+            time_signature: TimeSignature::default(),
+            tempo_map: TempoMap::new(),
+            loop_range: LoopRange::default(),
+            audio_cache: HashMap::new(),
+            stretch_cache: HashMap::new(),
+            clipboard: Vec::new(),
+            mix_cache,
+            console: Arc::new(Mutex::new(Console::new())),
+            waveforms: Arc::new(Mutex::new(WaveformStore::new())),
+            waveform_sender: None,
+            project_path: None,
+            import_pool: None,
+            importing: HashSet::new(),
+        }
+    }
+
+    /// Remove every side table entry belonging to the track and return them, together with
+    /// the routing of the other tracks to it: the tracks in it, the sends to it and
+    /// the sidechains taking its output.
+    pub fn take_track_data(&mut self, track_id: u32) -> TrackSideData {
+        let region_sources = self
+            .region_sources
+            .iter()
+            .filter(|((id, _), _)| *id == track_id)
+            .map(|((_, region_id), source)| (*region_id, source.clone()))
+            .collect();
+        self.region_sources.retain(|(id, _), _| *id != track_id);
+        let region_settings = self
+            .region_settings
+            .iter()
+            .filter(|((id, _), _)| *id == track_id)
+            .map(|((_, region_id), settings)| (*region_id, *settings))
+            .collect();
+        self.region_settings.retain(|(id, _), _| *id != track_id);
+        self.region_status.retain(|(id, _), _| *id != track_id);
+
+        let mut console = self.console.lock().unwrap();
+        let is_bus = console.is_bus(track_id);
+        let is_folder = console.is_folder(track_id);
+        console.set_bus(track_id, false);
+        console.set_folder(track_id, false);
+        TrackSideData {
+            color: self.track_colors.remove(&track_id),
+            mix: console.remove(track_id),
+            sends: console.set_sends(track_id, Vec::new()),
+            sidechains: console.set_sidechains(track_id, Vec::new()),
+            is_bus,
+            is_folder,
+            collapsed: self.collapsed_folders.remove(&track_id),
+            parent: console.set_parent(track_id, None),
+            children: console.remove_children(track_id),
+            sends_to: console.remove_sends_to(track_id),
+            sidechains_from: console.remove_sidechains_from(track_id),
+            node_positions: self.node_positions.remove(&track_id).unwrap_or_default(),
+            node_inputs: self.node_inputs.remove(&track_id).unwrap_or_default(),
+            region_sources,
+            region_settings,
+        }
+    }
+
+    /// Put back the side table entries and the routing taken with `take_track_data`.
+    pub fn restore_track_data(&mut self, track_id: u32, data: TrackSideData) {
+        if let Some(color) = data.color {
+            self.track_colors.insert(track_id, color);
+        }
+        let mut console = self.console.lock().unwrap();
+        if let Some(mix) = data.mix {
+            console.set(track_id, mix);
+        }
+        console.set_sends(track_id, data.sends);
+        console.set_sidechains(track_id, data.sidechains);
+        console.set_bus(track_id, data.is_bus);
+        console.set_folder(track_id, data.is_folder);
+        console.set_parent(track_id, data.parent);
+        for child_id in data.children {
+            console.set_parent(child_id, Some(track_id));
+        }
+        for (sender_id, sends) in data.sends_to {
+            console.set_sends(sender_id, sends);
+        }
+        for (destination_id, sidechains) in data.sidechains_from {
+            console.set_sidechains(destination_id, sidechains);
+        }
+        drop(console);
+        if data.collapsed {
+            self.collapsed_folders.insert(track_id);
+        }
+        self.node_positions.insert(track_id, data.node_positions);
+        self.node_inputs.insert(track_id, data.node_inputs);
+        for (region_id, source) in data.region_sources {
+            self.region_sources.insert((track_id, region_id), source);
+        }
+        for (region_id, settings) in data.region_settings {
+            self.region_settings.insert((track_id, region_id), settings);
+        }
+    }
+
+    /// Get the graph of the track, or of the master bus for `MASTER_TRACK_ID`.
+    pub fn graph_mut(&mut self, track_id: u32) -> Option<&mut Graph> {
+        if track_id == MASTER_TRACK_ID {
+            return Some(self.master.graph_mut());
+        }
+        self.mixer
+            .get_track_by_id_mut(track_id)
+            .map(|track| track.graph_mut())
+    }
+
+    /// Decode the audio file and convert it to the sample rate of the mixer,
+    /// or get it from the cache if it was already decoded.
+    pub fn load_audio_source(
+        &mut self,
+        path: &str,
+        track_index: usize,
+    ) -> Result<AudioSource, String> {
+        let key = (path.to_string(), track_index);
+        if let Some(source) = self.audio_cache.get(&key) {
+            return Ok(source.clone());
+        }
+
+        let source = AudioSource::from_path(path, track_index).map_err(|e| e.to_string())?;
+        let source = resample_source(&source, self.mixer.sample_rate);
+        self.audio_cache.insert(key, source.clone());
+        self.request_waveform(path, track_index, &source);
+        Ok(source)
+    }
+
+    /// Give the buffer region the audio of its source. Audio which hasn't been decoded yet
+    /// is queued in the import pool, and the region is marked as loading until it's done.
+    pub fn request_region_audio(&mut self, track_id: u32, region_id: u32) {
+        let ids = (track_id, region_id);
+        let Some(region_source) = self.region_sources.get(&ids) else {
+            return;
+        };
+        let key = (region_source.path.clone(), region_source.track_index);
+        if let Some(source) = self.audio_cache.get(&key).cloned() {
+            self.region_status.remove(&ids);
+            self.assign_audio_source(track_id, region_id, Some(source));
+            return;
+        }
+
+        let Some(import_pool) = &self.import_pool else {
+            match self.load_audio_source(&key.0, key.1) {
+                Ok(source) => {
+                    self.region_status.remove(&ids);
+                    self.assign_audio_source(track_id, region_id, Some(source));
+                }
+                Err(e) => {
+                    eprintln!("Error loading audio source: {}", e);
+                    self.region_status.insert(ids, RegionStatus::Failed(e));
+                    self.assign_audio_source(track_id, region_id, None);
+                }
+            }
+            return;
+        };
+
+        self.region_status.insert(ids, RegionStatus::Loading);
+        if self.importing.insert(key.clone()) {
+            import_pool.submit(ImportJob {
+                path: key.0,
+                track_index: key.1,
+                sample_rate: self.mixer.sample_rate,
+            });
+        }
+        self.assign_audio_source(track_id, region_id, None);
+    }
+
+    /// Give the buffer regions of the track their audio again. Restored copies of the track
+    /// hold the audio for the tempo and sample rate at the time they were taken.
+    pub fn request_track_audio(&mut self, track_id: u32) {
+        let region_ids = self
+            .region_sources
+            .keys()
+            .filter(|(id, _)| *id == track_id)
+            .map(|(_, region_id)| *region_id)
+            .collect::<Vec<_>>();
+        for region_id in region_ids {
+            self.request_region_audio(track_id, region_id);
+        }
+    }
+
+    /// Change where the project is saved, keeping the summaries of its audio files
+    /// in the new cache directory.
+    pub fn set_project_path(&mut self, path: PathBuf) {
+        let old_dir = waveform_cache_dir(self.project_path.as_deref());
+        self.project_path = Some(path);
+        let cache_dir = waveform_cache_dir(self.project_path.as_deref());
+        if cache_dir != old_dir {
+            self.waveforms
+                .lock()
+                .unwrap()
+                .save(&self.audio_cache, &cache_dir);
+        }
+    }
+
+    /// Have the waveform thread summarize the decoded audio, unless it already has.
+    pub fn request_waveform(&self, path: &str, track_index: usize, source: &AudioSource) {
+        let Some(sender) = &self.waveform_sender else {
+            return;
+        };
+        if !self.waveforms.lock().unwrap().request(path, track_index) {
+            return;
+        }
+        let job = WaveformJob {
+            path: path.to_string(),
+            track_index,
+            source: source.clone(),
+            cache_dir: waveform_cache_dir(self.project_path.as_deref()),
+        };
+        if sender.send(job).is_err() {
+            eprintln!("The waveform thread has stopped.");
+        }
+    }
+
+    /// Give the audio source to the buffer region, stretched to the current tempo.
+    /// The audio before the offset of the region is cut.
+    pub fn assign_audio_source(
+        &mut self,
+        track_id: u32,
+        region_id: u32,
+        mut source: Option<AudioSource>,
+    ) {
+        if let (Some(source), Some(region_source)) = (
+            source.as_mut(),
+            self.region_sources.get(&(track_id, region_id)),
+        ) {
+            region_source.trim(source);
+        }
+        let tempo = self.mixer.tempo;
+        set_buffer_audio(&mut self.mixer, track_id, region_id, source, tempo);
+    }
+
+    /// Copy the mixer to be mixed: warped by the tempo map, with the repeats, stretch,
+    /// pitch shift, fades, gain and polarity of the regions still to be applied to their audio
+    /// and the repeats and gain applied to their notes. It doesn't need the context,
+    /// so it can be rendered on another thread. Stretched audio is taken from the cache.
+    pub fn prepare_render(&mut self, quality: StretchQuality) -> MixerRender {
+        // Repeats are placed in beats, so the notes are looped before being warped
+        let looped = self.loop_note_regions();
+        let mut mixer = self
+            .tempo_map
+            .warp_mixer(looped.as_ref().unwrap_or(&self.mixer));
+        let tempo = self.mixer.tempo;
+        let samples_per_beat = self.mixer.samples_per_beat();
+        let tempo_map = &self.tempo_map;
+        // Fades are placed in beats, but the audio plays at its own speed
+        let frames = |from: Beats, to: Beats| {
+            let linear_beats = tempo_map.to_linear(tempo, to) - tempo_map.to_linear(tempo, from);
+            (linear_beats * samples_per_beat).max(0.0).round() as usize
+        };
+
+        let mut regions = Vec::new();
+        let mut used_keys = HashSet::new();
+        for track in &self.mixer.tracks {
+            let track_id = track.get_id();
+            let crossfades = crossfades(track.as_ref());
+            for region in track.regions() {
+                let region_id = *region.get_id();
+                let settings = self
+                    .region_settings
+                    .get(&(track_id, region_id))
+                    .copied()
+                    .unwrap_or_default();
+                if region.as_any().is::<NoteRegion>() {
+                    if settings.gain != 0.0 {
+                        scale_note_region(&mut mixer, track_id, region_id, settings.gain);
+                    }
+                    continue;
+                }
+                let Some(region_source) = self.region_sources.get(&(track_id, region_id)) else {
+                    continue;
+                };
+
+                let crossfade = crossfades.get(&region_id).copied().unwrap_or_default();
+                let (fade_in, fade_out) = effective_fades(&settings, crossfade);
+                let start = region.start_time();
+                let duration = region.duration();
+                let end = start + duration;
+                let content_duration = settings
+                    .loop_length
+                    .map_or(duration, |length| length.min(duration));
+                let render = BufferRegionRender {
+                    track_id,
+                    region_id,
+                    source: region_source.clone(),
+                    settings,
+                    ratio: region_source.stretch_ratio(tempo, settings.stretch),
+                    fade_in: (
+                        frames(start, start + fade_in.length.min(duration)),
+                        fade_in.curve,
+                    ),
+                    fade_out: (
+                        frames(end - fade_out.length.min(duration), end),
+                        fade_out.curve,
+                    ),
+                    repeats: repeat_starts(duration, settings.loop_length)
+                        .into_iter()
+                        .map(|repeat| frames(start, start + repeat))
+                        .collect(),
+                    content_length: frames(start, start + content_duration),
+                    length: frames(start, end),
+                };
+
+                let stretched = (render.ratio - 1.0).abs() > 1e-6 || settings.pitch_shift != 0.0;
+                if !stretched
+                    && render.repeats.is_empty()
+                    && render.fade_in.0 == 0
+                    && render.fade_out.0 == 0
+                    && settings.amplitude() == 1.0
+                {
+                    continue;
+                }
+                let key = (region_source.path.clone(), region_source.track_index);
+                let Some(source) = self.audio_cache.get(&key) else {
+                    continue;
+                };
+                let audio = match stretched {
+                    true => {
+                        let key = StretchKey {
+                            path: region_source.path.clone(),
+                            track_index: region_source.track_index,
+                            offset: region_source.offset.to_bits(),
+                            ratio: render.ratio.to_bits(),
+                            semitones: settings.pitch_shift.to_bits(),
+                            frames: render.content_length,
+                            quality,
+                        };
+                        used_keys.insert(key.clone());
+                        match self.stretch_cache.get(&key) {
+                            Some(stretched) => RegionAudio::Stretched(stretched.clone()),
+                            None => RegionAudio::ToStretch(source.clone(), key),
+                        }
+                    }
+                    false => RegionAudio::Unstretched(source.clone()),
+                };
+                regions.push((render, audio));
+            }
+        }
+
+        // Audio of regions which changed won't be used again
+        self.stretch_cache
+            .retain(|key, _| key.quality != quality || used_keys.contains(key));
+        MixerRender {
+            mixer,
+            tempo,
+            quality,
+            regions,
+        }
+    }
+
+    /// Copy of the mixer with the notes of the looping note regions repeated,
+    /// or `None` if no region loops.
+    fn loop_note_regions(&self) -> Option<Mixer> {
+        let looping = self
+            .region_settings
+            .iter()
+            .filter_map(|(ids, settings)| Some((*ids, settings.loop_length?)))
+            .collect::<Vec<_>>();
+        if looping.is_empty() {
+            return None;
+        }
+
+        let mut mixer = self.mixer.clone();
+        for ((track_id, region_id), loop_length) in looping {
+            let Some(region) = mixer
+                .get_track_by_id_mut(track_id)
+                .and_then(|track| track.get_region_mut(region_id))
+            else {
+                continue;
+            };
+            let duration = region.duration();
+            if let Some(note_region) = region.as_any_mut().downcast_mut::<NoteRegion>() {
+                loop_notes(note_region, loop_length, duration);
+            }
+        }
+        Some(mixer)
+    }
+
+    /// Change the tempo of the project, and give the audio sources to the buffer regions again
+    /// so that they stay in sync with the new tempo.
+    pub fn set_tempo(&mut self, tempo: f32) {
+        self.mixer.tempo = tempo;
+        self.reload_audio_sources();
+    }
+
+    /// Check that the project can switch to the settings. Tracks and graphs are built
+    /// for a number of channels, so the channel layout can only change while there are none.
+    pub fn validate_audio_settings(&self, settings: &AudioSettings) -> Result<(), String> {
+        let channels_changed = settings.channels() != self.mixer.channels;
+        if channels_changed
+            && (!self.mixer.tracks.is_empty() || !is_pass_through(self.master.graph()))
+        {
+            return Err(
+                "The channel layout can't be changed once the project has tracks or master effects."
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Change the format of the mixer, and convert the audio sources to the new sample rate.
+    /// The master bus is rebuilt for the new channel layout, see `validate_audio_settings`.
+    pub fn set_audio_settings(&mut self, settings: AudioSettings) {
+        let sample_rate_changed = settings.sample_rate as usize != self.mixer.sample_rate;
+        let channels_changed = settings.channels() != self.mixer.channels;
+        settings.apply(&mut self.mixer);
+        if channels_changed {
+            self.master = create_master_track(self.mixer.channels);
+            self.node_positions.remove(&MASTER_TRACK_ID);
+            self.node_inputs.remove(&MASTER_TRACK_ID);
+        }
+        if sample_rate_changed {
+            self.audio_cache.clear();
+            self.stretch_cache.clear();
+            self.reload_audio_sources();
+        }
+        self.mix_cache.lock().unwrap().clear(self.mixer.sample_rate);
+    }
+
+    /// Remove the rendered segments of the track covering the range from the mix cache,
+    /// or those of every track if `track_id` is `None`.
+    pub fn invalidate(&mut self, track_id: Option<u32>, range: DirtyRange) {
+        let track_ids = track_id.map(|track_id| self.sidechain_dependents(track_id));
+        let track_ids = track_ids.as_deref();
+        // Nodes can keep sounding for a while after their input changed
+        let tail_frames = EFFECT_TAIL_SECONDS * self.mixer.sample_rate;
+        let samples_per_beat = self.mixer.samples_per_beat();
+        let base_tempo = self.mixer.tempo;
+        let frame_at = |beat| {
+            (self.tempo_map.to_linear(base_tempo, beat) * samples_per_beat).max(0.0) as usize
+        };
+
+        let mut mix_cache = self.mix_cache.lock().unwrap();
+        match range {
+            DirtyRange::Nothing => {}
+            DirtyRange::Range(start, end) => mix_cache.invalidate(
+                track_ids,
+                frame_at(start),
+                Some(frame_at(end) + tail_frames),
+            ),
+            DirtyRange::From(start) => mix_cache.invalidate(track_ids, frame_at(start), None),
+            DirtyRange::All => mix_cache.invalidate(track_ids, 0, None),
+        }
+    }
+
+    /// The track, and the tracks whose nodes take its output as a sidechain,
+    /// directly or through other sidechains. Their rendered segments depend on it.
+    /// Sends and folders feed bus and folder tracks, which aren't cached.
+    fn sidechain_dependents(&self, track_id: u32) -> Vec<u32> {
+        let console = self.console.lock().unwrap();
+        let mut track_ids = vec![track_id];
+        let mut index = 0;
+        while let Some(&source_id) = track_ids.get(index) {
+            for track in &self.mixer.tracks {
+                let id = track.get_id();
+                let is_fed = console
+                    .sidechains(id)
+                    .iter()
+                    .any(|sidechain| sidechain.source_id == source_id);
+                if is_fed && !track_ids.contains(&id) {
+                    track_ids.push(id);
+                }
+            }
+            index += 1;
+        }
+        track_ids
+    }
+
+    /// Give the audio sources to the buffer regions again.
+    pub fn reload_audio_sources(&mut self) {
+        let region_ids = self.region_sources.keys().copied().collect::<Vec<_>>();
+        for (track_id, region_id) in region_ids {
+            self.request_region_audio(track_id, region_id);
+        }
+    }
+
+    /// Emit the current state of the mixer to the frontend.
+    pub fn emit_state(&mut self, app: &AppHandle) {
+        let state = MixerState::from_mixer(
+            &mut self.mixer,
+            &self.node_positions,
+            &self.track_colors,
+            &self.collapsed_folders,
+            &self.region_settings,
+            &self.region_sources,
+            &self.region_status,
+            self.time_signature,
+            &self.tempo_map,
+            self.loop_range,
+            &self.console.lock().unwrap(),
+            self.master.graph(),
+        );
+        app.emit("mixer_state", state).ok();
+    }
+}
+
+/// Give the audio source to a buffer region of the mixer, stretched to the tempo.
+fn set_buffer_audio(
+    mixer: &mut Mixer,
+    track_id: u32,
+    region_id: u32,
+    source: Option<AudioSource>,
+    tempo: f32,
+) {
+    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
+        if let Some(buffer_track) = track.as_any_mut().downcast_mut::<BufferTrack>() {
+            if let Some(region) = buffer_track.get_region_mut(region_id) {
+                if let Some(region) = region.as_any_mut().downcast_mut::<BufferRegion>() {
+                    region.set_audio_source(source, tempo);
+                }
+            }
+        }
+    }
+}
+
+/// Scale the velocity of the notes of a note region of the mixer by the gain in dB.
+fn scale_note_region(mixer: &mut Mixer, track_id: u32, region_id: u32, gain: f32) {
+    if let Some(track) = mixer.get_track_by_id_mut(track_id) {
+        if let Some(region) = track.get_region_mut(region_id) {
+            if let Some(note_region) = region.as_any_mut().downcast_mut::<NoteRegion>() {
+                scale_velocities(note_region, db_to_gain(gain));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::history::Edit;
+    use crate::api::mixing::mixer::create_track;
+    use crate::api::{TrackData, TrackType};
+
+    /// Create a context with a buffer track holding a region of the audio file.
+    /// Returns the IDs of the track and the region.
+    fn context(path: &str) -> (MixerContext, u32, u32) {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        context.mixer.add_track(create_track(&TrackData {
+            name: "Audio".to_string(),
+            channels: 2,
+            track_type: TrackType::BufferTrack,
+        }));
+        let track_id = context.mixer.tracks[0].get_id();
+        let region_id = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.as_any_mut().downcast_mut::<BufferTrack>())
+            .unwrap()
+            .add_region(Box::new(BufferRegion::empty("Take".to_string())), 0.0, 4.0)
+            .unwrap();
+        context.region_sources.insert(
+            (track_id, region_id),
+            RegionSource::new(path.to_string(), 0, 120.0),
+        );
+        (context, track_id, region_id)
+    }
+
+    fn missing_path() -> String {
+        std::env::temp_dir()
+            .join("knodiq_missing_audio.wav")
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn missing_audio_files_are_flagged_as_failed() {
+        let (mut context, track_id, region_id) = context(&missing_path());
+        context.request_region_audio(track_id, region_id);
+        assert!(matches!(
+            context.region_status.get(&(track_id, region_id)),
+            Some(RegionStatus::Failed(_))
+        ));
+        assert!(context.region_sources.contains_key(&(track_id, region_id)));
+    }
+
+    #[test]
+    fn cached_audio_is_given_right_away() {
+        let path = missing_path();
+        let (mut context, track_id, region_id) = context(&path);
+        context
+            .audio_cache
+            .insert((path, 0), AudioSource::new(48000, 2));
+        context.region_status.insert(
+            (track_id, region_id),
+            RegionStatus::Failed("Not found".to_string()),
+        );
+
+        context.request_region_audio(track_id, region_id);
+        assert_eq!(context.region_status.get(&(track_id, region_id)), None);
+    }
+
+    #[test]
+    fn removed_regions_lose_their_status() {
+        let (mut context, track_id, region_id) = context(&missing_path());
+        context.request_region_audio(track_id, region_id);
+        let restore = Edit::RemoveRegion(track_id, region_id)
+            .apply(&mut context)
+            .unwrap();
+        assert!(context.region_status.is_empty());
+
+        // The restored region asks for its audio again
+        restore.apply(&mut context).unwrap();
+        assert!(matches!(
+            context.region_status.get(&(track_id, region_id)),
+            Some(RegionStatus::Failed(_))
+        ));
+    }
+}
@@ -14,58 +14,106 @@
 // limitations under the License.
 //
 
-use crate::api::mixing::MixingThreadCommand;
+use crate::api::mixing::cache::SegmentStream;
+use crate::api::mixing::{MixerCommand, MixingThreadCommand};
+use knodiq_engine::{Beats, Sample};
 use std::{
     sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
-        mpsc::Receiver,
+        atomic::Ordering,
+        mpsc::{Receiver, Sender},
     },
     thread,
+    time::{Duration, Instant},
 };
 
+/// Number of frames crossfaded at the loop point to avoid a click.
+const LOOP_CROSSFADE_FRAMES: usize = 64;
+
+/// How far the playback is mixed ahead of real time.
+/// Mixing further ahead would delay the changes made on the console, and a loop never ends,
+/// so mixing it as fast as possible would fill the memory.
+const MIX_LOOKAHEAD: Duration = Duration::from_secs(1);
+
+/// Start the thread mixing the audio for playback.
+/// Each mix runs until it ends or the stop flag it came with is set.
+/// The audio stretched for a mix is sent back to the mixer with `MixerCommand::FinishStretch`.
 pub fn start_mixing_thread(
     mixing_command_receiver: Receiver<MixingThreadCommand>,
+    mixer_command_sender: Sender<MixerCommand>,
 ) -> Result<(), std::io::Error> {
     thread::Builder::new()
         .name("mixing_thread".into())
         .spawn(move || {
-            let should_stop_mixing = Arc::new(AtomicBool::new(false));
-
             loop {
                 match mixing_command_receiver.recv() {
                     Ok(command) => {
                         // Handle the command from the mixer
                         match command {
-                            MixingThreadCommand::StartMixing(mut mixer, start_beat, callback) => {
-                                // Reset stop flag for new mixing
-                                should_stop_mixing.store(false, Ordering::Release);
+                            MixingThreadCommand::StartMixing(
+                                render,
+                                start_beat,
+                                loop_range,
+                                master_track,
+                                tempo_map,
+                                cache,
+                                console,
+                                should_stop,
+                                callback,
+                            ) => {
+                                // Stopped before it started
+                                if should_stop.load(Ordering::Acquire) {
+                                    continue;
+                                }
 
-                                let should_stop_mixing_clone = Arc::clone(&should_stop_mixing);
+                                let (mut mixer, stretched) = render.finish();
+                                if !stretched.is_empty() {
+                                    let _ = mixer_command_sender
+                                        .send(MixerCommand::FinishStretch(stretched));
+                                }
+                                // Stopped while the audio was being stretched
+                                if should_stop.load(Ordering::Acquire) {
+                                    continue;
+                                }
 
-                                let mix_callback = Box::new(move |sample, current_beat| {
-                                    // Check stop flag
-                                    if should_stop_mixing_clone.load(Ordering::Relaxed) {
-                                        return false;
-                                    }
+                                if let Err(e) = mixer.prepare() {
+                                    eprintln!("Error preparing mixer: {}", e);
+                                    continue;
+                                }
 
-                                    // Call original callback with correct parameters
-                                    callback(sample, current_beat);
-                                    true
-                                });
+                                // Stream from the cache, rendering only the segments which changed
+                                let mut stream = SegmentStream::new(
+                                    mixer,
+                                    master_track,
+                                    tempo_map,
+                                    cache,
+                                    console,
+                                    should_stop,
+                                );
+                                let mut pacer = Pacer::new(&stream);
+                                let start_frame = stream.frame_at(start_beat);
 
-                                match mixer.prepare() {
-                                    Ok(()) => {
-                                        mixer.mix(start_beat, mix_callback);
+                                match loop_range {
+                                    Some((loop_start, loop_end)) if start_beat < loop_end => {
+                                        let loop_start = stream.frame_at(loop_start);
+                                        let loop_end = stream.frame_at(loop_end);
+                                        play_loop(
+                                            &mut stream,
+                                            start_frame,
+                                            loop_start,
+                                            loop_end,
+                                            &mut pacer,
+                                            &*callback,
+                                        );
                                     }
-                                    Err(e) => {
-                                        eprintln!("Error preparing mixer: {}", e);
+                                    _ => {
+                                        let end_frame = stream.end_frame();
+                                        stream.play(start_frame, end_frame, &mut |sample, beat| {
+                                            pacer.wait();
+                                            callback(sample, beat)
+                                        });
                                     }
                                 }
                             }
-                            MixingThreadCommand::StopMixing => {
-                                should_stop_mixing.store(true, Ordering::Release);
-                            }
                         }
                     }
                     Err(e) => {
@@ -77,3 +125,281 @@ pub fn start_mixing_thread(
         })
         .map(|_| ())
 }
+
+/// Play from the start frame to the end of the loop, then repeat the loop until stopped.
+/// Every pass ends at exactly the frame of the loop end, and the audio following the loop end
+/// is crossfaded into the loop start.
+fn play_loop(
+    stream: &mut SegmentStream,
+    start_frame: usize,
+    loop_start: usize,
+    loop_end: usize,
+    pacer: &mut Pacer,
+    callback: &(dyn Fn(Sample, Beats) + Send),
+) {
+    let channels = stream.channels();
+
+    let mut fade_out: Vec<Sample> = Vec::new();
+    let mut pass_start = start_frame;
+    if loop_end <= loop_start {
+        // Too short to loop
+        stream.play(start_frame, loop_end, &mut |sample, beat| {
+            pacer.wait();
+            callback(sample, beat)
+        });
+        return;
+    }
+
+    loop {
+        let mut index = 0;
+        let completed = stream.play(pass_start, loop_end, &mut |sample, beat| {
+            let sample = match fade_out.get(index) {
+                Some(fade_out) => {
+                    let gain = (index / channels) as Sample / LOOP_CROSSFADE_FRAMES as Sample;
+                    sample * gain + fade_out * (1.0 - gain)
+                }
+                None => sample,
+            };
+            index += 1;
+
+            pacer.wait();
+            callback(sample, beat);
+        });
+        if !completed {
+            return;
+        }
+
+        // Take the audio following the loop end to fade it out over the next pass
+        let mut tail = Vec::with_capacity(LOOP_CROSSFADE_FRAMES * channels);
+        if !stream.play(
+            loop_end,
+            loop_end + LOOP_CROSSFADE_FRAMES,
+            &mut |sample, _| tail.push(sample),
+        ) {
+            return;
+        }
+
+        fade_out = tail;
+        pass_start = loop_start;
+    }
+}
+
+/// Keeps the mixing from getting more than `MIX_LOOKAHEAD` ahead of real time.
+struct Pacer {
+    started_at: Instant,
+    sample_rate: f64,
+    channels: usize,
+    sent_samples: usize,
+}
+
+impl Pacer {
+    fn new(stream: &SegmentStream) -> Self {
+        Pacer {
+            started_at: Instant::now(),
+            sample_rate: stream.sample_rate().max(1) as f64,
+            channels: stream.channels(),
+            sent_samples: 0,
+        }
+    }
+
+    /// Called before sending each sample, waits whenever the mixing is too far ahead.
+    fn wait(&mut self) {
+        if self.sent_samples % (self.channels * 1024) == 0 {
+            let frames = self.sent_samples / self.channels;
+            let ahead = Duration::from_secs_f64(frames as f64 / self.sample_rate);
+            let elapsed = self.started_at.elapsed();
+            if ahead > elapsed + MIX_LOOKAHEAD {
+                thread::sleep(ahead - elapsed - MIX_LOOKAHEAD);
+            }
+        }
+        self.sent_samples += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::cache::MixCache;
+    use crate::api::mixing::console::Console;
+    use crate::api::mixing::master_bus::create_master_track;
+    use crate::api::mixing::mixer::create_track;
+    use crate::api::mixing::mixer_context::MixerContext;
+    use crate::api::mixing::region::{RegionSource, StretchQuality};
+    use crate::api::mixing::tempo::TempoMap;
+    use crate::api::{TrackData, TrackType};
+    use knodiq_engine::mixing::region::BufferRegion;
+    use knodiq_engine::mixing::track::BufferTrack;
+    use knodiq_engine::{AudioSource, Mixer};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex, mpsc};
+
+    /// Command to play the mixer of the context from the start,
+    /// looping over the first two beats if `looping`.
+    fn start_mixing(
+        context: &mut MixerContext,
+        looping: bool,
+        should_stop: &Arc<AtomicBool>,
+        callback: Box<dyn Fn(Sample, Beats) + Send>,
+    ) -> MixingThreadCommand {
+        let channels = context.mixer.channels;
+        MixingThreadCommand::StartMixing(
+            context.prepare_render(StretchQuality::Fast),
+            0.0,
+            looping.then_some((0.0, 2.0)),
+            create_master_track(channels),
+            TempoMap::new(),
+            Arc::new(Mutex::new(MixCache::new(context.mixer.sample_rate))),
+            Arc::new(Mutex::new(Console::new())),
+            Arc::clone(should_stop),
+            callback,
+        )
+    }
+
+    /// Loop over an empty project with 8 frames per beat,
+    /// and return the frame of each of the first `samples` samples sent.
+    fn loop_frames(
+        start_beat: Beats,
+        loop_start: Beats,
+        loop_end: Beats,
+        samples: usize,
+    ) -> Vec<usize> {
+        let mut mixer = Mixer::new(60.0, 8, 1);
+        assert!(mixer.prepare().is_ok());
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let cache = Arc::new(Mutex::new(MixCache::new(mixer.sample_rate)));
+        let mut stream = SegmentStream::new(
+            mixer,
+            create_master_track(1),
+            TempoMap::new(),
+            cache,
+            Arc::new(Mutex::new(Console::new())),
+            Arc::clone(&should_stop),
+        );
+        let mut pacer = Pacer::new(&stream);
+        let frames = Mutex::new(Vec::new());
+
+        // The playback only stops between two blocks, so the samples past the count are dropped
+        let callback = |_: Sample, beat: Beats| {
+            let mut frames = frames.lock().unwrap();
+            frames.push((beat * 8.0).round() as usize);
+            if frames.len() >= samples {
+                should_stop.store(true, Ordering::Release);
+            }
+        };
+        let start_frame = stream.frame_at(start_beat);
+        let loop_start = stream.frame_at(loop_start);
+        let loop_end = stream.frame_at(loop_end);
+        play_loop(
+            &mut stream,
+            start_frame,
+            loop_start,
+            loop_end,
+            &mut pacer,
+            &callback,
+        );
+
+        let mut frames = frames.into_inner().unwrap();
+        frames.truncate(samples);
+        frames
+    }
+
+    #[test]
+    fn playback_wraps_at_the_loop_end() {
+        let frames = loop_frames(1.0, 0.0, 2.0, 40);
+        let expected = (8..16).chain(0..16).chain(0..16).collect::<Vec<_>>();
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn playback_starting_before_the_loop_runs_into_it() {
+        let frames = loop_frames(0.5, 1.0, 1.5, 20);
+        let expected = (4..12)
+            .chain(8..12)
+            .chain(8..12)
+            .chain(8..12)
+            .collect::<Vec<_>>();
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn stopping_a_mix_before_it_starts_keeps_the_next_one_playing() {
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (mixer_command_sender, _mixer_command_receiver) = mpsc::channel();
+        assert!(start_mixing_thread(command_receiver, mixer_command_sender).is_ok());
+        let (sample_sender, sample_receiver) = mpsc::channel();
+
+        // Loop over an empty project, so that each mix plays until it's stopped
+        let mut stop_flags = Vec::new();
+        for mix in 0..2 {
+            let should_stop = Arc::new(AtomicBool::new(false));
+            let sample_sender = sample_sender.clone();
+            let mut context = MixerContext::new(Mixer::new(60.0, 8, 1));
+            let command = start_mixing(
+                &mut context,
+                true,
+                &should_stop,
+                Box::new(move |_, _| {
+                    let _ = sample_sender.send(mix);
+                }),
+            );
+            // The first mix is stopped while it's still waiting to be started
+            if mix == 0 {
+                should_stop.store(true, Ordering::Release);
+            }
+            assert!(command_sender.send(command).is_ok());
+            stop_flags.push(should_stop);
+        }
+
+        let timeout = Duration::from_secs(5);
+        for _ in 0..8 {
+            assert_eq!(sample_receiver.recv_timeout(timeout), Ok(1));
+        }
+        stop_flags[1].store(true, Ordering::Release);
+    }
+
+    #[test]
+    fn stretched_audio_is_sent_back_to_the_mixer() {
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (mixer_command_sender, mixer_command_receiver) = mpsc::channel();
+        assert!(start_mixing_thread(command_receiver, mixer_command_sender).is_ok());
+
+        // Audio recorded at half the tempo of the project is stretched to half its length
+        let mut context = MixerContext::new(Mixer::new(120.0, 8, 1));
+        context.mixer.add_track(create_track(&TrackData {
+            name: "Audio".to_string(),
+            channels: 1,
+            track_type: TrackType::BufferTrack,
+        }));
+        let track_id = context.mixer.tracks[0].get_id();
+        let region_id = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.as_any_mut().downcast_mut::<BufferTrack>())
+            .unwrap()
+            .add_region(Box::new(BufferRegion::empty("Take".to_string())), 0.0, 4.0)
+            .unwrap();
+        context.region_sources.insert(
+            (track_id, region_id),
+            RegionSource::new("take.wav".to_string(), 0, 60.0),
+        );
+        let mut source = AudioSource::new(8, 1);
+        source.data = vec![vec![0.5; 32]];
+        context
+            .audio_cache
+            .insert(("take.wav".to_string(), 0), source);
+
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let command = start_mixing(&mut context, false, &should_stop, Box::new(|_, _| {}));
+        assert!(command_sender.send(command).is_ok());
+
+        let timeout = Duration::from_secs(5);
+        match mixer_command_receiver.recv_timeout(timeout) {
+            Ok(MixerCommand::FinishStretch(stretched)) => {
+                assert_eq!(stretched.len(), 1);
+                assert_eq!(stretched[0].0.path, "take.wav");
+            }
+            _ => panic!("The stretched audio wasn't sent back"),
+        }
+        should_stop.store(true, Ordering::Release);
+    }
+}
@@ -14,10 +14,24 @@
 // limitations under the License.
 //
 
+pub mod cache;
+pub mod console;
+pub mod graph_processor;
+pub mod history;
+pub mod master_bus;
 pub mod mixer;
 pub mod mixer_command;
+pub mod mixer_context;
 pub mod mixing_thread;
 pub mod region;
+pub mod render;
+pub mod resample;
+pub mod settings;
+pub mod sidechain;
+pub mod tempo;
 pub mod track;
 
-pub use mixer_command::{MixerCommand, MixerResult, MixingThreadCommand, send_mixer_command};
+pub use mixer_command::{
+    MixerCommand, MixerResult, MixingThreadCommand, request_mixer_result, send_mixer_command,
+};
+pub use mixer_context::MixerContext;
@@ -0,0 +1,253 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::RegionSettings;
+use crate::api::mixing::MixerContext;
+use crate::api::mixing::region::RegionSource;
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::mixing::track::BufferTrack;
+use knodiq_engine::{Beats, Region};
+use knodiq_note::{NoteRegion, NoteTrack};
+
+/// A copy of a region, which can be pasted onto any track of the same kind.
+pub struct ClipboardRegion {
+    /// Beats from the start of the first copied region to the start of this one.
+    pub offset: Beats,
+    pub duration: Beats,
+    pub data: ClipboardData,
+    pub settings: RegionSettings,
+}
+
+pub enum ClipboardData {
+    /// A buffer region, keeping the audio it was given and the file it was loaded from.
+    BufferRegion(BufferRegion, Option<RegionSource>),
+    /// A note region with all of its notes.
+    NoteRegion(NoteRegion),
+}
+
+impl ClipboardRegion {
+    /// Copy the region of the track.
+    pub fn copy(
+        context: &MixerContext,
+        track_id: u32,
+        region: &dyn Region,
+        offset: Beats,
+    ) -> Option<Self> {
+        let data = if let Some(note_region) = region.as_any().downcast_ref::<NoteRegion>() {
+            ClipboardData::NoteRegion(note_region.clone())
+        } else if let Some(buffer_region) = region.as_any().downcast_ref::<BufferRegion>() {
+            let source = context
+                .region_sources
+                .get(&(track_id, *region.get_id()))
+                .cloned();
+            ClipboardData::BufferRegion(buffer_region.clone(), source)
+        } else {
+            eprintln!("Unknown region type.");
+            return None;
+        };
+
+        Some(ClipboardRegion {
+            offset,
+            duration: region.duration(),
+            data,
+            settings: context
+                .region_settings
+                .get(&(track_id, *region.get_id()))
+                .copied()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Check that the region can be pasted onto the track.
+    pub fn validate(&self, context: &MixerContext, track_id: u32) -> Result<(), String> {
+        let track = context
+            .mixer
+            .tracks
+            .iter()
+            .find(|track| track.get_id() == track_id)
+            .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+        if context.console.lock().unwrap().is_submix(track_id) {
+            return Err("Bus and folder tracks can't have regions.".to_string());
+        }
+
+        match &self.data {
+            ClipboardData::BufferRegion(..) if !track.as_any().is::<BufferTrack>() => {
+                Err("Audio regions can only be pasted onto buffer tracks.".to_string())
+            }
+            ClipboardData::NoteRegion(_) if !track.as_any().is::<NoteTrack>() => {
+                Err("Note regions can only be pasted onto note tracks.".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a copy of the region to the track at the beat. Returns the ID of the new region.
+    /// The audio of a buffer region is shared with the copied region instead of loaded again.
+    pub fn paste(
+        &self,
+        context: &mut MixerContext,
+        track_id: u32,
+        start: Beats,
+    ) -> Result<u32, String> {
+        self.validate(context, track_id)?;
+        let track = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+
+        let added = match &self.data {
+            ClipboardData::BufferRegion(region, _) => track
+                .as_any_mut()
+                .downcast_mut::<BufferTrack>()
+                .map(|buffer_track| {
+                    buffer_track.add_region(Box::new(region.clone()), start, self.duration)
+                }),
+            ClipboardData::NoteRegion(region) => track
+                .as_any_mut()
+                .downcast_mut::<NoteTrack>()
+                .map(|note_track| {
+                    note_track.add_region(Box::new(region.clone()), start, self.duration)
+                }),
+        };
+        let region_id = match added {
+            Some(Ok(region_id)) => region_id,
+            Some(Err(e)) => return Err(format!("Error adding region: {}", e)),
+            None => return Err(format!("Track with ID {} not found.", track_id)),
+        };
+
+        if let ClipboardData::BufferRegion(_, Some(source)) = &self.data {
+            context
+                .region_sources
+                .insert((track_id, region_id), source.clone());
+        }
+        if !self.settings.is_default() {
+            context
+                .region_settings
+                .insert((track_id, region_id), self.settings);
+        }
+        Ok(region_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mixing::mixer::create_track;
+    use crate::api::{Fade, FadeCurve, TrackData, TrackType};
+    use knodiq_engine::{Mixer, Track};
+
+    /// A context with a note track holding a region with a note, and a buffer track.
+    /// Returns the IDs of the note track, its region and the buffer track.
+    fn context() -> (MixerContext, u32, u32, u32) {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        for track_type in [
+            TrackType::NoteTrack,
+            TrackType::NoteTrack,
+            TrackType::BufferTrack,
+        ] {
+            context.mixer.add_track(create_track(&TrackData {
+                name: "Track".to_string(),
+                channels: 2,
+                track_type,
+            }));
+        }
+        let note_track_id = context.mixer.tracks[0].get_id();
+        let buffer_track_id = context.mixer.tracks[2].get_id();
+        let mut region = NoteRegion::new("Melody".to_string(), 2.0, 4.0);
+        region.add_note(64, 90, 1.0, 0.5);
+        let region_id = context.mixer.tracks[0]
+            .as_any_mut()
+            .downcast_mut::<NoteTrack>()
+            .unwrap()
+            .add_region(Box::new(region), 2.0, 4.0)
+            .unwrap();
+        (context, note_track_id, region_id, buffer_track_id)
+    }
+
+    fn copy(context: &MixerContext, track_id: u32, region_id: u32) -> ClipboardRegion {
+        let track = context
+            .mixer
+            .tracks
+            .iter()
+            .find(|track| track.get_id() == track_id)
+            .unwrap();
+        let region = track
+            .regions()
+            .into_iter()
+            .find(|region| *region.get_id() == region_id)
+            .unwrap();
+        ClipboardRegion::copy(context, track_id, region, 0.0).unwrap()
+    }
+
+    #[test]
+    fn notes_are_pasted_onto_another_track() {
+        let (mut context, track_id, region_id, _) = context();
+        let other_track_id = context.mixer.tracks[1].get_id();
+        let clipboard = copy(&context, track_id, region_id);
+
+        let pasted_id = clipboard.paste(&mut context, other_track_id, 8.0).unwrap();
+        let pasted = context
+            .mixer
+            .get_track_by_id_mut(other_track_id)
+            .and_then(|track| track.get_region_mut(pasted_id))
+            .unwrap();
+        assert_eq!((pasted.start_time(), pasted.duration()), (8.0, 4.0));
+        let notes = pasted
+            .as_any()
+            .downcast_ref::<NoteRegion>()
+            .unwrap()
+            .notes();
+        assert_eq!(notes.len(), 1);
+        assert_eq!((notes[0].pitch, notes[0].start_beat), (64, 1.0));
+    }
+
+    #[test]
+    fn regions_are_only_pasted_onto_matching_tracks() {
+        let (mut context, track_id, region_id, buffer_track_id) = context();
+        let clipboard = copy(&context, track_id, region_id);
+
+        assert!(clipboard.validate(&context, track_id).is_ok());
+        assert!(clipboard.paste(&mut context, buffer_track_id, 0.0).is_err());
+        assert!(
+            clipboard
+                .paste(&mut context, buffer_track_id + 100, 0.0)
+                .is_err()
+        );
+        assert!(context.mixer.tracks[2].regions().is_empty());
+    }
+
+    #[test]
+    fn settings_are_pasted_along() {
+        let (mut context, track_id, region_id, _) = context();
+        let settings = RegionSettings {
+            fade_in: Fade {
+                length: 1.0,
+                curve: FadeCurve::Exponential,
+            },
+            ..RegionSettings::default()
+        };
+        context
+            .region_settings
+            .insert((track_id, region_id), settings);
+        let clipboard = copy(&context, track_id, region_id);
+
+        let pasted_id = clipboard.paste(&mut context, track_id, 8.0).unwrap();
+        assert_eq!(
+            context.region_settings.get(&(track_id, pasted_id)),
+            Some(&settings)
+        );
+    }
+}
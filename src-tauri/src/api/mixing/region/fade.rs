@@ -0,0 +1,194 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::{Fade, FadeCurve, RegionSettings};
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::{AudioSource, Beats, Track};
+use std::collections::HashMap;
+
+/// Lengths of the automatic crossfades between the overlapping buffer regions of the track,
+/// as (fade in, fade out) keyed by region ID.
+/// A region lying entirely within another one gets no crossfade.
+pub fn crossfades(track: &dyn Track) -> HashMap<u32, (Beats, Beats)> {
+    let mut regions = track
+        .regions()
+        .into_iter()
+        .filter(|region| region.as_any().is::<BufferRegion>())
+        .map(|region| {
+            let start = region.start_time();
+            (*region.get_id(), start, start + region.duration())
+        })
+        .collect::<Vec<_>>();
+    regions.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut crossfades: HashMap<u32, (Beats, Beats)> = HashMap::new();
+    for (index, &(region_id, _, end)) in regions.iter().enumerate() {
+        for &(next_id, next_start, next_end) in &regions[index + 1..] {
+            if next_start >= end {
+                break;
+            }
+            if next_end <= end {
+                continue;
+            }
+            // The earlier region fades out over the overlap while the later one fades in
+            let overlap = end - next_start;
+            let fade_out = &mut crossfades.entry(region_id).or_default().1;
+            *fade_out = fade_out.max(overlap);
+            let fade_in = &mut crossfades.entry(next_id).or_default().0;
+            *fade_in = fade_in.max(overlap);
+        }
+    }
+    crossfades
+}
+
+/// Fades heard on a region: its own fades, replaced by the automatic crossfades
+/// where those are longer.
+pub fn effective_fades(settings: &RegionSettings, crossfade: (Beats, Beats)) -> (Fade, Fade) {
+    let pick = |fade: Fade, crossfade: Beats| match crossfade > fade.length {
+        true => Fade {
+            length: crossfade,
+            curve: FadeCurve::EqualPower,
+        },
+        false => fade,
+    };
+    (
+        pick(settings.fade_in, crossfade.0),
+        pick(settings.fade_out, crossfade.1),
+    )
+}
+
+/// Apply the fades to the audio of a region.
+/// The lengths are in frames, and `length` is the number of frames the region plays.
+pub fn apply_fades(
+    source: &mut AudioSource,
+    fade_in: (usize, FadeCurve),
+    fade_out: (usize, FadeCurve),
+    length: usize,
+) {
+    let (fade_in_frames, fade_in_curve) = fade_in;
+    let (fade_out_frames, fade_out_curve) = fade_out;
+    let fade_out_start = length.saturating_sub(fade_out_frames);
+
+    for channel in source.data.iter_mut() {
+        let end = length.min(channel.len());
+        for (frame, sample) in channel[..fade_in_frames.min(end)].iter_mut().enumerate() {
+            *sample *= fade_in_curve.gain(frame as f32 / fade_in_frames as f32);
+        }
+        for frame in fade_out_start.min(end)..end {
+            let position = (length - frame) as f32 / fade_out_frames as f32;
+            channel[frame] *= fade_out_curve.gain(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use knodiq_engine::mixing::track::BufferTrack;
+
+    fn track(regions: &[(Beats, Beats)]) -> (BufferTrack, Vec<u32>) {
+        let mut track = BufferTrack::new("Track", 2);
+        let region_ids = regions
+            .iter()
+            .map(|(start, end)| {
+                track
+                    .add_region(
+                        Box::new(BufferRegion::empty("Region".to_string())),
+                        *start,
+                        end - start,
+                    )
+                    .unwrap()
+            })
+            .collect();
+        (track, region_ids)
+    }
+
+    #[test]
+    fn overlapping_regions_crossfade() {
+        let (track, ids) = track(&[(0.0, 4.0), (3.0, 8.0), (5.0, 6.0), (7.5, 10.0)]);
+        let crossfades = crossfades(&track);
+
+        assert_eq!(crossfades.get(&ids[0]), Some(&(0.0, 1.0)));
+        assert_eq!(crossfades.get(&ids[1]), Some(&(1.0, 0.5)));
+        // Lies entirely within the second region
+        assert_eq!(crossfades.get(&ids[2]), None);
+        assert_eq!(crossfades.get(&ids[3]), Some(&(0.5, 0.0)));
+    }
+
+    #[test]
+    fn longer_fades_win_over_crossfades() {
+        let settings = RegionSettings {
+            fade_in: Fade {
+                length: 2.0,
+                curve: FadeCurve::Exponential,
+            },
+            fade_out: Fade {
+                length: 0.25,
+                curve: FadeCurve::Linear,
+            },
+            ..RegionSettings::default()
+        };
+        let (fade_in, fade_out) = effective_fades(&settings, (1.0, 0.5));
+        assert_eq!(
+            (fade_in.length, fade_in.curve),
+            (2.0, FadeCurve::Exponential)
+        );
+        assert_eq!(
+            (fade_out.length, fade_out.curve),
+            (0.5, FadeCurve::EqualPower)
+        );
+    }
+
+    #[test]
+    fn fades_scale_the_ends_of_the_audio() {
+        let mut source = AudioSource::new(48000, 1);
+        source.data = vec![vec![1.0; 120]];
+        apply_fades(
+            &mut source,
+            (10, FadeCurve::Linear),
+            (20, FadeCurve::Linear),
+            100,
+        );
+
+        let channel = &source.data[0];
+        assert_eq!(channel[0], 0.0);
+        assert_eq!(channel[5], 0.5);
+        assert_eq!(channel[50], 1.0);
+        assert_eq!(channel[90], 0.5);
+        assert!((channel[99] - 0.05).abs() < 1e-6);
+        // Past the end of the region is left to the repeats
+        assert_eq!(channel[110], 1.0);
+    }
+
+    #[test]
+    fn fades_longer_than_the_audio_stay_in_bounds() {
+        let mut source = AudioSource::new(48000, 2);
+        source.data = vec![vec![1.0; 8], vec![1.0; 8]];
+        apply_fades(
+            &mut source,
+            (100, FadeCurve::EqualPower),
+            (100, FadeCurve::Exponential),
+            16,
+        );
+        assert!(
+            source
+                .data
+                .iter()
+                .flatten()
+                .all(|sample| (0.0..=1.0).contains(sample))
+        );
+    }
+}
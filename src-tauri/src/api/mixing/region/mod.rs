@@ -14,7 +14,19 @@
 // limitations under the License.
 //
 
+pub mod clipboard;
+pub mod fade;
 pub mod region;
 pub mod region_op;
+pub mod region_source;
+pub mod repeat;
+pub mod stretch;
 
-pub use region_op::RegionOperation;
+pub use clipboard::{ClipboardData, ClipboardRegion};
+pub use fade::{apply_fades, crossfades, effective_fades};
+pub use region_op::{
+    RegionOperation, add_note_with_id, scale_velocities, shift_notes, split_notes,
+};
+pub use region_source::{RegionSource, RegionStatus, validate_source_offset};
+pub use repeat::{loop_audio, loop_notes, repeat_starts, rotate_notes};
+pub use stretch::{StretchQuality, stretch_source};
@@ -14,9 +14,12 @@
 // limitations under the License.
 //
 
-use crate::api::mixing::region::RegionOperation;
-use crate::api::mixing::{MixerCommand, send_mixer_command};
-use crate::api::{AppState, NoteData, RegionData};
+use crate::api::data::region_settings::{
+    validate_loop_length, validate_pitch_shift, validate_region_gain,
+};
+use crate::api::mixing::region::{RegionOperation, validate_source_offset};
+use crate::api::mixing::{MixerCommand, MixerResult, request_mixer_result, send_mixer_command};
+use crate::api::{AppState, Fade, NoteData, RegionData};
 use knodiq_engine::audio_utils::Beats;
 use std::sync::Mutex;
 use tauri::{State, command};
@@ -53,6 +56,91 @@ pub fn set_duration(
     send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
 }
 
+#[command]
+pub fn split_region(
+    track_id: u32,
+    region_id: u32,
+    at_beat: Beats,
+    cut_notes: bool,
+    state: State<'_, Mutex<AppState>>,
+) {
+    send_mixer_command(
+        MixerCommand::SplitRegion(track_id, region_id, at_beat, cut_notes),
+        &state,
+    );
+}
+
+#[command]
+pub fn trim_region_start(
+    track_id: u32,
+    region_id: u32,
+    start: Beats,
+    state: State<'_, Mutex<AppState>>,
+) {
+    send_mixer_command(
+        MixerCommand::TrimRegionStart(track_id, region_id, start),
+        &state,
+    );
+}
+
+#[command]
+pub fn trim_region_end(
+    track_id: u32,
+    region_id: u32,
+    end: Beats,
+    state: State<'_, Mutex<AppState>>,
+) {
+    send_mixer_command(
+        MixerCommand::TrimRegionEnd(track_id, region_id, end),
+        &state,
+    );
+}
+
+#[command]
+pub fn slip_region(track_id: u32, region_id: u32, delta: Beats, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::SlipRegion(track_id, region_id, delta), &state);
+}
+
+#[command]
+pub fn set_source_offset(
+    track_id: u32,
+    region_id: u32,
+    offset: f64,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_source_offset(offset)?;
+    let op = RegionOperation::SetSourceOffset(offset);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+    Ok(())
+}
+
+#[command]
+pub fn duplicate_region(
+    track_id: u32,
+    region_id: u32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<u32, String> {
+    let region_ids = request_paste(MixerCommand::DuplicateRegion(track_id, region_id), &state)?;
+    region_ids
+        .first()
+        .copied()
+        .ok_or_else(|| "The region wasn't duplicated.".to_string())
+}
+
+#[command]
+pub fn copy_regions(track_id: u32, region_ids: Vec<u32>, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::CopyRegions(track_id, region_ids), &state);
+}
+
+#[command]
+pub fn paste_regions(
+    track_id: u32,
+    at_beat: Beats,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<u32>, String> {
+    request_paste(MixerCommand::PasteRegions(track_id, at_beat), &state)
+}
+
 #[command]
 pub fn set_region_name(
     track_id: u32,
@@ -75,6 +163,82 @@ pub fn scale_region(
     send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
 }
 
+#[command]
+pub fn set_fade_in(
+    track_id: u32,
+    region_id: u32,
+    fade: Fade,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    fade.validate()?;
+    let op = RegionOperation::SetFadeIn(fade);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_fade_out(
+    track_id: u32,
+    region_id: u32,
+    fade: Fade,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    fade.validate()?;
+    let op = RegionOperation::SetFadeOut(fade);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_region_gain(
+    track_id: u32,
+    region_id: u32,
+    gain: f32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_region_gain(gain)?;
+    let op = RegionOperation::SetGain(gain);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_region_invert(
+    track_id: u32,
+    region_id: u32,
+    invert: bool,
+    state: State<'_, Mutex<AppState>>,
+) {
+    let op = RegionOperation::SetInvert(invert);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+}
+
+#[command]
+pub fn set_pitch_shift(
+    track_id: u32,
+    region_id: u32,
+    semitones: f32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_pitch_shift(semitones)?;
+    let op = RegionOperation::SetPitchShift(semitones);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_loop_length(
+    track_id: u32,
+    region_id: u32,
+    loop_length: Option<Beats>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_loop_length(loop_length)?;
+    let op = RegionOperation::SetLoopLength(loop_length);
+    send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
+    Ok(())
+}
+
 #[command]
 pub fn add_note_to_region(
     track_id: u32,
@@ -119,3 +283,14 @@ pub fn modify_note_in_region(
     };
     send_mixer_command(MixerCommand::ApplyRegionOp(track_id, region_id, op), &state);
 }
+
+/// Send a command adding copies of regions, and wait for the IDs of the new regions.
+fn request_paste(
+    command: MixerCommand,
+    state: &State<'_, Mutex<AppState>>,
+) -> Result<Vec<u32>, String> {
+    match request_mixer_result(command, state)? {
+        MixerResult::RegionsPasted(result) => result,
+        _ => Err("Unexpected result type received.".to_string()),
+    }
+}
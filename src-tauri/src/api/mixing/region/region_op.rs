@@ -14,7 +14,10 @@
 // limitations under the License.
 //
 
-use knodiq_engine::{Beats, Region};
+use crate::api::state::NoteState;
+use crate::api::{Fade, RegionSettings};
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::{Beats, Region, Sample};
 use knodiq_note::NoteRegion;
 use serde::{Deserialize, Serialize};
 
@@ -27,7 +30,22 @@ pub enum RegionOperation {
     /// Set the name of the region.
     SetName(String),
     /// Scale the region to the new duration.
+    /// The audio of a `BufferRegion` is stretched along, keeping its pitch.
     Scale(f32),
+    /// Set the fade at the start of a `BufferRegion`.
+    SetFadeIn(Fade),
+    /// Set the fade at the end of a `BufferRegion`.
+    SetFadeOut(Fade),
+    /// Set the clip gain of the region in dB.
+    SetGain(f32),
+    /// Invert the polarity of a `BufferRegion`.
+    SetInvert(bool),
+    /// Shift the pitch of a `BufferRegion` in semitones, keeping its length.
+    SetPitchShift(f32),
+    /// Set the seconds into the audio where a `BufferRegion` starts playing.
+    SetSourceOffset(f64),
+    /// Repeat the content of the region every number of beats, or play it once with `None`.
+    SetLoopLength(Option<Beats>),
 
     /// Add a note to a `NoteRegion`.
     AddNote {
@@ -49,12 +67,56 @@ pub enum RegionOperation {
 }
 
 impl RegionOperation {
-    pub fn apply(&self, region: &mut dyn Region) {
+    /// Apply the operation to the region, or to the settings the mixer keeps for it.
+    pub fn apply(&self, region: &mut dyn Region, settings: &mut RegionSettings) {
         match self {
             RegionOperation::SetStartTime(beats) => region.set_start_time(*beats),
             RegionOperation::SetDuration(beats) => region.set_duration(*beats),
             RegionOperation::SetName(name) => region.set_name(name.clone()),
-            RegionOperation::Scale(new_duration) => region.scale(*new_duration),
+            RegionOperation::Scale(new_duration) => {
+                if region.as_any().is::<BufferRegion>() {
+                    let duration = region.duration();
+                    if duration > 0.0 && *new_duration > 0.0 {
+                        settings.stretch *= *new_duration / duration;
+                    }
+                    region.set_duration(*new_duration);
+                } else {
+                    region.scale(*new_duration);
+                }
+            }
+
+            RegionOperation::SetFadeIn(fade) => {
+                if region.as_any().is::<BufferRegion>() {
+                    settings.fade_in = *fade;
+                } else {
+                    eprintln!("Cannot fade a non-buffer region");
+                }
+            }
+            RegionOperation::SetFadeOut(fade) => {
+                if region.as_any().is::<BufferRegion>() {
+                    settings.fade_out = *fade;
+                } else {
+                    eprintln!("Cannot fade a non-buffer region");
+                }
+            }
+            RegionOperation::SetGain(gain) => settings.gain = *gain,
+            RegionOperation::SetInvert(invert) => {
+                if region.as_any().is::<BufferRegion>() {
+                    settings.invert = *invert;
+                } else {
+                    eprintln!("Cannot invert a non-buffer region");
+                }
+            }
+            RegionOperation::SetPitchShift(semitones) => {
+                if region.as_any().is::<BufferRegion>() {
+                    settings.pitch_shift = *semitones;
+                } else {
+                    eprintln!("Cannot pitch shift a non-buffer region");
+                }
+            }
+            // The audio source is kept by the mixer context, which applies the offset itself
+            RegionOperation::SetSourceOffset(_) => {}
+            RegionOperation::SetLoopLength(loop_length) => settings.loop_length = *loop_length,
 
             RegionOperation::AddNote {
                 pitch,
@@ -106,6 +168,15 @@ impl Clone for RegionOperation {
             RegionOperation::SetDuration(beats) => RegionOperation::SetDuration(*beats),
             RegionOperation::SetName(name) => RegionOperation::SetName(name.clone()),
             RegionOperation::Scale(new_duration) => RegionOperation::Scale(*new_duration),
+            RegionOperation::SetFadeIn(fade) => RegionOperation::SetFadeIn(*fade),
+            RegionOperation::SetFadeOut(fade) => RegionOperation::SetFadeOut(*fade),
+            RegionOperation::SetGain(gain) => RegionOperation::SetGain(*gain),
+            RegionOperation::SetInvert(invert) => RegionOperation::SetInvert(*invert),
+            RegionOperation::SetPitchShift(semitones) => RegionOperation::SetPitchShift(*semitones),
+            RegionOperation::SetSourceOffset(offset) => RegionOperation::SetSourceOffset(*offset),
+            RegionOperation::SetLoopLength(loop_length) => {
+                RegionOperation::SetLoopLength(*loop_length)
+            }
             RegionOperation::AddNote {
                 pitch,
                 velocity,
@@ -134,3 +205,301 @@ impl Clone for RegionOperation {
         }
     }
 }
+
+/// Add a note to the region and give it the specified ID, so that anything
+/// referring to the note stays valid when the note is restored.
+/// Fails if the region already has a note with the ID.
+pub fn add_note_with_id(
+    note_region: &mut NoteRegion,
+    id: u32,
+    pitch: u8,
+    velocity: u8,
+    start_beat: Beats,
+    duration: Beats,
+) -> Result<(), String> {
+    if note_region.notes().iter().any(|note| note.id == id) {
+        return Err(format!("Note with id {} already exists.", id));
+    }
+
+    // The region hands out the next IDs from its counter, which must get past the ID,
+    // so notes are added until it has. If the ID it hands out doesn't change,
+    // it's derived from the notes, and the restored note keeps it past the ID.
+    let mut previous_id = None;
+    loop {
+        let new_id = add_note(note_region, pitch, velocity, start_beat, duration)
+            .ok_or_else(|| "Failed to add note.".to_string())?;
+        if new_id >= id || previous_id == Some(new_id) {
+            if let Some(note) = note_region.get_note_mut(new_id) {
+                note.id = id;
+            }
+            return Ok(());
+        }
+        note_region.remove_note(new_id);
+        previous_id = Some(new_id);
+    }
+}
+
+/// Add a note to the region and return the ID it was given.
+fn add_note(
+    note_region: &mut NoteRegion,
+    pitch: u8,
+    velocity: u8,
+    start_beat: Beats,
+    duration: Beats,
+) -> Option<u32> {
+    let existing_ids = note_region
+        .notes()
+        .iter()
+        .map(|note| note.id)
+        .collect::<Vec<_>>();
+    note_region.add_note(pitch, velocity, start_beat, duration);
+    note_region
+        .notes()
+        .iter()
+        .map(|note| note.id)
+        .find(|note_id| !existing_ids.contains(note_id))
+}
+
+/// Remove the notes starting after the split from the region and return them,
+/// placed relative to the split. Notes crossing the split are cut in two if `cut_notes` is set,
+/// and stay whole in the region otherwise.
+pub fn split_notes(note_region: &mut NoteRegion, split: Beats, cut_notes: bool) -> Vec<NoteState> {
+    let notes = note_region
+        .notes()
+        .iter()
+        .map(NoteState::from_note)
+        .collect::<Vec<_>>();
+
+    let mut taken = Vec::new();
+    for note in notes {
+        let end = note.start_time + note.duration;
+        if note.start_time >= split {
+            note_region.remove_note(note.id);
+            taken.push(NoteState {
+                start_time: note.start_time - split,
+                ..note
+            });
+        } else if cut_notes && end > split {
+            if let Some(first_half) = note_region.get_note_mut(note.id) {
+                first_half.duration = split - note.start_time;
+            }
+            taken.push(NoteState {
+                start_time: 0.0,
+                duration: end - split,
+                ..note
+            });
+        }
+    }
+    taken
+}
+
+/// Move every note of the region by the beats.
+pub fn shift_notes(note_region: &mut NoteRegion, delta: Beats) {
+    let note_ids = note_region
+        .notes()
+        .iter()
+        .map(|note| note.id)
+        .collect::<Vec<_>>();
+    for id in note_ids {
+        if let Some(note) = note_region.get_note_mut(id) {
+            note.start_beat += delta;
+        }
+    }
+}
+
+/// Scale the velocity of every note of the region by the gain.
+pub fn scale_velocities(note_region: &mut NoteRegion, gain: Sample) {
+    let note_ids = note_region
+        .notes()
+        .iter()
+        .map(|note| note.id)
+        .collect::<Vec<_>>();
+    for note_id in note_ids {
+        if let Some(note) = note_region.get_note_mut(note_id) {
+            note.velocity = (note.velocity as Sample * gain).round().clamp(0.0, 127.0) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::FadeCurve;
+
+    fn region(notes: &[(Beats, Beats)]) -> NoteRegion {
+        let mut region = NoteRegion::new("Notes".to_string(), 0.0, 8.0);
+        for (start_beat, duration) in notes {
+            region.add_note(60, 100, *start_beat, *duration);
+        }
+        region
+    }
+
+    fn placements(region: &NoteRegion) -> Vec<(Beats, Beats)> {
+        let mut placements = region
+            .notes()
+            .iter()
+            .map(|note| (note.start_beat, note.duration))
+            .collect::<Vec<_>>();
+        placements.sort_by(|a, b| a.0.total_cmp(&b.0));
+        placements
+    }
+
+    #[test]
+    fn note_is_restored_with_its_id() {
+        let mut note_region = region(&[(0.0, 1.0)]);
+        add_note_with_id(&mut note_region, 10, 64, 90, 2.0, 1.0).unwrap();
+
+        let note = note_region
+            .notes()
+            .iter()
+            .find(|note| note.id == 10)
+            .expect("The note should keep its ID");
+        assert_eq!((note.pitch, note.velocity), (64, 90));
+        assert_eq!((note.start_beat, note.duration), (2.0, 1.0));
+        assert_eq!(note_region.notes().len(), 2);
+    }
+
+    #[test]
+    fn restored_id_is_not_handed_out_again() {
+        let mut note_region = region(&[]);
+        add_note_with_id(&mut note_region, 5, 60, 100, 0.0, 1.0).unwrap();
+        let new_id = add_note(&mut note_region, 62, 100, 1.0, 1.0).unwrap();
+        assert_ne!(new_id, 5);
+        assert_eq!(note_region.notes().len(), 2);
+    }
+
+    #[test]
+    fn colliding_id_is_rejected() {
+        let mut note_region = region(&[(0.0, 1.0)]);
+        let id = note_region.notes()[0].id;
+        assert!(add_note_with_id(&mut note_region, id, 60, 100, 1.0, 1.0).is_err());
+        assert_eq!(note_region.notes().len(), 1);
+    }
+
+    #[test]
+    fn note_operations_edit_the_notes() {
+        let mut note_region = region(&[(0.0, 1.0)]);
+        let mut settings = RegionSettings::default();
+        let id = note_region.notes()[0].id;
+        RegionOperation::AddNote {
+            pitch: 62,
+            velocity: 80,
+            start_beat: 2.0,
+            duration: 0.5,
+        }
+        .apply(&mut note_region, &mut settings);
+        RegionOperation::ModifyNote {
+            id,
+            pitch: 64,
+            velocity: 100,
+            start_beat: 1.0,
+            duration: 2.0,
+        }
+        .apply(&mut note_region, &mut settings);
+        assert_eq!(placements(&note_region), vec![(1.0, 2.0), (2.0, 0.5)]);
+
+        RegionOperation::RemoveNote { id }.apply(&mut note_region, &mut settings);
+        assert_eq!(placements(&note_region), vec![(2.0, 0.5)]);
+        RegionOperation::SetStartTime(4.0).apply(&mut note_region, &mut settings);
+        assert_eq!(note_region.start_time(), 4.0);
+    }
+
+    #[test]
+    fn split_keeps_crossing_notes_whole() {
+        let mut note_region = region(&[(0.0, 1.0), (1.5, 1.0), (3.0, 1.0)]);
+        let taken = split_notes(&mut note_region, 2.0, false);
+
+        assert_eq!(placements(&note_region), vec![(0.0, 1.0), (1.5, 1.0)]);
+        assert_eq!(taken.len(), 1);
+        assert_eq!((taken[0].start_time, taken[0].duration), (1.0, 1.0));
+    }
+
+    #[test]
+    fn split_cuts_crossing_notes() {
+        let mut note_region = region(&[(0.0, 1.0), (1.5, 1.0), (3.0, 1.0)]);
+        let mut taken = split_notes(&mut note_region, 2.0, true);
+        taken.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+        assert_eq!(placements(&note_region), vec![(0.0, 1.0), (1.5, 0.5)]);
+        let taken = taken
+            .iter()
+            .map(|note| (note.start_time, note.duration))
+            .collect::<Vec<_>>();
+        assert_eq!(taken, vec![(0.0, 0.5), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn fades_only_apply_to_audio() {
+        let fade = Fade {
+            length: 1.0,
+            curve: FadeCurve::EqualPower,
+        };
+        let mut audio = BufferRegion::empty("Audio".to_string());
+        let mut settings = RegionSettings::default();
+        RegionOperation::SetFadeIn(fade).apply(&mut audio, &mut settings);
+        RegionOperation::SetFadeOut(fade).apply(&mut audio, &mut settings);
+        assert_eq!((settings.fade_in, settings.fade_out), (fade, fade));
+
+        let mut notes = region(&[]);
+        let mut settings = RegionSettings::default();
+        RegionOperation::SetFadeIn(fade).apply(&mut notes, &mut settings);
+        assert!(settings.is_default());
+    }
+
+    #[test]
+    fn gain_applies_to_both_region_types() {
+        let mut notes = region(&[]);
+        let mut settings = RegionSettings::default();
+        RegionOperation::SetGain(-6.0).apply(&mut notes, &mut settings);
+        RegionOperation::SetInvert(true).apply(&mut notes, &mut settings);
+        assert_eq!((settings.gain, settings.invert), (-6.0, false));
+
+        let mut audio = BufferRegion::empty("Audio".to_string());
+        RegionOperation::SetInvert(true).apply(&mut audio, &mut settings);
+        assert!(settings.invert);
+        assert!(settings.amplitude() < 0.0);
+    }
+
+    #[test]
+    fn velocities_are_scaled_within_range() {
+        let mut note_region = region(&[(0.0, 1.0)]);
+        scale_velocities(&mut note_region, 0.5);
+        assert_eq!(note_region.notes()[0].velocity, 50);
+        scale_velocities(&mut note_region, 4.0);
+        assert_eq!(note_region.notes()[0].velocity, 127);
+    }
+
+    #[test]
+    fn scaling_audio_stretches_it() {
+        let mut audio = BufferRegion::empty("Audio".to_string());
+        audio.set_duration(4.0);
+        let mut settings = RegionSettings::default();
+        RegionOperation::Scale(8.0).apply(&mut audio, &mut settings);
+        assert_eq!(audio.duration(), 8.0);
+        assert_eq!(settings.stretch, 2.0);
+
+        RegionOperation::Scale(6.0).apply(&mut audio, &mut settings);
+        assert_eq!(settings.stretch, 1.5);
+        RegionOperation::SetPitchShift(-3.0).apply(&mut audio, &mut settings);
+        assert_eq!(settings.pitch_shift, -3.0);
+    }
+
+    #[test]
+    fn notes_are_shifted_together() {
+        let mut note_region = region(&[(0.0, 1.0), (2.0, 0.5)]);
+        shift_notes(&mut note_region, 1.5);
+        assert_eq!(placements(&note_region), vec![(1.5, 1.0), (3.5, 0.5)]);
+        shift_notes(&mut note_region, -1.5);
+        assert_eq!(placements(&note_region), vec![(0.0, 1.0), (2.0, 0.5)]);
+    }
+
+    #[test]
+    fn loop_length_is_kept_in_the_settings() {
+        let mut notes = region(&[]);
+        let mut settings = RegionSettings::default();
+        RegionOperation::SetLoopLength(Some(2.0)).apply(&mut notes, &mut settings);
+        assert_eq!(settings.loop_length, Some(2.0));
+        RegionOperation::SetLoopLength(None).apply(&mut notes, &mut settings);
+        assert_eq!(settings.loop_length, None);
+    }
+}
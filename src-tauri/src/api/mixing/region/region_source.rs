@@ -0,0 +1,72 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::AudioSource;
+
+/// Audio file played by a buffer region.
+#[derive(Clone)]
+pub struct RegionSource {
+    pub path: String,
+    /// Index of the audio track in the file.
+    pub track_index: usize,
+    /// Seconds into the audio where the region starts playing.
+    pub offset: f64,
+    /// Tempo of the project at which the audio plays at its own speed.
+    /// The audio is stretched to follow the project when its tempo changes.
+    pub tempo: f32,
+}
+
+impl RegionSource {
+    pub fn new(path: String, track_index: usize, tempo: f32) -> Self {
+        RegionSource {
+            path,
+            track_index,
+            offset: 0.0,
+            tempo,
+        }
+    }
+
+    /// How much longer the audio plays than at its own speed,
+    /// given the tempo of the project and the stretch of the region.
+    pub fn stretch_ratio(&self, tempo: f32, stretch: f32) -> f64 {
+        stretch as f64 * self.tempo as f64 / tempo as f64
+    }
+
+    /// Cut the part of the audio before the offset, so that the region starts playing from it.
+    pub fn trim(&self, source: &mut AudioSource) {
+        let frames = (self.offset.max(0.0) * source.sample_rate as f64).round() as usize;
+        for channel in source.data.iter_mut() {
+            channel.drain(..frames.min(channel.len()));
+        }
+    }
+}
+
+/// Whether the audio of a buffer region is missing, and why.
+/// Regions whose audio is available have no status.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RegionStatus {
+    /// The audio file is being decoded by the import pool.
+    Loading,
+    /// The audio file couldn't be decoded.
+    Failed(String),
+}
+
+pub fn validate_source_offset(offset: f64) -> Result<(), String> {
+    if !offset.is_finite() || offset < 0.0 {
+        return Err(format!("{} is not a valid source offset.", offset));
+    }
+    Ok(())
+}
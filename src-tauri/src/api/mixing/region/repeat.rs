@@ -0,0 +1,164 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::state::NoteState;
+use knodiq_engine::{AudioSource, Beats};
+use knodiq_note::NoteRegion;
+
+/// Beats relative to the start of the region where its content starts over.
+pub fn repeat_starts(duration: Beats, loop_length: Option<Beats>) -> Vec<Beats> {
+    let Some(loop_length) = loop_length.filter(|length| *length > 0.0) else {
+        return Vec::new();
+    };
+    (1..)
+        .map(|repeat| repeat as Beats * loop_length)
+        .take_while(|start| *start < duration)
+        .collect()
+}
+
+/// Keep only the notes within the loop, and repeat them over the duration of the region.
+/// Notes crossing the end of the loop are cut there.
+pub fn loop_notes(note_region: &mut NoteRegion, loop_length: Beats, duration: Beats) {
+    let notes = note_region
+        .notes()
+        .iter()
+        .map(NoteState::from_note)
+        .collect::<Vec<_>>();
+
+    let mut looped = Vec::new();
+    for note in notes {
+        if note.start_time >= loop_length {
+            note_region.remove_note(note.id);
+            continue;
+        }
+        let duration = note.duration.min(loop_length - note.start_time);
+        if let Some(note) = note_region.get_note_mut(note.id) {
+            note.duration = duration;
+        }
+        looped.push(NoteState { duration, ..note });
+    }
+
+    for repeat_start in repeat_starts(duration, Some(loop_length)) {
+        for note in &looped {
+            let start = repeat_start + note.start_time;
+            if start < duration {
+                note_region.add_note(note.pitch, note.velocity, start, note.duration);
+            }
+        }
+    }
+}
+
+/// Notes of the loop placed relative to the phase, wrapping around the end of the loop,
+/// so that a region starting at the phase keeps playing the same repeats.
+pub fn rotate_notes(note_region: &NoteRegion, loop_length: Beats, phase: Beats) -> Vec<NoteState> {
+    note_region
+        .notes()
+        .iter()
+        .map(NoteState::from_note)
+        .filter(|note| note.start_time < loop_length)
+        .map(|note| NoteState {
+            start_time: (note.start_time - phase).rem_euclid(loop_length),
+            ..note
+        })
+        .collect()
+}
+
+/// Keep only the first `loop_frames` of the audio, and repeat them at each of the frames,
+/// up to `length` frames.
+pub fn loop_audio(source: &mut AudioSource, repeats: &[usize], loop_frames: usize, length: usize) {
+    for channel in source.data.iter_mut() {
+        channel.resize(loop_frames, 0.0);
+        let content = channel.clone();
+        for &repeat in repeats {
+            // A repeat may be shorter than the loop where the tempo speeds up
+            channel.resize(repeat, 0.0);
+            channel.extend_from_slice(&content);
+        }
+        channel.truncate(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts(notes: &[NoteState]) -> Vec<(u8, Beats, Beats)> {
+        let mut starts = notes
+            .iter()
+            .map(|note| (note.pitch, note.start_time, note.duration))
+            .collect::<Vec<_>>();
+        starts.sort_by(|a, b| a.1.total_cmp(&b.1));
+        starts
+    }
+
+    fn region() -> NoteRegion {
+        let mut region = NoteRegion::new("Loop".to_string(), 0.0, 8.0);
+        region.add_note(60, 100, 0.5, 1.0);
+        region.add_note(62, 100, 1.5, 1.0);
+        region.add_note(64, 100, 2.5, 0.5);
+        region
+    }
+
+    #[test]
+    fn repeats_start_at_each_loop_length() {
+        assert_eq!(repeat_starts(8.0, Some(3.0)), vec![3.0, 6.0]);
+        // A repeat starting right at the end isn't heard
+        assert_eq!(repeat_starts(4.0, Some(2.0)), vec![2.0]);
+        assert!(repeat_starts(4.0, None).is_empty());
+        assert!(repeat_starts(4.0, Some(0.0)).is_empty());
+    }
+
+    #[test]
+    fn notes_are_repeated_over_the_region() {
+        let mut region = region();
+        loop_notes(&mut region, 2.0, 5.0);
+        let notes = region
+            .notes()
+            .iter()
+            .map(NoteState::from_note)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            starts(&notes),
+            vec![
+                (60, 0.5, 1.0),
+                (62, 1.5, 0.5),
+                (60, 2.5, 1.0),
+                (62, 3.5, 0.5),
+                (60, 4.5, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn rotated_notes_wrap_around_the_loop() {
+        let notes = rotate_notes(&region(), 2.0, 1.0);
+        assert_eq!(starts(&notes), vec![(62, 0.5, 1.0), (60, 1.5, 1.0)]);
+    }
+
+    #[test]
+    fn audio_is_repeated_up_to_the_length() {
+        let mut source = AudioSource::new(48000, 2);
+        source.data = vec![vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]; 2];
+        loop_audio(&mut source, &[2, 4], 2, 5);
+        assert_eq!(source.data, vec![vec![1.0, 2.0, 1.0, 2.0, 1.0]; 2]);
+
+        // Shorter repeats cut the previous one off
+        let mut source = AudioSource::new(48000, 1);
+        source.data = vec![vec![1.0, 2.0, 3.0]];
+        loop_audio(&mut source, &[2, 3], 2, 6);
+        assert_eq!(source.data, vec![vec![1.0, 2.0, 1.0, 1.0, 2.0]]);
+    }
+}
@@ -0,0 +1,242 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::resample::resample;
+use knodiq_engine::{AudioSource, Sample};
+use std::f64::consts::PI;
+
+/// Quality of the time-stretching and pitch-shifting.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum StretchQuality {
+    /// Short windows and a coarse search, fast enough to preview while playing.
+    Fast,
+    /// Long windows and a fine search, used when exporting.
+    High,
+}
+
+impl StretchQuality {
+    /// Length of the overlapping windows, in seconds.
+    fn window_seconds(&self) -> f64 {
+        match self {
+            StretchQuality::Fast => 0.03,
+            StretchQuality::High => 0.06,
+        }
+    }
+
+    /// How far a window may be moved to line up with the previous one, in seconds.
+    fn tolerance_seconds(&self) -> f64 {
+        match self {
+            StretchQuality::Fast => 0.008,
+            StretchQuality::High => 0.012,
+        }
+    }
+
+    /// Step between the compared positions and samples when lining up the windows.
+    fn search_step(&self) -> usize {
+        match self {
+            StretchQuality::Fast => 4,
+            StretchQuality::High => 2,
+        }
+    }
+
+    /// Number of extra input frames the stretching reads past the end of the output.
+    pub fn margin(&self, sample_rate: usize) -> usize {
+        ((self.window_seconds() + self.tolerance_seconds()) * sample_rate as f64).ceil() as usize
+    }
+}
+
+/// Change the length of the audio by the ratio and its pitch by the semitones,
+/// independently of each other.
+pub fn stretch_source(
+    source: &AudioSource,
+    ratio: f64,
+    semitones: f32,
+    quality: StretchQuality,
+) -> AudioSource {
+    let pitch = 2.0_f64.powf(semitones as f64 / 12.0);
+    let sample_rate = source.sample_rate;
+    let window = ((quality.window_seconds() * sample_rate as f64) as usize / 2 * 2).max(2);
+    let tolerance = (quality.tolerance_seconds() * sample_rate as f64) as usize;
+
+    // Stretch further by the pitch factor, then play it back faster by the same factor,
+    // which brings the length back and moves the pitch
+    let stretched = wsola(
+        &source.data,
+        ratio * pitch,
+        window,
+        tolerance,
+        quality.search_step(),
+    );
+    let mut result = source.clone();
+    result.data = match semitones != 0.0 {
+        true => resample(
+            &stretched,
+            (sample_rate as f64 * pitch).round() as usize,
+            sample_rate,
+        ),
+        false => stretched,
+    };
+    result
+}
+
+/// Stretch the buffers of each channel by the ratio without changing the pitch,
+/// with waveform-similarity overlap-add.
+/// Each window of the input is moved within the tolerance to where it best continues the
+/// previous one, and the windows are crossfaded into the output.
+fn wsola(
+    channels: &[Vec<Sample>],
+    ratio: f64,
+    window: usize,
+    tolerance: usize,
+    step: usize,
+) -> Vec<Vec<Sample>> {
+    let input_length = channels.first().map_or(0, Vec::len);
+    if (ratio - 1.0).abs() < 1e-6 || input_length == 0 {
+        return channels.to_vec();
+    }
+    let output_length = (input_length as f64 * ratio).round() as usize;
+    let hop = window / 2;
+    // Hann windows overlapping by half sum up to a constant gain of 1
+    let hann = (0..window)
+        .map(|n| (0.5 - 0.5 * (2.0 * PI * n as f64 / window as f64).cos()) as Sample)
+        .collect::<Vec<_>>();
+    // The windows are lined up on the sum of the channels, so the channels stay in phase
+    let mono = (0..input_length)
+        .map(|frame| {
+            channels
+                .iter()
+                .map(|channel| channel[frame])
+                .sum::<Sample>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut output = vec![vec![0.0; output_length + window]; channels.len()];
+    let mut previous: Option<usize> = None;
+    let mut output_position = 0;
+    while output_position < output_length {
+        let nominal = (output_position as f64 / ratio).round() as usize;
+        let position = match previous {
+            Some(previous) => best_position(&mono, previous + hop, nominal, hop, tolerance, step),
+            None => nominal,
+        };
+
+        for (channel, output) in channels.iter().zip(output.iter_mut()) {
+            for (n, weight) in hann.iter().enumerate() {
+                // The first window has nothing to crossfade with
+                let weight = match previous.is_none() && n < hop {
+                    true => 1.0,
+                    false => *weight,
+                };
+                if let Some(sample) = channel.get(position + n) {
+                    output[output_position + n] += sample * weight;
+                }
+            }
+        }
+        previous = Some(position);
+        output_position += hop;
+    }
+
+    for channel in output.iter_mut() {
+        channel.truncate(output_length);
+    }
+    output
+}
+
+/// Position around `nominal` where the input is the most similar to the input at `natural`,
+/// the audio which would follow the previous window without stretching.
+fn best_position(
+    mono: &[Sample],
+    natural: usize,
+    nominal: usize,
+    length: usize,
+    tolerance: usize,
+    step: usize,
+) -> usize {
+    if natural + length > mono.len() {
+        return nominal;
+    }
+    let first = nominal.saturating_sub(tolerance);
+    let last = (nominal + tolerance).min(mono.len().saturating_sub(length));
+
+    let mut best = nominal;
+    let mut best_similarity = Sample::MIN;
+    for candidate in (first..=last).step_by(step) {
+        let similarity = (0..length)
+            .step_by(step)
+            .map(|n| mono[candidate + n] * mono[natural + n])
+            .sum::<Sample>();
+        if similarity > best_similarity {
+            best = candidate;
+            best_similarity = similarity;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant_source(channels: usize, frames: usize) -> AudioSource {
+        let mut source = AudioSource::new(48000, channels);
+        source.data = vec![vec![0.5; frames]; channels];
+        source
+    }
+
+    fn frames(source: &AudioSource) -> Vec<usize> {
+        source.data.iter().map(Vec::len).collect()
+    }
+
+    #[test]
+    fn stretching_scales_the_length() {
+        let source = constant_source(2, 4800);
+        for (ratio, length) in [(1.5, 7200), (0.5, 2400), (2.0, 9600)] {
+            let stretched = stretch_source(&source, ratio, 0.0, StretchQuality::Fast);
+            assert_eq!(frames(&stretched), vec![length; 2]);
+        }
+    }
+
+    #[test]
+    fn pitch_shifting_keeps_the_length() {
+        let source = constant_source(1, 4800);
+        for semitones in [12.0, -12.0] {
+            for quality in [StretchQuality::Fast, StretchQuality::High] {
+                let shifted = stretch_source(&source, 1.0, semitones, quality);
+                assert_eq!(frames(&shifted), vec![4800]);
+            }
+        }
+        // Pitch factors that are not a whole ratio are off by a rounding at most
+        let shifted = stretch_source(&source, 1.0, -7.0, StretchQuality::Fast);
+        assert!(shifted.data[0].len().abs_diff(4800) <= 1);
+    }
+
+    #[test]
+    fn overlapping_windows_keep_the_level() {
+        let source = constant_source(1, 4800);
+        let stretched = stretch_source(&source, 1.5, 0.0, StretchQuality::Fast);
+        for frame in [0, 1000, 3600, 5000] {
+            assert!((stretched.data[0][frame] - 0.5).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn nothing_to_do_returns_the_audio_as_is() {
+        let mut source = constant_source(1, 100);
+        source.data[0][10] = -1.0;
+        let result = stretch_source(&source, 1.0, 0.0, StretchQuality::High);
+        assert_eq!(result.data, source.data);
+    }
+}
@@ -0,0 +1,72 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::cache::{MixCache, SegmentStream};
+use crate::api::mixing::console::Console;
+use crate::api::mixing::tempo::TempoMap;
+use knodiq_engine::{Beats, Mixer, Sample, Track};
+use std::sync::{Arc, Mutex, atomic::AtomicBool};
+
+/// Render the mix with the volume, pan, mute, solo and sends set on the console,
+/// through the bus tracks and the master bus, the same way it's played back.
+/// `on_progress` is called with the rendered fraction of the whole mix.
+pub fn render_mix(
+    mixer: &Mixer,
+    tempo_map: &TempoMap,
+    console: &Console,
+    master_track: Box<dyn Track>,
+    start: Beats,
+    end: Beats,
+    should_stop: Arc<AtomicBool>,
+    on_progress: Arc<dyn Fn(f32) + Send + Sync>,
+) -> Result<Option<Vec<Vec<Sample>>>, String> {
+    let mut mixer = mixer.clone();
+    mixer
+        .prepare()
+        .map_err(|e| format!("Error preparing mixer: {}", e))?;
+
+    // Nothing rendered for playback is reused, the export may run while the project changes
+    let cache = Arc::new(Mutex::new(MixCache::new(mixer.sample_rate)));
+    let mut stream = SegmentStream::new(
+        mixer,
+        master_track,
+        tempo_map.clone(),
+        cache,
+        Arc::new(Mutex::new(console.clone())),
+        Arc::clone(&should_stop),
+    );
+
+    let channels = stream.channels();
+    let start_frame = stream.frame_at(start);
+    let frames = stream.frame_at(end).saturating_sub(start_frame);
+    let total_samples = frames * channels;
+    let progress_interval = (stream.sample_rate() * channels).max(1);
+
+    let mut buffers = vec![Vec::with_capacity(frames); channels];
+    let mut sample_count = 0;
+    let completed = stream.play(start_frame, start_frame + frames, &mut |sample, _beat| {
+        buffers[sample_count % channels].push(sample);
+        sample_count += 1;
+        if sample_count % progress_interval == 0 {
+            on_progress(sample_count as f32 / total_samples as f32);
+        }
+    });
+
+    match completed {
+        true => Ok(Some(buffers)),
+        false => Ok(None),
+    }
+}
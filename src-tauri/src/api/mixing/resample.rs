@@ -0,0 +1,137 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::{AudioSource, Sample};
+use std::f64::consts::PI;
+
+/// Number of input samples used on each side of an output sample.
+const HALF_TAPS: isize = 32;
+
+/// Convert the audio source to the sample rate.
+pub fn resample_source(source: &AudioSource, sample_rate: usize) -> AudioSource {
+    let mut resampled = source.clone();
+    if source.sample_rate != sample_rate {
+        resampled.data = resample(&source.data, source.sample_rate, sample_rate);
+        resampled.sample_rate = sample_rate;
+    }
+    resampled
+}
+
+/// Convert the buffers of each channel from one sample rate to another,
+/// using windowed sinc interpolation.
+pub fn resample(buffers: &[Vec<Sample>], from_rate: usize, to_rate: usize) -> Vec<Vec<Sample>> {
+    if from_rate == to_rate || from_rate == 0 || to_rate == 0 {
+        return buffers.to_vec();
+    }
+    buffers
+        .iter()
+        .map(|buffer| resample_channel(buffer, from_rate, to_rate))
+        .collect()
+}
+
+fn resample_channel(input: &[Sample], from_rate: usize, to_rate: usize) -> Vec<Sample> {
+    let ratio = to_rate as f64 / from_rate as f64;
+    let output_length = (input.len() as f64 * ratio).round() as usize;
+    // Lower the cutoff when downsampling to avoid aliasing
+    let cutoff = ratio.min(1.0);
+
+    (0..output_length)
+        .map(|index| {
+            let position = index as f64 / ratio;
+            let center = position.floor() as isize;
+
+            let mut sum = 0.0;
+            for tap in (center - HALF_TAPS + 1)..=(center + HALF_TAPS) {
+                if tap < 0 || tap as usize >= input.len() {
+                    continue;
+                }
+                let distance = position - tap as f64;
+                sum += input[tap as usize] as f64
+                    * cutoff
+                    * sinc(distance * cutoff)
+                    * window(distance / HALF_TAPS as f64);
+            }
+            sum as Sample
+        })
+        .collect()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Blackman window over -1..1.
+fn window(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    let phase = PI * (x + 1.0);
+    0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f64, sample_rate: usize, frames: usize) -> Vec<Sample> {
+        (0..frames)
+            .map(|frame| (2.0 * PI * frequency * frame as f64 / sample_rate as f64).sin() as Sample)
+            .collect()
+    }
+
+    #[test]
+    fn length_follows_the_rate() {
+        let buffers = vec![vec![0.0; 44100], vec![0.0; 44100]];
+        let resampled = resample(&buffers, 44100, 48000);
+        assert_eq!(resampled.len(), 2);
+        assert!(resampled.iter().all(|buffer| buffer.len() == 48000));
+        assert_eq!(resample(&buffers, 44100, 22050)[0].len(), 22050);
+    }
+
+    #[test]
+    fn same_rate_is_unchanged() {
+        let buffers = vec![sine(440.0, 44100, 1000)];
+        assert_eq!(resample(&buffers, 44100, 44100), buffers);
+    }
+
+    #[test]
+    fn tone_keeps_its_pitch() {
+        let resampled = resample(&[sine(1000.0, 44100, 4410)], 44100, 48000);
+        let expected = sine(1000.0, 48000, 4800);
+        // The edges lack the taps before and after them
+        for frame in 100..4700 {
+            assert!(
+                (resampled[0][frame] - expected[frame]).abs() < 0.02,
+                "frame {}: expected {}, got {}",
+                frame,
+                expected[frame],
+                resampled[0][frame]
+            );
+        }
+    }
+
+    #[test]
+    fn constant_signal_keeps_its_level() {
+        let resampled = resample(&[vec![0.5; 4800]], 48000, 44100);
+        for sample in &resampled[0][100..4300] {
+            assert!((sample - 0.5).abs() < 0.01, "got {}", sample);
+        }
+    }
+}
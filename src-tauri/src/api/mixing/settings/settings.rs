@@ -0,0 +1,32 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::{MixerCommand, MixerResult, request_mixer_result};
+use crate::api::{AppState, AudioSettings};
+use std::sync::Mutex;
+use tauri::{State, command};
+
+#[command]
+pub fn set_audio_settings(
+    settings: AudioSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    settings.validate()?;
+    match request_mixer_result(MixerCommand::SetAudioSettings(settings), &state)? {
+        MixerResult::AudioSettingsChanged(result) => result,
+        _ => Err("Unexpected result type received.".to_string()),
+    }
+}
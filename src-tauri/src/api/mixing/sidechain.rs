@@ -0,0 +1,30 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::Sidechain;
+use knodiq_engine::{Graph, Value};
+
+/// Give the output of the source track to the input of the node receiving the sidechain.
+/// `channels` holds an array of the samples of each channel, from the frame the next mix
+/// starts at, so that the node reads it in step with its own audio.
+/// Returns `false` if the node isn't in the graph anymore.
+pub fn feed_sidechain(graph: &mut Graph, sidechain: &Sidechain, channels: Vec<Value>) -> bool {
+    let Some(node) = graph.get_node_mut(sidechain.node_id) else {
+        return false;
+    };
+    node.set_input(sidechain.param.as_str(), Value::Array(channels));
+    true
+}
@@ -0,0 +1,69 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::tempo::TempoCurve;
+use crate::api::mixing::tempo::tempo_map::{validate_beat, validate_tempo};
+use crate::api::mixing::{MixerCommand, send_mixer_command};
+use crate::api::{AppState, TimeSignature};
+use knodiq_engine::audio_utils::Beats;
+use std::sync::Mutex;
+use tauri::{State, command};
+
+#[command]
+pub fn set_tempo(tempo: f32, state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+    validate_tempo(tempo)?;
+    send_mixer_command(MixerCommand::SetTempo(tempo), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_time_signature(
+    time_signature: TimeSignature,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    time_signature.validate()?;
+    send_mixer_command(MixerCommand::SetTimeSignature(time_signature), &state);
+    Ok(())
+}
+
+#[command]
+pub fn add_tempo_event(
+    beat: Beats,
+    tempo: f32,
+    curve: TempoCurve,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_beat(beat)?;
+    validate_tempo(tempo)?;
+    send_mixer_command(MixerCommand::AddTempoEvent(beat, tempo, curve), &state);
+    Ok(())
+}
+
+#[command]
+pub fn move_tempo_event(
+    event_id: u32,
+    beat: Beats,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_beat(beat)?;
+    send_mixer_command(MixerCommand::MoveTempoEvent(event_id, beat), &state);
+    Ok(())
+}
+
+#[command]
+pub fn remove_tempo_event(event_id: u32, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::RemoveTempoEvent(event_id), &state);
+}
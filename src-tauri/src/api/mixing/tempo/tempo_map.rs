@@ -0,0 +1,398 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::{Beats, Mixer};
+use knodiq_note::NoteRegion;
+use serde::{Deserialize, Serialize};
+
+/// How the tempo reaches the tempo of an event.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TempoCurve {
+    /// The tempo changes at the beat of the event.
+    Stepped = 0,
+    /// The tempo changes linearly from the previous event to this one.
+    Ramped = 1,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TempoEvent {
+    pub id: u32,
+    /// Beat at which the tempo is reached.
+    pub beat: Beats,
+    /// Tempo in BPM.
+    pub tempo: f32,
+    pub curve: TempoCurve,
+}
+
+/// Tempo changes over the timeline, on top of the tempo of the mixer.
+///
+/// The engine mixes at a constant tempo, so the mixer is "warped" before mixing:
+/// every position is converted to linear beats, which are beats at the tempo of the mixer
+/// taking the same time as the musical beats do with the tempo changes.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct TempoMap {
+    /// Tempo events, sorted by beat.
+    events: Vec<TempoEvent>,
+}
+
+/// A part of the timeline where the tempo is constant or changes linearly.
+struct TempoSegment {
+    start_beat: Beats,
+    start_tempo: f32,
+    /// Beat where the segment ends, `None` for the last segment.
+    end_beat: Option<Beats>,
+    end_tempo: f32,
+}
+
+impl TempoSegment {
+    /// Change of the tempo per beat.
+    fn slope(&self) -> f32 {
+        match self.end_beat {
+            Some(end_beat) if end_beat > self.start_beat => {
+                (self.end_tempo - self.start_tempo) / (end_beat - self.start_beat)
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn tempo_at(&self, beat: Beats) -> f32 {
+        self.start_tempo + self.slope() * (beat - self.start_beat)
+    }
+
+    /// Seconds from the start of the segment to the beat.
+    fn seconds_to(&self, beat: Beats) -> f64 {
+        let beats = (beat - self.start_beat) as f64;
+        let slope = self.slope() as f64;
+        let start_tempo = self.start_tempo as f64;
+        if slope.abs() < 1e-6 {
+            60.0 * beats / start_tempo
+        } else {
+            60.0 / slope * ((start_tempo + slope * beats) / start_tempo).ln()
+        }
+    }
+
+    /// Beat reached after the seconds from the start of the segment.
+    fn beat_after(&self, seconds: f64) -> Beats {
+        let slope = self.slope() as f64;
+        let start_tempo = self.start_tempo as f64;
+        let beats = if slope.abs() < 1e-6 {
+            seconds * start_tempo / 60.0
+        } else {
+            start_tempo * ((slope * seconds / 60.0).exp() - 1.0) / slope
+        };
+        self.start_beat + beats as Beats
+    }
+}
+
+impl TempoMap {
+    pub fn new() -> Self {
+        TempoMap { events: Vec::new() }
+    }
+
+    pub fn events(&self) -> &Vec<TempoEvent> {
+        &self.events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// ID to give to the next event added to the map.
+    pub fn next_id(&self) -> u32 {
+        self.events
+            .iter()
+            .map(|event| event.id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn get_event(&self, id: u32) -> Option<&TempoEvent> {
+        self.events.iter().find(|event| event.id == id)
+    }
+
+    /// Add the event, replacing the event with the same ID if there is one.
+    pub fn insert_event(&mut self, event: TempoEvent) {
+        self.events.retain(|existing| existing.id != event.id);
+        self.events.push(event);
+        self.sort();
+    }
+
+    /// Remove the event and return it.
+    pub fn remove_event(&mut self, id: u32) -> Option<TempoEvent> {
+        let index = self.events.iter().position(|event| event.id == id)?;
+        Some(self.events.remove(index))
+    }
+
+    /// Move the event to the beat. Returns the beat it was at.
+    pub fn move_event(&mut self, id: u32, beat: Beats) -> Option<Beats> {
+        let event = self.events.iter_mut().find(|event| event.id == id)?;
+        let previous = event.beat;
+        event.beat = beat;
+        self.sort();
+        Some(previous)
+    }
+
+    /// Check that the events can be used by the project.
+    pub fn validate(&self) -> Result<(), String> {
+        self.events.iter().try_for_each(|event| {
+            validate_beat(event.beat)?;
+            validate_tempo(event.tempo)
+        })
+    }
+
+    /// Tempo at the beat, in BPM.
+    pub fn tempo_at(&self, base_tempo: f32, beat: Beats) -> f32 {
+        let segments = self.segments(base_tempo);
+        segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start_beat <= beat)
+            .unwrap_or(&segments[0])
+            .tempo_at(beat)
+    }
+
+    /// Seconds from the start of the project to the beat.
+    pub fn beat_to_seconds(&self, base_tempo: f32, beat: Beats) -> f64 {
+        let mut seconds = 0.0;
+        for segment in self.segments(base_tempo) {
+            match segment.end_beat {
+                Some(end_beat) if end_beat < beat => seconds += segment.seconds_to(end_beat),
+                _ => return seconds + segment.seconds_to(beat),
+            }
+        }
+        seconds
+    }
+
+    /// Beat reached after the seconds from the start of the project.
+    pub fn seconds_to_beat(&self, base_tempo: f32, seconds: f64) -> Beats {
+        let mut remaining = seconds;
+        for segment in self.segments(base_tempo) {
+            let length = segment
+                .end_beat
+                .map(|end_beat| segment.seconds_to(end_beat));
+            match length {
+                Some(length) if length < remaining => remaining -= length,
+                _ => return segment.beat_after(remaining),
+            }
+        }
+        remaining as Beats * base_tempo / 60.0
+    }
+
+    /// Convert a musical beat to a linear beat at `base_tempo`.
+    pub fn to_linear(&self, base_tempo: f32, beat: Beats) -> Beats {
+        if self.is_empty() {
+            return beat;
+        }
+        (self.beat_to_seconds(base_tempo, beat) * base_tempo as f64 / 60.0) as Beats
+    }
+
+    /// Convert a linear beat at `base_tempo` back to a musical beat.
+    pub fn from_linear(&self, base_tempo: f32, linear_beat: Beats) -> Beats {
+        if self.is_empty() {
+            return linear_beat;
+        }
+        self.seconds_to_beat(base_tempo, linear_beat as f64 * 60.0 / base_tempo as f64)
+    }
+
+    /// Copy the mixer with every region and note moved to linear beats,
+    /// so that mixing it at its constant tempo follows the tempo changes.
+    pub fn warp_mixer(&self, mixer: &Mixer) -> Mixer {
+        let mut warped = mixer.clone();
+        if self.is_empty() {
+            return warped;
+        }
+
+        let base_tempo = mixer.tempo;
+        for track in warped.tracks.iter_mut() {
+            let region_ids = track
+                .regions()
+                .iter()
+                .map(|region| *region.get_id())
+                .collect::<Vec<_>>();
+
+            for region_id in region_ids {
+                let Some(region) = track.get_region_mut(region_id) else {
+                    continue;
+                };
+                let start = region.start_time();
+                let end = start + region.duration();
+                let linear_start = self.to_linear(base_tempo, start);
+
+                // Notes are placed relative to the start of the region
+                if let Some(note_region) = region.as_any_mut().downcast_mut::<NoteRegion>() {
+                    let note_ids = note_region
+                        .notes()
+                        .iter()
+                        .map(|note| note.id)
+                        .collect::<Vec<_>>();
+                    for note_id in note_ids {
+                        if let Some(note) = note_region.get_note_mut(note_id) {
+                            let note_start = start + note.start_beat;
+                            let note_end = note_start + note.duration;
+                            let linear_note_start = self.to_linear(base_tempo, note_start);
+                            note.start_beat = linear_note_start - linear_start;
+                            note.duration =
+                                self.to_linear(base_tempo, note_end) - linear_note_start;
+                        }
+                    }
+                }
+
+                // Audio plays at its own speed, so only the placement of buffer regions changes
+                region.set_start_time(linear_start);
+                region.set_duration(self.to_linear(base_tempo, end) - linear_start);
+            }
+        }
+        warped
+    }
+
+    fn sort(&mut self) {
+        self.events
+            .sort_by(|a, b| a.beat.total_cmp(&b.beat).then(a.id.cmp(&b.id)));
+    }
+
+    /// Split the timeline into the parts between the events.
+    fn segments(&self, base_tempo: f32) -> Vec<TempoSegment> {
+        let mut segments = Vec::new();
+        let mut beat = 0.0;
+        let mut tempo = base_tempo;
+
+        for event in &self.events {
+            if event.beat > beat {
+                let end_tempo = match event.curve {
+                    TempoCurve::Stepped => tempo,
+                    TempoCurve::Ramped => event.tempo,
+                };
+                segments.push(TempoSegment {
+                    start_beat: beat,
+                    start_tempo: tempo,
+                    end_beat: Some(event.beat),
+                    end_tempo,
+                });
+                beat = event.beat;
+            }
+            tempo = event.tempo;
+        }
+
+        segments.push(TempoSegment {
+            start_beat: beat,
+            start_tempo: tempo,
+            end_beat: None,
+            end_tempo: tempo,
+        });
+        segments
+    }
+}
+
+/// Check that the tempo event can be placed at the beat.
+pub fn validate_beat(beat: Beats) -> Result<(), String> {
+    if !beat.is_finite() || beat < 0.0 {
+        return Err(format!("{} is not a valid beat for a tempo event.", beat));
+    }
+    Ok(())
+}
+
+/// Check that the tempo can be used by the project.
+pub fn validate_tempo(tempo: f32) -> Result<(), String> {
+    if !tempo.is_finite() || tempo <= 0.0 {
+        return Err(format!("{} is not a valid tempo.", tempo));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(events: &[(Beats, f32, TempoCurve)]) -> TempoMap {
+        let mut map = TempoMap::new();
+        for (id, (beat, tempo, curve)) in events.iter().enumerate() {
+            map.insert_event(TempoEvent {
+                id: id as u32,
+                beat: *beat,
+                tempo: *tempo,
+                curve: *curve,
+            });
+        }
+        map
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn stepped_tempo_changes_at_the_event() {
+        let map = map(&[(4.0, 60.0, TempoCurve::Stepped)]);
+        assert_close(map.beat_to_seconds(120.0, 4.0), 2.0);
+        assert_close(map.beat_to_seconds(120.0, 8.0), 6.0);
+        assert_close(map.seconds_to_beat(120.0, 6.0) as f64, 8.0);
+        assert_close(map.tempo_at(120.0, 3.0) as f64, 120.0);
+        assert_close(map.tempo_at(120.0, 5.0) as f64, 60.0);
+    }
+
+    #[test]
+    fn ramped_tempo_changes_linearly() {
+        let map = map(&[(4.0, 60.0, TempoCurve::Ramped)]);
+        // 60 / slope * ln(end tempo / start tempo), with a slope of -15 BPM per beat
+        let ramp_seconds = 60.0 / -15.0 * (60.0f64 / 120.0).ln();
+        assert_close(map.beat_to_seconds(120.0, 4.0), ramp_seconds);
+        assert_close(map.beat_to_seconds(120.0, 6.0), ramp_seconds + 2.0);
+        assert_close(map.tempo_at(120.0, 2.0) as f64, 90.0);
+    }
+
+    #[test]
+    fn beats_round_trip_through_seconds() {
+        let map = map(&[
+            (2.0, 90.0, TempoCurve::Stepped),
+            (6.0, 150.0, TempoCurve::Ramped),
+            (10.0, 70.0, TempoCurve::Ramped),
+            (12.0, 100.0, TempoCurve::Stepped),
+        ]);
+        for beat in [0.0, 1.5, 2.0, 3.25, 6.0, 7.5, 10.0, 11.0, 12.0, 20.0] {
+            let seconds = map.beat_to_seconds(120.0, beat);
+            assert_close(map.seconds_to_beat(120.0, seconds) as f64, beat as f64);
+            let linear = map.to_linear(120.0, beat);
+            assert_close(map.from_linear(120.0, linear) as f64, beat as f64);
+        }
+    }
+
+    #[test]
+    fn ramp_is_inverted_within_a_segment() {
+        let segment = TempoSegment {
+            start_beat: 4.0,
+            start_tempo: 80.0,
+            end_beat: Some(12.0),
+            end_tempo: 160.0,
+        };
+        for beat in [4.0, 5.0, 8.0, 11.5, 12.0] {
+            let seconds = segment.seconds_to(beat);
+            assert_close(segment.beat_after(seconds) as f64, beat as f64);
+        }
+    }
+
+    #[test]
+    fn empty_map_keeps_beats_linear() {
+        let map = TempoMap::new();
+        assert_eq!(map.to_linear(120.0, 7.5), 7.5);
+        assert_eq!(map.from_linear(120.0, 7.5), 7.5);
+        assert_close(map.beat_to_seconds(120.0, 8.0), 4.0);
+    }
+}
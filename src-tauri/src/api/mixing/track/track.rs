@@ -14,8 +14,9 @@
 // limitations under the License.
 //
 
-use crate::api::mixing::{MixerCommand, send_mixer_command};
-use crate::api::{AppState, TrackData};
+use crate::api::data::track_mix::{validate_pan, validate_volume};
+use crate::api::mixing::{MixerCommand, MixerResult, request_mixer_result, send_mixer_command};
+use crate::api::{AppState, Automation, TrackData};
 use std::sync::Mutex;
 use tauri::{State, command};
 
@@ -29,7 +30,128 @@ pub fn remove_track(track_id: u32, state: State<'_, Mutex<AppState>>) {
     send_mixer_command(MixerCommand::RemoveTrack(track_id), &state);
 }
 
+#[command]
+pub fn rename_track(track_id: u32, name: String, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::RenameTrack(track_id, name), &state);
+}
+
+#[command]
+pub fn move_track(track_id: u32, new_index: usize, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::MoveTrack(track_id, new_index), &state);
+}
+
+#[command]
+pub fn duplicate_track(track_id: u32, include_regions: bool, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(
+        MixerCommand::DuplicateTrack(track_id, include_regions),
+        &state,
+    );
+}
+
+#[command]
+pub fn set_track_parent(
+    track_id: u32,
+    parent_id: Option<u32>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    request_routing_change(MixerCommand::SetTrackParent(track_id, parent_id), &state)
+}
+
+#[command]
+pub fn set_folder_collapsed(track_id: u32, collapsed: bool, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(
+        MixerCommand::SetFolderCollapsed(track_id, collapsed),
+        &state,
+    );
+}
+
 #[command]
 pub fn set_track_color(track_id: u32, color: String, state: State<'_, Mutex<AppState>>) {
     send_mixer_command(MixerCommand::SetTrackColor(track_id, color), &state);
 }
+
+#[command]
+pub fn set_track_volume(
+    track_id: u32,
+    volume: f32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_volume(volume)?;
+    send_mixer_command(MixerCommand::SetTrackVolume(track_id, volume), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_track_pan(
+    track_id: u32,
+    pan: f32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_pan(pan)?;
+    send_mixer_command(MixerCommand::SetTrackPan(track_id, pan), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_track_mute(track_id: u32, mute: bool, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::SetTrackMute(track_id, mute), &state);
+}
+
+#[command]
+pub fn set_track_solo(track_id: u32, solo: bool, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::SetTrackSolo(track_id, solo), &state);
+}
+
+#[command]
+pub fn set_track_solo_safe(track_id: u32, solo_safe: bool, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::SetTrackSoloSafe(track_id, solo_safe), &state);
+}
+
+#[command]
+pub fn set_send(
+    track_id: u32,
+    bus_id: u32,
+    level: f32,
+    pre_fader: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    validate_volume(level)?;
+    request_routing_change(
+        MixerCommand::SetSend(track_id, bus_id, level, pre_fader),
+        &state,
+    )
+}
+
+#[command]
+pub fn remove_send(
+    track_id: u32,
+    bus_id: u32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    request_routing_change(MixerCommand::RemoveSend(track_id, bus_id), &state)
+}
+
+#[command]
+pub fn set_send_automation(
+    track_id: u32,
+    bus_id: u32,
+    automation: Automation,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    automation.validate()?;
+    request_routing_change(
+        MixerCommand::SetSendAutomation(track_id, bus_id, automation),
+        &state,
+    )
+}
+
+/// Send a command changing the routing, and wait for the mixer to accept or reject it.
+fn request_routing_change(
+    command: MixerCommand,
+    state: &State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    match request_mixer_result(command, state)? {
+        MixerResult::RoutingChanged(result) => result,
+        _ => Err("Unexpected result type received.".to_string()),
+    }
+}
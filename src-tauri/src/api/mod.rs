@@ -15,14 +15,24 @@
 //
 
 pub mod app_state;
+pub mod audio_output;
 pub mod data;
+pub mod export;
 pub mod graph;
+pub mod import;
 pub mod mixing;
 pub mod playback;
+pub mod project;
 pub mod setup;
 pub mod state;
+pub mod transport;
+pub mod waveform;
 pub mod window;
 
 pub use app_state::AppState;
-pub use data::{NodeType, NoteData, RegionData, RegionType, TrackData, TrackType};
+pub use data::{
+    AudioSettings, Automation, AutomationPoint, Fade, FadeCurve, LoopRange, NodeType, NoteData,
+    RegionData, RegionSettings, RegionType, Sidechain, TimeSignature, TrackData, TrackMix,
+    TrackSend, TrackType,
+};
 pub use state::{MixerState, RegionState, TrackState};
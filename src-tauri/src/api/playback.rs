@@ -15,42 +15,56 @@
 //
 
 use super::mixing::{MixerResult, mixer_command::send_mixer_command_locked};
-use crate::api::AppState;
-use crate::api::mixing::MixerCommand;
-use knodiq_engine::{AudioPlayer, audio_utils::Beats};
-use std::sync::Mutex;
-use tauri::{State, command};
+use crate::api::audio_output::AudioOutput;
+use crate::api::mixing::{MixerCommand, send_mixer_command};
+use crate::api::transport::{Transport, TransportState};
+use crate::api::{AppState, LoopRange};
+use knodiq_engine::audio_utils::Beats;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State, command};
 
 #[command]
-pub fn play_audio(at: Beats, state: State<'_, Mutex<AppState>>) {
+pub fn play_audio(at: Beats, app: AppHandle, state: State<'_, Mutex<AppState>>) {
     let mut locked_state = state.lock().unwrap();
 
-    // Clear the old audio player if it exists
-    locked_state.clear_audio_player();
-
-    let sample_rate = 48000;
-    let channels = 2;
-    let completion_handler = || {};
-
-    // Initialize the audio player with the given sample rate and channels
-    let audio_player = AudioPlayer::new(sample_rate);
-    locked_state.set_audio_player(audio_player);
-
-    // Get the audio player mutable reference from the app state
-    let audio_player_mut = locked_state.get_audio_player().unwrap();
-
-    // Initialize the audio player and get the sender to pass samples to the audio player
-    let sample_sender = match audio_player_mut.initialize_player(
-        sample_rate,
-        channels,
-        Some(Box::new(completion_handler)),
-    ) {
-        Ok(sender) => sender,
-        Err(e) => {
-            eprintln!("Error initializing audio player: {}", e);
+    // Stop the output of the old playback if it exists
+    locked_state.clear_audio_output();
+    if let Some(transport) = locked_state.transport.take() {
+        transport.cancel();
+    }
+
+    // Play back in the format of the project
+    send_mixer_command_locked(MixerCommand::GetAudioSettings, &locked_state);
+    let audio_settings = match locked_state
+        .mixer_result_receiver
+        .as_ref()
+        .map(|receiver| receiver.recv())
+    {
+        Some(Ok(MixerResult::AudioSettings(settings))) => settings,
+        _ => {
+            eprintln!("Failed to get the audio settings of the project.");
             return;
         }
     };
+    let sample_rate = audio_settings.sample_rate as usize;
+    let channels = audio_settings.channels();
+
+    // Track the playhead, and report the end of the playback once everything was heard
+    let transport = Transport::start(app.clone(), at, channels);
+    locked_state.transport = Some(Arc::clone(&transport));
+
+    // Play the samples on the default output device, which reports the frames it played
+    let (audio_output, sample_sender) =
+        match AudioOutput::start(sample_rate, channels, Arc::clone(&transport)) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Error starting the audio output: {}", e);
+                transport.stop(TransportState::Stopped, &app);
+                locked_state.transport = None;
+                return;
+            }
+        };
+    locked_state.set_audio_output(audio_output);
 
     // Start mixing the audio
     let sender = match &locked_state.mixer_command_sender {
@@ -58,73 +72,65 @@ pub fn play_audio(at: Beats, state: State<'_, Mutex<AppState>>) {
         None => return,
     };
 
-    // Send a command to the mixer to check if it needs to mix
-    let mut needs_mix = true;
-    if locked_state.mixer_result_cache.is_some() {
-        send_mixer_command_locked(MixerCommand::DoesNeedMix, &locked_state);
-        let mixer_receiver = match locked_state.mixer_result_receiver.as_ref() {
-            Some(receiver) => receiver,
-            None => {
-                eprintln!("Mixer result receiver not initialized.");
-                return;
-            }
-        };
-        needs_mix = match mixer_receiver.recv().unwrap_or_else(|err| {
-            eprintln!("Error receiving mix result: {}", err);
-            MixerResult::NeedsMix(false)
-        }) {
-            MixerResult::NeedsMix(need) => need,
-            _ => true,
-        };
-    }
+    // The mixing thread streams the segments from the mix cache, and renders the missing ones
+    let mix_command = MixerCommand::Mix(
+        at,
+        Box::new(move |sample, current_beats| {
+            // Send the mixed sample to the audio output
+            let _ = sample_sender.send(sample);
+            transport.on_sample(current_beats);
+        }),
+    );
+
+    // Send the mix command to the mixer thread
+    sender.send(mix_command).unwrap_or_else(|e| {
+        eprintln!("Error sending mix command to mixer: {}", e);
+    });
+}
 
-    if needs_mix {
-        // TODO: --- Add a cache processing here!!! ---
-
-        // let app_handle = app.clone();
-
-        // If mixing is needed, send the mix command to the mixer
-        let mix_command = MixerCommand::Mix(
-            at,
-            Box::new(move |sample, _current_beats| {
-                // Send the mixed sample to the audio player
-                let _ = sample_sender.send(sample);
-            }),
-        );
-
-        // Send the mix command to the mixer thread
-        sender.send(mix_command).unwrap_or_else(|e| {
-            eprintln!("Error sending mix command to mixer: {}", e);
-        });
-    } else {
-        // If no mixing is needed, we can directly send the samples to the audio player
-        let cached_source = locked_state.mixer_result_cache.as_ref().unwrap();
-        let channels_number = cached_source.channels;
-        let samples_number = cached_source.samples();
-
-        // Iterate through the cached mixed buffers and send samples to the audio player
-        for sample_index in 0..samples_number {
-            for channel in 0..channels_number {
-                let sample = cached_source.data[channel][sample_index];
-                // Send the sample to the audio player
-                sample_sender.send(sample).unwrap_or_else(|e| {
-                    eprintln!("Error sending sample to audio player: {}", e);
-                });
-            }
-        }
-    }
+#[command]
+pub fn pause_audio(app: AppHandle, state: State<'_, Mutex<AppState>>) {
+    end_playback(TransportState::Paused, &app, &state);
 }
 
 #[command]
-pub fn pause_audio(state: State<'_, Mutex<AppState>>) {
+pub fn stop_audio(app: AppHandle, state: State<'_, Mutex<AppState>>) {
+    end_playback(TransportState::Stopped, &app, &state);
+}
+
+/// Stop the audio output and the mixing, and report the new transport state.
+fn end_playback(
+    transport_state: TransportState,
+    app: &AppHandle,
+    state: &State<'_, Mutex<AppState>>,
+) {
     let mut locked_state = state.lock().unwrap();
-    if let Some(audio_player) = locked_state.get_audio_player() {
-        audio_player
-            .pause()
-            .unwrap_or_else(|e| eprintln!("Error pausing audio player: {}", e));
+    if let Some(transport) = locked_state.transport.take() {
+        transport.stop(transport_state, app);
     }
 
-    locked_state.clear_audio_player();
+    locked_state.clear_audio_output();
 
     send_mixer_command_locked(MixerCommand::StopMixing, &locked_state);
 }
+
+#[command]
+pub fn set_loop_range(
+    start: Beats,
+    end: Beats,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let loop_range = LoopRange {
+        start,
+        end,
+        enabled: true,
+    };
+    loop_range.validate()?;
+    send_mixer_command(MixerCommand::SetLoopRange(start, end), &state);
+    Ok(())
+}
+
+#[command]
+pub fn set_loop_enabled(enabled: bool, state: State<'_, Mutex<AppState>>) {
+    send_mixer_command(MixerCommand::SetLoopEnabled(enabled), &state);
+}
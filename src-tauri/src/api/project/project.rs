@@ -0,0 +1,57 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::AppState;
+use crate::api::mixing::{MixerCommand, MixerResult, request_mixer_result, send_mixer_command};
+use crate::api::project::{PROJECT_FILE_EXTENSION, ProjectFile};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{State, command};
+
+#[command]
+pub fn save_project(path: String, state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+    let project = match request_mixer_result(MixerCommand::GetProject, &state)? {
+        MixerResult::Project(project) => project,
+        _ => return Err("Unexpected result type received.".to_string()),
+    };
+
+    // Add the extension if the user didn't type it
+    let mut path = PathBuf::from(path);
+    if path.extension().is_none() {
+        path.set_extension(PROJECT_FILE_EXTENSION);
+    }
+
+    let json = project.to_json()?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    send_mixer_command(MixerCommand::SetProjectPath(path), &state);
+    Ok(())
+}
+
+#[command]
+pub fn open_project(path: String, state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let project = ProjectFile::from_json(&json)?;
+
+    match request_mixer_result(MixerCommand::LoadProject(project), &state)? {
+        MixerResult::ProjectLoaded(result) => result?,
+        _ => return Err("Unexpected result type received.".to_string()),
+    }
+
+    // A project which failed to load keeps the path of the current one
+    send_mixer_command(MixerCommand::SetProjectPath(PathBuf::from(path)), &state);
+    Ok(())
+}
@@ -0,0 +1,709 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::mixing::MixerContext;
+use crate::api::mixing::console::Console;
+use crate::api::mixing::master_bus::MASTER_TRACK_ID;
+use crate::api::mixing::mixer::{create_node, create_track};
+use crate::api::mixing::region::{RegionSource, add_note_with_id};
+use crate::api::mixing::tempo::TempoMap;
+use crate::api::state::{NodeData, NoteState};
+use crate::api::{
+    AudioSettings, LoopRange, NodeType, RegionSettings, Sidechain, TimeSignature, TrackData,
+    TrackMix, TrackSend, TrackType,
+};
+use kash::AudioShaderNode;
+use knodiq_engine::mixing::region::BufferRegion;
+use knodiq_engine::mixing::track::BufferTrack;
+use knodiq_engine::{Beats, Graph, Mixer, NodeId, Region, Track, Value};
+use knodiq_note::{NoteRegion, NoteTrack};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version of the project file format written by this build.
+/// This is the first format, so there are no older files to read. Files saved with a newer
+/// version are rejected, as they may hold settings this build would drop.
+pub const PROJECT_FILE_VERSION: u32 = 1;
+
+/// Extension of the project files.
+pub const PROJECT_FILE_EXTENSION: &str = "knodiq";
+
+/// On-disk representation of a whole project.
+/// Fields marked `#[serde(default)]` may be left out of the file.
+#[derive(Serialize, Deserialize)]
+pub struct ProjectFile {
+    /// Version of the file format.
+    pub version: u32,
+    /// Tempo of the project in BPM.
+    pub tempo: f32,
+    /// Time signature of the project, 4/4 by default.
+    #[serde(default)]
+    pub time_signature: TimeSignature,
+    /// Tempo changes over the timeline, none by default.
+    #[serde(default)]
+    pub tempo_map: TempoMap,
+    /// Sample rate and channel layout, 48 kHz stereo by default.
+    #[serde(default)]
+    pub audio_settings: AudioSettings,
+    /// Section of the timeline to loop over, disabled by default.
+    #[serde(default)]
+    pub loop_range: LoopRange,
+    /// Tracks in the project, in mixer order.
+    pub tracks: Vec<TrackFile>,
+    /// Graph of the master bus, passing the audio through by default.
+    #[serde(default)]
+    pub master_graph: Option<GraphFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TrackFile {
+    /// ID of the track when it was saved. Tracks get a fresh ID when loaded.
+    pub id: u32,
+    pub name: String,
+    pub channels: usize,
+    pub track_type: TrackType,
+    pub color: Option<String>,
+    /// Volume, pan, mute and solo of the track.
+    #[serde(default)]
+    pub mix: TrackMix,
+    /// Sends of the track to the bus tracks, with the saved IDs of the buses.
+    #[serde(default)]
+    pub sends: Vec<TrackSend>,
+    /// Outputs of other tracks routed into the nodes of the track, with the saved IDs
+    /// of the tracks and nodes.
+    #[serde(default)]
+    pub sidechains: Vec<Sidechain>,
+    /// Saved ID of the folder the track is in.
+    #[serde(default)]
+    pub parent_id: Option<u32>,
+    /// Whether the children of the folder track are hidden.
+    #[serde(default)]
+    pub collapsed: bool,
+    pub regions: Vec<RegionFile>,
+    pub graph: GraphFile,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RegionFile {
+    /// ID of the region when it was saved. Regions get a fresh ID when loaded.
+    pub id: u32,
+    pub name: String,
+    pub start_time: Beats,
+    pub duration: Beats,
+    pub data: RegionFileData,
+    /// Fades, gain, polarity, stretch, pitch shift and loop of the region.
+    #[serde(default)]
+    pub settings: RegionSettings,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum RegionFileData {
+    /// A region playing an audio file.
+    /// `source` is the path to the audio file and the track index in it.
+    BufferRegion {
+        source: Option<(String, usize)>,
+        /// Seconds into the audio where the region starts playing.
+        #[serde(default)]
+        offset: f64,
+        /// Tempo at which the audio plays at its own speed, the tempo of the project by default.
+        #[serde(default)]
+        tempo: Option<f32>,
+    },
+    /// A region containing notes.
+    NoteRegion { notes: Vec<NoteState> },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GraphFile {
+    pub nodes: Vec<NodeFile>,
+    pub connections: Vec<ConnectionFile>,
+    pub input_node: NodeId,
+    pub output_node: NodeId,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NodeFile {
+    /// ID of the node when it was saved. Nodes get a fresh ID when loaded.
+    pub id: NodeId,
+    pub position: (f32, f32),
+    pub data: NodeData,
+    /// Input property values set on the node.
+    pub inputs: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConnectionFile {
+    pub from: NodeId,
+    pub from_param: String,
+    pub to: NodeId,
+    pub to_param: String,
+}
+
+/// Minimal view of a project file, used to check the version before parsing the rest.
+#[derive(Deserialize)]
+struct ProjectFileHeader {
+    version: u32,
+}
+
+impl ProjectFile {
+    /// Capture the current state of the mixer.
+    pub fn from_context(context: &mut MixerContext) -> Self {
+        let tracks = context
+            .mixer
+            .tracks
+            .iter_mut()
+            .filter_map(|track| {
+                let track_id = track.get_id();
+                TrackFile::from_track(
+                    track,
+                    context.track_colors.get(&track_id).cloned(),
+                    context.collapsed_folders.contains(&track_id),
+                    &context.console.lock().unwrap(),
+                    context.node_positions.get(&track_id),
+                    context.node_inputs.get(&track_id),
+                    &context.region_sources,
+                    &context.region_settings,
+                )
+            })
+            .collect();
+
+        ProjectFile {
+            version: PROJECT_FILE_VERSION,
+            tempo: context.mixer.tempo,
+            time_signature: context.time_signature,
+            tempo_map: context.tempo_map.clone(),
+            audio_settings: AudioSettings::from_mixer(&context.mixer),
+            loop_range: context.loop_range,
+            tracks,
+            master_graph: Some(GraphFile::from_graph(
+                context.master.graph(),
+                context.node_positions.get(&MASTER_TRACK_ID),
+                context.node_inputs.get(&MASTER_TRACK_ID),
+            )),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize project: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let header: ProjectFileHeader =
+            serde_json::from_str(json).map_err(|e| format!("Invalid project file: {}", e))?;
+        if header.version > PROJECT_FILE_VERSION {
+            return Err(format!(
+                "The project was saved with a newer file format (version {}, supported up to {}).",
+                header.version, PROJECT_FILE_VERSION
+            ));
+        }
+
+        serde_json::from_str(json).map_err(|e| format!("Invalid project file: {}", e))
+    }
+
+    /// Rebuild the mixer and its side tables from the project.
+    /// The audio of the buffer regions isn't loaded yet, see `MixerContext::reload_audio_sources`.
+    pub fn restore(&self, mut mixer: Mixer) -> Result<MixerContext, String> {
+        self.time_signature.validate()?;
+        self.tempo_map.validate()?;
+        self.audio_settings.validate()?;
+        self.loop_range.validate()?;
+        mixer.tracks.clear();
+        mixer.tempo = self.tempo;
+        self.audio_settings.apply(&mut mixer);
+
+        let mut context = MixerContext::new(mixer);
+        context.time_signature = self.time_signature;
+        context.tempo_map = self.tempo_map.clone();
+        context.loop_range = self.loop_range;
+        // Sends and sidechains refer to the other tracks by their saved IDs,
+        // so they're restored once every track has its new ID
+        let mut track_ids = HashMap::new();
+        let mut restored = Vec::with_capacity(self.tracks.len());
+        for track_file in &self.tracks {
+            let (track_id, node_ids) = track_file.restore(&mut context)?;
+            track_ids.insert(track_file.id, track_id);
+            restored.push((track_id, node_ids));
+        }
+        for (track_file, (track_id, node_ids)) in self.tracks.iter().zip(&restored) {
+            track_file.restore_routing(&context, *track_id, &track_ids, node_ids)?;
+        }
+        if let Some(graph_file) = &self.master_graph {
+            let node_ids = graph_file.restore(context.master.graph_mut());
+            graph_file.restore_side_tables(&mut context, MASTER_TRACK_ID, &node_ids);
+        }
+        Ok(context)
+    }
+}
+
+impl TrackFile {
+    /// Capture the track, or `None` if it's of a type the app doesn't know.
+    pub fn from_track(
+        track: &mut Box<dyn Track>,
+        color: Option<String>,
+        collapsed: bool,
+        console: &Console,
+        node_positions: Option<&HashMap<NodeId, (f32, f32)>>,
+        node_inputs: Option<&HashMap<NodeId, HashMap<String, Value>>>,
+        region_sources: &HashMap<(u32, u32), RegionSource>,
+        region_settings: &HashMap<(u32, u32), RegionSettings>,
+    ) -> Option<Self> {
+        let id = track.get_id();
+        let Some(track_type) = console.track_type(track.as_ref()) else {
+            eprintln!(
+                "Skipping track {} of unexpected type {}.",
+                id,
+                track.track_type()
+            );
+            return None;
+        };
+        let regions = track
+            .regions()
+            .iter()
+            .map(|&region| {
+                let key = (id, *region.get_id());
+                RegionFile::from_region(
+                    region,
+                    region_sources.get(&key),
+                    region_settings.get(&key).copied().unwrap_or_default(),
+                )
+            })
+            .collect();
+        let graph = GraphFile::from_graph(track.graph(), node_positions, node_inputs);
+
+        Some(TrackFile {
+            id,
+            name: track.get_name().to_string(),
+            channels: track.channels(),
+            track_type,
+            color,
+            mix: console.get(id),
+            sends: console.sends(id).to_vec(),
+            sidechains: console.sidechains(id).to_vec(),
+            parent_id: console.parent(id),
+            collapsed,
+            regions,
+            graph,
+        })
+    }
+
+    /// Add the track to the mixer, without loading the audio of its buffer regions.
+    /// Returns its new ID, and the new ID of each node keyed by its saved ID.
+    pub fn restore(
+        &self,
+        context: &mut MixerContext,
+    ) -> Result<(u32, HashMap<NodeId, NodeId>), String> {
+        self.mix.validate()?;
+        if self.track_type.is_submix() && !self.regions.is_empty() {
+            return Err(format!("Track \"{}\" can't have regions.", self.name));
+        }
+        let track_data = TrackData {
+            name: self.name.clone(),
+            channels: self.channels,
+            track_type: self.track_type.clone(),
+        };
+        let mut track = create_track(&track_data);
+        let node_ids = self.graph.restore(track.graph_mut());
+
+        context.mixer.add_track(track);
+        let track_id = match context.mixer.tracks.last() {
+            Some(track) => track.get_id(),
+            None => return Err(format!("Failed to add track \"{}\".", self.name)),
+        };
+
+        self.graph.restore_side_tables(context, track_id, &node_ids);
+        if let Some(color) = &self.color {
+            context.track_colors.insert(track_id, color.clone());
+        }
+        if self.collapsed {
+            context.collapsed_folders.insert(track_id);
+        }
+        let mut console = context.console.lock().unwrap();
+        console.set(track_id, self.mix);
+        console.set_track_type(track_id, &self.track_type);
+        drop(console);
+
+        for region_file in &self.regions {
+            region_file.restore(context, track_id)?;
+        }
+        Ok((track_id, node_ids))
+    }
+
+    /// Restore the folder, sends and sidechains of the track,
+    /// given the new ID of each track and node keyed by its saved ID.
+    pub fn restore_routing(
+        &self,
+        context: &MixerContext,
+        track_id: u32,
+        track_ids: &HashMap<u32, u32>,
+        node_ids: &HashMap<NodeId, NodeId>,
+    ) -> Result<(), String> {
+        let new_track_id = |saved_id: u32| {
+            track_ids
+                .get(&saved_id)
+                .copied()
+                .ok_or_else(|| format!("Track with ID {} not found.", saved_id))
+        };
+
+        // Set the routes one by one so that the next ones are checked for feedback
+        let mut console = context.console.lock().unwrap();
+        if let Some(parent_id) = self.parent_id {
+            let parent_id = new_track_id(parent_id)?;
+            console.validate_parent(track_id, parent_id)?;
+            console.set_parent(track_id, Some(parent_id));
+        }
+
+        let mut sends = Vec::with_capacity(self.sends.len());
+        for send in &self.sends {
+            send.validate()?;
+            let bus_id = new_track_id(send.bus_id)?;
+            console.validate_send(track_id, bus_id)?;
+            sends.push(TrackSend {
+                bus_id,
+                ..send.clone()
+            });
+            console.set_sends(track_id, sends.clone());
+        }
+
+        let mut sidechains = Vec::with_capacity(self.sidechains.len());
+        for sidechain in &self.sidechains {
+            let source_id = new_track_id(sidechain.source_id)?;
+            let node_id = *node_ids
+                .get(&sidechain.node_id)
+                .ok_or_else(|| format!("Node with ID {} not found.", sidechain.node_id))?;
+            console.validate_sidechain(track_id, source_id)?;
+            sidechains.push(Sidechain {
+                source_id,
+                node_id,
+                param: sidechain.param.clone(),
+            });
+            console.set_sidechains(track_id, sidechains.clone());
+        }
+        Ok(())
+    }
+}
+
+impl RegionFile {
+    pub fn from_region(
+        region: &dyn Region,
+        source: Option<&RegionSource>,
+        settings: RegionSettings,
+    ) -> Self {
+        let data = if let Some(note_region) = region.as_any().downcast_ref::<NoteRegion>() {
+            RegionFileData::NoteRegion {
+                notes: note_region
+                    .notes()
+                    .iter()
+                    .map(NoteState::from_note)
+                    .collect(),
+            }
+        } else {
+            RegionFileData::BufferRegion {
+                source: source.map(|source| (source.path.clone(), source.track_index)),
+                offset: source.map_or(0.0, |source| source.offset),
+                tempo: source.map(|source| source.tempo),
+            }
+        };
+
+        RegionFile {
+            id: *region.get_id(),
+            name: region.get_name().to_string(),
+            start_time: region.start_time(),
+            duration: region.duration(),
+            data,
+            settings,
+        }
+    }
+
+    fn restore(&self, context: &mut MixerContext, track_id: u32) -> Result<(), String> {
+        self.settings.validate()?;
+        let track = context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .ok_or_else(|| format!("Track with ID {} not found.", track_id))?;
+
+        let region_id = match &self.data {
+            RegionFileData::BufferRegion {
+                source,
+                offset,
+                tempo,
+            } => {
+                let buffer_track = track
+                    .as_any_mut()
+                    .downcast_mut::<BufferTrack>()
+                    .ok_or_else(|| format!("Region \"{}\" requires a buffer track.", self.name))?;
+                let region = BufferRegion::empty(self.name.clone());
+                let region_id = buffer_track
+                    .add_region(Box::new(region), self.start_time, self.duration)
+                    .map_err(|e| format!("Error adding region \"{}\": {}", self.name, e))?;
+
+                if let Some((path, track_index)) = source {
+                    let tempo = tempo.unwrap_or(context.mixer.tempo);
+                    let mut source = RegionSource::new(path.clone(), *track_index, tempo);
+                    source.offset = *offset;
+                    context.region_sources.insert((track_id, region_id), source);
+                }
+                region_id
+            }
+
+            RegionFileData::NoteRegion { notes } => {
+                let note_track = track
+                    .as_any_mut()
+                    .downcast_mut::<NoteTrack>()
+                    .ok_or_else(|| format!("Region \"{}\" requires a note track.", self.name))?;
+                let mut region = NoteRegion::new(self.name.clone(), self.start_time, self.duration);
+                for note in notes {
+                    add_note_with_id(
+                        &mut region,
+                        note.id,
+                        note.pitch,
+                        note.velocity,
+                        note.start_time,
+                        note.duration,
+                    )
+                    .map_err(|e| format!("Region \"{}\": {}", self.name, e))?;
+                }
+                note_track
+                    .add_region(Box::new(region), self.start_time, self.duration)
+                    .map_err(|e| format!("Error adding region \"{}\": {}", self.name, e))?
+            }
+        };
+
+        if !self.settings.is_default() {
+            context
+                .region_settings
+                .insert((track_id, region_id), self.settings);
+        }
+        Ok(())
+    }
+}
+
+impl GraphFile {
+    pub fn from_graph(
+        graph: &Graph,
+        node_positions: Option<&HashMap<NodeId, (f32, f32)>>,
+        node_inputs: Option<&HashMap<NodeId, HashMap<String, Value>>>,
+    ) -> Self {
+        let nodes = graph
+            .get_nodes()
+            .iter()
+            .map(|node| {
+                let id = node.get_id();
+                NodeFile {
+                    id,
+                    position: node_positions
+                        .and_then(|positions| positions.get(&id))
+                        .cloned()
+                        .unwrap_or((0.0, 0.0)),
+                    data: NodeData::from_node(node),
+                    inputs: node_inputs
+                        .and_then(|inputs| inputs.get(&id))
+                        .cloned()
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let connections = graph
+            .get_connections()
+            .iter()
+            .map(|connection| ConnectionFile {
+                from: connection.from,
+                from_param: connection.from_param.clone(),
+                to: connection.to,
+                to_param: connection.to_param.clone(),
+            })
+            .collect();
+
+        GraphFile {
+            nodes,
+            connections,
+            input_node: graph.get_input_node_id(),
+            output_node: graph.get_output_node_id(),
+        }
+    }
+
+    /// Restore the positions and input properties of the nodes with their new IDs.
+    pub fn restore_side_tables(
+        &self,
+        context: &mut MixerContext,
+        track_id: u32,
+        node_ids: &HashMap<NodeId, NodeId>,
+    ) {
+        for node_file in &self.nodes {
+            if let Some(node_id) = node_ids.get(&node_file.id) {
+                context
+                    .node_positions
+                    .entry(track_id)
+                    .or_default()
+                    .insert(*node_id, node_file.position);
+                if !node_file.inputs.is_empty() {
+                    context
+                        .node_inputs
+                        .entry(track_id)
+                        .or_default()
+                        .insert(*node_id, node_file.inputs.clone());
+                }
+            }
+        }
+    }
+
+    /// Rebuild the nodes and connections in the graph of a newly created track.
+    /// Returns the new ID of each node, keyed by the ID stored in the file.
+    pub fn restore(&self, graph: &mut Graph) -> HashMap<NodeId, NodeId> {
+        // The input and output nodes are created together with the track
+        let mut node_ids = HashMap::new();
+        node_ids.insert(self.input_node, graph.get_input_node_id());
+        node_ids.insert(self.output_node, graph.get_output_node_id());
+
+        for node_file in &self.nodes {
+            if node_ids.contains_key(&node_file.id) {
+                continue;
+            }
+
+            let node_type = match node_file.data {
+                NodeData::AudioShaderNode { .. } => NodeType::AudioShaderNode,
+                NodeData::EmptyNode => NodeType::EmptyNode,
+                NodeData::NoteInputNode => NodeType::NoteInputNode,
+                NodeData::Invalid => {
+                    eprintln!("Skipping node {} of unknown type.", node_file.id);
+                    continue;
+                }
+            };
+            let node = create_node(&node_type);
+            node_ids.insert(node_file.id, node.get_id());
+            graph.add_node(node);
+        }
+
+        for node_file in &self.nodes {
+            let Some(node) = node_ids
+                .get(&node_file.id)
+                .and_then(|node_id| graph.get_node_mut(*node_id))
+            else {
+                continue;
+            };
+
+            if let NodeData::AudioShaderNode { shader_code } = &node_file.data {
+                if let Some(shader_node) = node.as_any_mut().downcast_mut::<AudioShaderNode>() {
+                    if let Err(errors) = shader_node.set_shader(shader_code.clone()) {
+                        eprintln!("Error compiling AudioShader: {}", errors.join("\n"));
+                    }
+                }
+            }
+            for (key, value) in &node_file.inputs {
+                node.set_input(key.as_str(), value.clone());
+            }
+        }
+
+        for connection in &self.connections {
+            match (node_ids.get(&connection.from), node_ids.get(&connection.to)) {
+                (Some(from), Some(to)) => {
+                    graph.connect(
+                        *from,
+                        connection.from_param.clone(),
+                        *to,
+                        connection.to_param.clone(),
+                    );
+                }
+                _ => eprintln!(
+                    "Skipping connection from {} to {} with a missing node.",
+                    connection.from, connection.to
+                ),
+            }
+        }
+
+        node_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_json(version: u32, tempo: f32) -> String {
+        format!(r#"{{"version":{},"tempo":{},"tracks":[]}}"#, version, tempo)
+    }
+
+    #[test]
+    fn newer_file_formats_are_rejected() {
+        let json = project_json(PROJECT_FILE_VERSION + 1, 120.0);
+        assert!(ProjectFile::from_json(&json).is_err());
+    }
+
+    #[test]
+    fn left_out_fields_take_their_defaults() {
+        let project = ProjectFile::from_json(&project_json(1, 98.0)).unwrap();
+        assert_eq!((project.version, project.tempo), (1, 98.0));
+        assert!(project.tracks.is_empty());
+
+        let context = project.restore(Mixer::new(120.0, 48000, 2)).unwrap();
+        assert_eq!(context.mixer.tempo, 98.0);
+        assert!(context.mixer.tracks.is_empty());
+    }
+
+    #[test]
+    fn tracks_and_notes_are_restored() {
+        let mut context = MixerContext::new(Mixer::new(120.0, 48000, 2));
+        context.mixer.add_track(create_track(&TrackData {
+            name: "Lead".to_string(),
+            channels: 2,
+            track_type: TrackType::NoteTrack,
+        }));
+        let track_id = context.mixer.tracks[0].get_id();
+        let mut region = NoteRegion::new("Melody".to_string(), 4.0, 8.0);
+        region.add_note(64, 90, 1.0, 0.5);
+        let note_id = region.notes()[0].id;
+        context
+            .mixer
+            .get_track_by_id_mut(track_id)
+            .and_then(|track| track.as_any_mut().downcast_mut::<NoteTrack>())
+            .unwrap()
+            .add_region(Box::new(region), 4.0, 8.0)
+            .unwrap();
+        context.track_colors.insert(track_id, "#ff8800".to_string());
+
+        let json = ProjectFile::from_context(&mut context).to_json().unwrap();
+        let restored = ProjectFile::from_json(&json)
+            .unwrap()
+            .restore(Mixer::new(120.0, 48000, 2))
+            .unwrap();
+
+        assert_eq!(restored.mixer.tracks.len(), 1);
+        let track = &restored.mixer.tracks[0];
+        assert_eq!(track.get_name(), "Lead");
+        assert_eq!(
+            restored
+                .track_colors
+                .get(&track.get_id())
+                .map(String::as_str),
+            Some("#ff8800")
+        );
+        let regions = track.regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].start_time(), regions[0].duration()), (4.0, 8.0));
+        let notes = regions[0]
+            .as_any()
+            .downcast_ref::<NoteRegion>()
+            .unwrap()
+            .notes();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(
+            (notes[0].id, notes[0].pitch, notes[0].velocity),
+            (note_id, 64, 90)
+        );
+        assert_eq!((notes[0].start_beat, notes[0].duration), (1.0, 0.5));
+    }
+}
@@ -14,7 +14,6 @@
 // limitations under the License.
 //
 
-use kash::AudioShaderNode;
 use knodiq_engine::{Connector, Graph, Node, NodeId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -36,17 +35,7 @@ pub struct NodeState {
 
 impl NodeState {
     pub fn from_node(node: &Box<dyn Node>, position: (f32, f32)) -> Self {
-        let data = match node.get_type().as_str() {
-            "AudioShaderNode" => {
-                let shader_node = node.as_any().downcast_ref::<AudioShaderNode>().unwrap();
-                NodeData::AudioShaderNode {
-                    shader_code: shader_node.get_shader().to_string(),
-                }
-            }
-            "EmptyNode" => NodeData::EmptyNode,
-            "NoteInputNode" => NodeData::NoteInputNode,
-            _ => NodeData::Invalid,
-        };
+        let data = NodeData::from_node(node);
 
         NodeState {
             id: node.get_id().to_string(),
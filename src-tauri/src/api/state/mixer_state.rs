@@ -14,15 +14,29 @@
 // limitations under the License.
 //
 
-use crate::api::TrackState;
-use knodiq_engine::{Mixer, NodeId, audio_utils::Beats};
+use crate::api::mixing::console::Console;
+use crate::api::mixing::master_bus::MASTER_TRACK_ID;
+use crate::api::mixing::region::{RegionSource, RegionStatus};
+use crate::api::mixing::tempo::{TempoEvent, TempoMap};
+use crate::api::state::GraphState;
+use crate::api::{AudioSettings, LoopRange, RegionSettings, TimeSignature, TrackState};
+use knodiq_engine::{Graph, Mixer, NodeId, audio_utils::Beats};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, Deserialize)]
 pub struct MixerState {
+    /// Tracks shown in the track list. Tracks in a collapsed folder are left out.
     pub tracks: Vec<TrackState>,
+    /// Graph of the master bus, addressed with `MASTER_TRACK_ID` in the graph commands.
+    pub master_graph: GraphState,
     pub bpm: f32,
+    pub time_signature: TimeSignature,
+    pub audio_settings: AudioSettings,
+    /// Tempo changes over the timeline, sorted by beat.
+    pub tempo_events: Vec<TempoEvent>,
+    pub loop_range: LoopRange,
+    /// Samples per beat at the start of the project.
     pub samples_per_beat: f32,
     pub duration: Beats,
     pub node_positions: Vec<(u32, Vec<(NodeId, (f32, f32))>)>,
@@ -33,11 +47,21 @@ impl MixerState {
         mixer: &mut Mixer,
         node_positions: &HashMap<u32, HashMap<NodeId, (f32, f32)>>,
         track_colors: &HashMap<u32, String>,
+        collapsed_folders: &HashSet<u32>,
+        region_settings: &HashMap<(u32, u32), RegionSettings>,
+        region_sources: &HashMap<(u32, u32), RegionSource>,
+        region_status: &HashMap<(u32, u32), RegionStatus>,
+        time_signature: TimeSignature,
+        tempo_map: &TempoMap,
+        loop_range: LoopRange,
+        console: &Console,
+        master_graph: &Graph,
     ) -> Self {
         let tracks = mixer
             .tracks
             .iter_mut()
-            .map(|track| {
+            .filter(|track| !is_hidden(track.get_id(), collapsed_folders, console))
+            .filter_map(|track| {
                 let track_node_positions = node_positions
                     .get(&track.get_id())
                     .cloned()
@@ -46,11 +70,30 @@ impl MixerState {
                     .get(&track.get_id())
                     .cloned()
                     .unwrap_or_else(|| "#FFFFFF".to_string());
-                TrackState::from_track(track, &track_node_positions, track_color)
+                TrackState::from_track(
+                    track,
+                    &track_node_positions,
+                    track_color,
+                    collapsed_folders.contains(&track.get_id()),
+                    region_settings,
+                    region_sources,
+                    region_status,
+                    console,
+                )
             })
             .collect::<Vec<_>>();
+        let master_graph = GraphState::from_graph(
+            master_graph,
+            &node_positions
+                .get(&MASTER_TRACK_ID)
+                .cloned()
+                .unwrap_or_default(),
+        );
         let bpm = mixer.tempo;
-        let samples_per_beat = mixer.samples_per_beat();
+        let audio_settings = AudioSettings::from_mixer(mixer);
+        let tempo_events = tempo_map.events().clone();
+        let samples_per_beat =
+            mixer.samples_per_beat() * mixer.tempo / tempo_map.tempo_at(mixer.tempo, 0.0);
         let duration = mixer.duration();
 
         let node_positions = node_positions
@@ -68,7 +111,12 @@ impl MixerState {
 
         MixerState {
             tracks,
+            master_graph,
             bpm,
+            time_signature,
+            audio_settings,
+            tempo_events,
+            loop_range,
             samples_per_beat,
             duration,
             node_positions,
@@ -76,14 +124,53 @@ impl MixerState {
     }
 }
 
+/// Whether the track is in a collapsed folder, directly or through other folders.
+fn is_hidden(track_id: u32, collapsed_folders: &HashSet<u32>, console: &Console) -> bool {
+    console
+        .ancestors(track_id)
+        .iter()
+        .any(|folder_id| collapsed_folders.contains(folder_id))
+}
+
 impl Clone for MixerState {
     fn clone(&self) -> Self {
         MixerState {
             tracks: self.tracks.clone(),
+            master_graph: self.master_graph.clone(),
             bpm: self.bpm,
+            time_signature: self.time_signature,
+            audio_settings: self.audio_settings,
+            tempo_events: self.tempo_events.clone(),
+            loop_range: self.loop_range,
             samples_per_beat: self.samples_per_beat,
             duration: self.duration,
             node_positions: self.node_positions.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::TrackType;
+
+    #[test]
+    fn tracks_in_a_collapsed_folder_are_hidden() {
+        // Track 3 is in folder 2, which is in folder 1
+        let mut console = Console::new();
+        for folder_id in [1, 2] {
+            console.set_track_type(folder_id, &TrackType::FolderTrack);
+        }
+        console.set_parent(2, Some(1));
+        console.set_parent(3, Some(2));
+
+        let collapsed_folders = HashSet::from([1]);
+        assert!(!is_hidden(1, &collapsed_folders, &console));
+        assert!(is_hidden(2, &collapsed_folders, &console));
+        assert!(is_hidden(3, &collapsed_folders, &console));
+
+        let collapsed_folders = HashSet::from([2]);
+        assert!(!is_hidden(2, &collapsed_folders, &console));
+        assert!(is_hidden(3, &collapsed_folders, &console));
+    }
+}
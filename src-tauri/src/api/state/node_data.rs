@@ -14,6 +14,8 @@
 // limitations under the License.
 //
 
+use kash::AudioShaderNode;
+use knodiq_engine::Node;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -24,6 +26,22 @@ pub enum NodeData {
     Invalid,
 }
 
+impl NodeData {
+    pub fn from_node(node: &Box<dyn Node>) -> Self {
+        match node.get_type().as_str() {
+            "AudioShaderNode" => {
+                let shader_node = node.as_any().downcast_ref::<AudioShaderNode>().unwrap();
+                NodeData::AudioShaderNode {
+                    shader_code: shader_node.get_shader().to_string(),
+                }
+            }
+            "EmptyNode" => NodeData::EmptyNode,
+            "NoteInputNode" => NodeData::NoteInputNode,
+            _ => NodeData::Invalid,
+        }
+    }
+}
+
 impl Clone for NodeData {
     fn clone(&self) -> Self {
         match self {
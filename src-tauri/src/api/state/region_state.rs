@@ -14,11 +14,13 @@
 // limitations under the License.
 //
 
-use knodiq_engine::{Region, mixing::region::BufferRegion};
+use knodiq_engine::{Beats, Region, mixing::region::BufferRegion};
 use knodiq_note::NoteRegion;
 use serde::{Deserialize, Serialize};
 
+use crate::api::mixing::region::{RegionSource, RegionStatus, repeat_starts};
 use crate::api::state::NoteState;
+use crate::api::{Fade, RegionSettings};
 
 #[derive(Serialize, Deserialize)]
 pub struct RegionState {
@@ -27,10 +29,42 @@ pub struct RegionState {
     pub start_time: f32,
     pub duration: f32,
     pub data: RegionDataState,
+    /// Fade at the start of the region set by the user.
+    pub fade_in: Fade,
+    /// Fade at the end of the region set by the user.
+    pub fade_out: Fade,
+    /// Length of the automatic equal-power crossfade with an earlier overlapping region.
+    pub crossfade_in: f32,
+    /// Length of the automatic equal-power crossfade with a later overlapping region.
+    pub crossfade_out: f32,
+    /// Clip gain of the region in dB.
+    pub gain: f32,
+    /// Whether the polarity of the audio is inverted.
+    pub invert: bool,
+    /// How much longer the audio plays than at its own speed, on top of following the tempo.
+    pub stretch: f32,
+    /// Pitch shift of the audio in semitones.
+    pub pitch_shift: f32,
+    /// Seconds into the audio where the region starts playing.
+    pub source_offset: f64,
+    /// Length of the content repeated over the region, if it loops.
+    pub loop_length: Option<Beats>,
+    /// Beats relative to the start of the region where its content starts over.
+    pub repeats: Vec<Beats>,
+    /// Whether the audio file is still being decoded.
+    pub loading: bool,
+    /// Why the audio file couldn't be decoded.
+    pub load_error: Option<String>,
 }
 
 impl RegionState {
-    pub fn from_region(region: Box<&dyn Region>) -> Self {
+    pub fn from_region(
+        region: Box<&dyn Region>,
+        settings: RegionSettings,
+        crossfade: (Beats, Beats),
+        source: Option<&RegionSource>,
+        status: Option<&RegionStatus>,
+    ) -> Self {
         RegionState {
             id: *region.get_id(),
             name: region.get_name().to_string(),
@@ -49,6 +83,22 @@ impl RegionState {
             } else {
                 panic!("Unknown region type");
             },
+            fade_in: settings.fade_in,
+            fade_out: settings.fade_out,
+            crossfade_in: crossfade.0,
+            crossfade_out: crossfade.1,
+            gain: settings.gain,
+            invert: settings.invert,
+            stretch: settings.stretch,
+            pitch_shift: settings.pitch_shift,
+            source_offset: source.map_or(0.0, |source| source.offset),
+            loop_length: settings.loop_length,
+            repeats: repeat_starts(region.duration(), settings.loop_length),
+            loading: matches!(status, Some(RegionStatus::Loading)),
+            load_error: match status {
+                Some(RegionStatus::Failed(error)) => Some(error.clone()),
+                _ => None,
+            },
         }
     }
 }
@@ -66,6 +116,19 @@ impl Clone for RegionState {
                     RegionDataState::NoteRegion(notes.iter().cloned().collect())
                 }
             },
+            fade_in: self.fade_in,
+            fade_out: self.fade_out,
+            crossfade_in: self.crossfade_in,
+            crossfade_out: self.crossfade_out,
+            gain: self.gain,
+            invert: self.invert,
+            stretch: self.stretch,
+            pitch_shift: self.pitch_shift,
+            source_offset: self.source_offset,
+            loop_length: self.loop_length,
+            repeats: self.repeats.clone(),
+            loading: self.loading,
+            load_error: self.load_error.clone(),
         }
     }
 }
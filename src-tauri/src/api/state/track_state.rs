@@ -14,7 +14,11 @@
 // limitations under the License.
 //
 
-use crate::api::{RegionState, TrackType, state::GraphState};
+use crate::api::mixing::console::Console;
+use crate::api::mixing::region::{RegionSource, RegionStatus, crossfades};
+use crate::api::{
+    RegionSettings, RegionState, Sidechain, TrackMix, TrackSend, TrackType, state::GraphState,
+};
 use knodiq_engine::{NodeId, Track};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -28,30 +32,64 @@ pub struct TrackState {
     pub regions: Vec<RegionState>,
     pub color: String,
     pub graph: GraphState,
+    /// Volume, pan, mute and solo of the track.
+    pub mix: TrackMix,
+    /// Whether the track is heard, taking the solo of the other tracks into account.
+    pub audible: bool,
+    /// Sends of the track to the bus tracks.
+    pub sends: Vec<TrackSend>,
+    /// Outputs of other tracks routed into the nodes of the track.
+    pub sidechains: Vec<Sidechain>,
+    /// ID of the folder track the track is in.
+    pub parent_id: Option<u32>,
+    /// Whether the tracks in the folder are hidden.
+    pub collapsed: bool,
 }
 
 impl TrackState {
+    /// State of the track, or `None` if it's of a type the app doesn't know.
     pub fn from_track(
         track: &mut Box<dyn Track>,
         node_positions: &HashMap<NodeId, (f32, f32)>,
         color: String,
-    ) -> Self {
+        collapsed: bool,
+        region_settings: &HashMap<(u32, u32), RegionSettings>,
+        region_sources: &HashMap<(u32, u32), RegionSource>,
+        region_status: &HashMap<(u32, u32), RegionStatus>,
+        console: &Console,
+    ) -> Option<Self> {
         let id = track.get_id();
         let name = track.get_name().to_string();
         let channels = track.channels();
-        let track_type = match track.track_type().as_str() {
-            "BufferTrack" => TrackType::BufferTrack,
-            "NoteTrack" => TrackType::NoteTrack,
-            _ => panic!("Unexpected track type"),
+        let Some(track_type) = console.track_type(track.as_ref()) else {
+            eprintln!(
+                "Skipping track {} of unexpected type {}.",
+                id,
+                track.track_type()
+            );
+            return None;
         };
+        let crossfades = crossfades(track.as_ref());
         let regions = track
             .regions()
             .iter()
-            .map(|&region| RegionState::from_region(Box::new(region)))
+            .map(|&region| {
+                let region_id = *region.get_id();
+                RegionState::from_region(
+                    Box::new(region),
+                    region_settings
+                        .get(&(id, region_id))
+                        .copied()
+                        .unwrap_or_default(),
+                    crossfades.get(&region_id).copied().unwrap_or_default(),
+                    region_sources.get(&(id, region_id)),
+                    region_status.get(&(id, region_id)),
+                )
+            })
             .collect::<Vec<_>>();
         let graph = GraphState::from_graph(track.graph(), node_positions);
 
-        TrackState {
+        Some(TrackState {
             id,
             name,
             channels,
@@ -59,7 +97,13 @@ impl TrackState {
             regions,
             color,
             graph,
-        }
+            mix: console.get(id),
+            audible: console.is_audible(id),
+            sends: console.sends(id).to_vec(),
+            sidechains: console.sidechains(id).to_vec(),
+            parent_id: console.parent(id),
+            collapsed,
+        })
     }
 }
 
@@ -73,6 +117,12 @@ impl Clone for TrackState {
             regions: self.regions.clone(),
             color: self.color.clone(),
             graph: self.graph.clone(),
+            mix: self.mix,
+            audible: self.audible,
+            sends: self.sends.clone(),
+            sidechains: self.sidechains.clone(),
+            parent_id: self.parent_id,
+            collapsed: self.collapsed,
         }
     }
 }
@@ -0,0 +1,258 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::audio_utils::Beats;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Interval between two `transport_position` events, about the refresh rate of a display.
+const POSITION_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Number of frames between two beats recorded from the mixer.
+const CHECKPOINT_FRAMES: usize = 256;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransportState {
+    Playing,
+    Paused,
+    Stopped,
+    /// The playback reached the end of the project.
+    Finished,
+}
+
+/// Payload of the `transport_position` event.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransportPosition {
+    /// Beat the audio player is playing.
+    pub beat: Beats,
+}
+
+/// Payload of the `transport_state` event.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransportStateChanged {
+    pub state: TransportState,
+    /// Position of the playhead when the state changed.
+    pub beat: Beats,
+}
+
+/// Playhead of a single playback.
+///
+/// The mixer runs ahead of the audio output, so the beats reported by the mixer are kept
+/// together with the frame they were mixed at, and the position is taken at the frame
+/// the audio output reports to be heard.
+pub struct Transport {
+    start_beat: Beats,
+    channels: usize,
+    /// Number of samples sent to the audio output.
+    samples_sent: AtomicUsize,
+    /// Number of frames the audio output has taken to be played.
+    frames_played: AtomicUsize,
+    /// Number of frames between the audio output taking a frame and the frame being heard.
+    latency_frames: AtomicUsize,
+    /// Beats reported by the mixer, with the frame they were mixed at.
+    checkpoints: Mutex<VecDeque<(usize, Beats)>>,
+    /// Last beat passed by the playhead.
+    position: Mutex<Beats>,
+    finished: AtomicBool,
+    stopped: AtomicBool,
+}
+
+impl Transport {
+    pub fn new(start_beat: Beats, channels: usize) -> Self {
+        Transport {
+            start_beat,
+            channels: channels.max(1),
+            samples_sent: AtomicUsize::new(0),
+            frames_played: AtomicUsize::new(0),
+            latency_frames: AtomicUsize::new(0),
+            checkpoints: Mutex::new(VecDeque::new()),
+            position: Mutex::new(start_beat),
+            finished: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Start tracking a playback from the beat, and emit its position until it ends.
+    pub fn start(app: AppHandle, start_beat: Beats, channels: usize) -> Arc<Transport> {
+        let transport = Arc::new(Transport::new(start_beat, channels));
+        emit_state(&app, TransportState::Playing, start_beat);
+
+        let thread_transport = Arc::clone(&transport);
+        if let Err(e) = thread::Builder::new()
+            .name("transport_thread".into())
+            .spawn(move || thread_transport.run(&app))
+        {
+            eprintln!("Failed to start transport thread: {}", e);
+        }
+        transport
+    }
+
+    /// Record a sample sent to the audio output, with the beat the mixer reported for it.
+    pub fn on_sample(&self, beat: Beats) {
+        let sample_index = self.samples_sent.fetch_add(1, Ordering::AcqRel);
+        if sample_index % (CHECKPOINT_FRAMES * self.channels) == 0 {
+            let frame = sample_index / self.channels;
+            self.checkpoints.lock().unwrap().push_back((frame, beat));
+        }
+    }
+
+    /// Record the frames the audio output has taken to be played,
+    /// and the number of frames it takes until they are heard.
+    pub fn on_played(&self, frames: usize, latency_frames: usize) {
+        self.frames_played.fetch_add(frames, Ordering::AcqRel);
+        self.latency_frames.store(latency_frames, Ordering::Release);
+    }
+
+    /// Mark the playback as finished, once the audio output has taken every sample.
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Release);
+    }
+
+    /// End the playback without emitting anything, when it's replaced by a new one.
+    pub fn cancel(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    /// End the playback because it was paused or stopped by the user.
+    /// Stopping moves the playhead back to where the playback started.
+    pub fn stop(&self, state: TransportState, app: &AppHandle) {
+        if self.stopped.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let beat = match state {
+            TransportState::Stopped => self.start_beat,
+            _ => self.update_position(),
+        };
+        app.emit("transport_position", TransportPosition { beat })
+            .ok();
+        emit_state(app, state, beat);
+    }
+
+    fn run(&self, app: &AppHandle) {
+        loop {
+            thread::sleep(POSITION_INTERVAL);
+            if self.stopped.load(Ordering::Acquire) {
+                break;
+            }
+
+            let finished = self.is_finished();
+            let beat = self.update_position();
+            app.emit("transport_position", TransportPosition { beat })
+                .ok();
+
+            if finished && !self.stopped.swap(true, Ordering::AcqRel) {
+                emit_state(app, TransportState::Finished, beat);
+                break;
+            }
+        }
+    }
+
+    /// Move the playhead to the frame being heard and return its beat.
+    fn update_position(&self) -> Beats {
+        let played_frames = self.played_frames();
+        let mut checkpoints = self.checkpoints.lock().unwrap();
+        let mut position = self.position.lock().unwrap();
+        while let Some((frame, beat)) = checkpoints.front() {
+            if *frame > played_frames {
+                break;
+            }
+            *position = *beat;
+            checkpoints.pop_front();
+        }
+        *position
+    }
+
+    /// Number of frames heard, which the frames sent to the audio output are never behind.
+    fn played_frames(&self) -> usize {
+        let sent_frames = self.samples_sent.load(Ordering::Acquire) / self.channels;
+        let frames_played = self.frames_played.load(Ordering::Acquire);
+        let latency_frames = self.latency_frames.load(Ordering::Acquire);
+        frames_played
+            .saturating_sub(latency_frames)
+            .min(sent_frames)
+    }
+
+    /// Whether every frame sent to the audio output was heard.
+    fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+            && self.played_frames() >= self.samples_sent.load(Ordering::Acquire) / self.channels
+    }
+}
+
+fn emit_state(app: &AppHandle, state: TransportState, beat: Beats) {
+    app.emit("transport_state", TransportStateChanged { state, beat })
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mono transport which was sent `frames` frames, mixed at 256 frames per beat.
+    fn transport(frames: usize) -> Transport {
+        let transport = Transport::new(0.0, 1);
+        for frame in 0..frames {
+            transport.on_sample(frame as Beats / CHECKPOINT_FRAMES as Beats);
+        }
+        transport
+    }
+
+    #[test]
+    fn playhead_follows_the_played_frames() {
+        let transport = transport(1024);
+        assert_eq!(transport.update_position(), 0.0);
+        transport.on_played(300, 0);
+        assert_eq!(transport.update_position(), 1.0);
+        transport.on_played(300, 0);
+        assert_eq!(transport.update_position(), 2.0);
+    }
+
+    #[test]
+    fn output_latency_holds_the_playhead_back() {
+        let transport = transport(1024);
+        transport.on_played(600, 400);
+        assert_eq!(transport.update_position(), 0.0);
+        transport.on_played(100, 400);
+        assert_eq!(transport.update_position(), 1.0);
+    }
+
+    #[test]
+    fn playhead_never_passes_the_sent_frames() {
+        let transport = transport(300);
+        transport.on_played(2048, 0);
+        assert_eq!(transport.played_frames(), 300);
+        assert_eq!(transport.update_position(), 1.0);
+    }
+
+    #[test]
+    fn playback_finishes_once_the_last_frame_is_heard() {
+        let transport = transport(512);
+        transport.on_played(512, 128);
+        transport.finish();
+        assert!(!transport.is_finished());
+
+        // The audio output keeps counting the silence played after the end
+        transport.on_played(128, 128);
+        assert!(transport.is_finished());
+    }
+}
@@ -0,0 +1,345 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use knodiq_engine::{AudioSource, Sample};
+
+/// Frames summarized by each block of the finest level.
+pub const BASE_BLOCK_FRAMES: usize = 256;
+/// Number of blocks of a level merged into each block of the next level.
+pub const LEVEL_FACTOR: usize = 4;
+
+/// Identifies the files written by `WaveformMipmap::to_bytes`.
+const MAGIC: &[u8; 4] = b"KNWF";
+const FORMAT_VERSION: u32 = 1;
+
+/// Summary of a run of samples.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Peak {
+    pub min: Sample,
+    pub max: Sample,
+    pub rms: Sample,
+}
+
+impl Peak {
+    pub const SILENT: Peak = Peak {
+        min: 0.0,
+        max: 0.0,
+        rms: 0.0,
+    };
+
+    fn from_samples(samples: &[Sample]) -> Self {
+        if samples.is_empty() {
+            return Peak::SILENT;
+        }
+        let (min, max) = samples
+            .iter()
+            .fold((Sample::MAX, Sample::MIN), |(min, max), sample| {
+                (min.min(*sample), max.max(*sample))
+            });
+        let squares = samples.iter().map(|sample| sample * sample).sum::<Sample>();
+        Peak {
+            min,
+            max,
+            rms: (squares / samples.len() as Sample).sqrt(),
+        }
+    }
+
+    /// Merge the peaks, each summarizing the number of frames it's paired with.
+    pub fn merge(peaks: impl IntoIterator<Item = (Peak, usize)>) -> Self {
+        let mut merged = Peak {
+            min: Sample::MAX,
+            max: Sample::MIN,
+            rms: 0.0,
+        };
+        let mut squares = 0.0;
+        let mut frames = 0;
+        for (peak, peak_frames) in peaks {
+            merged.min = merged.min.min(peak.min);
+            merged.max = merged.max.max(peak.max);
+            squares += peak.rms * peak.rms * peak_frames as Sample;
+            frames += peak_frames;
+        }
+        if frames == 0 {
+            return Peak::SILENT;
+        }
+        merged.rms = (squares / frames as Sample).sqrt();
+        merged
+    }
+}
+
+/// Peaks of an audio source at multiple resolutions, so that its waveform can be drawn
+/// at any zoom without reading every sample.
+pub struct WaveformMipmap {
+    /// Sample rate of the summarized audio.
+    pub sample_rate: usize,
+    /// Blocks of each level, per channel. Each level is `LEVEL_FACTOR` times coarser
+    /// than the previous one, starting from `BASE_BLOCK_FRAMES`.
+    pub levels: Vec<Vec<Vec<Peak>>>,
+}
+
+impl WaveformMipmap {
+    /// Summarize every channel of the audio.
+    pub fn from_source(source: &AudioSource) -> Self {
+        let mut levels = vec![
+            source
+                .data
+                .iter()
+                .map(|channel| {
+                    channel
+                        .chunks(BASE_BLOCK_FRAMES)
+                        .map(Peak::from_samples)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+        ];
+        while let Some(level) = levels.last() {
+            if level.iter().all(|blocks| blocks.len() <= 1) {
+                break;
+            }
+            let next = level
+                .iter()
+                .map(|blocks| {
+                    blocks
+                        .chunks(LEVEL_FACTOR)
+                        .map(|chunk| Peak::merge(chunk.iter().map(|peak| (*peak, 1))))
+                        .collect()
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        WaveformMipmap {
+            sample_rate: source.sample_rate,
+            levels,
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    /// Frames summarized by each block of the level.
+    pub fn block_frames(level: usize) -> usize {
+        BASE_BLOCK_FRAMES * LEVEL_FACTOR.pow(level as u32)
+    }
+
+    /// Summary of the frames of the channel between `from` and `to`, precise to a block
+    /// of the finest level. The largest blocks fitting in the range are merged.
+    pub fn peak(&self, channel: usize, from: usize, to: usize) -> Peak {
+        let mut peaks = Vec::new();
+        let mut position = from / BASE_BLOCK_FRAMES * BASE_BLOCK_FRAMES;
+        while position < to.max(from + 1) {
+            let mut level = 0;
+            while level + 1 < self.levels.len()
+                && position % Self::block_frames(level + 1) == 0
+                && position + Self::block_frames(level + 1) <= to
+            {
+                level += 1;
+            }
+            let block_frames = Self::block_frames(level);
+            let block = self.levels[level]
+                .get(channel)
+                .and_then(|blocks| blocks.get(position / block_frames));
+            match block {
+                Some(peak) => peaks.push((*peak, block_frames)),
+                None => break,
+            }
+            position += block_frames;
+        }
+        Peak::merge(peaks)
+    }
+
+    /// Encode the mipmap to be cached on disk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.sample_rate as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.channels() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.levels.len() as u64).to_le_bytes());
+        for level in &self.levels {
+            let blocks = level.first().map_or(0, Vec::len);
+            bytes.extend_from_slice(&(blocks as u64).to_le_bytes());
+            for channel in level {
+                for peak in channel {
+                    bytes.extend_from_slice(&peak.min.to_le_bytes());
+                    bytes.extend_from_slice(&peak.max.to_le_bytes());
+                    bytes.extend_from_slice(&peak.rms.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decode a mipmap written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err("Not a waveform file.".to_string());
+        }
+        let version = reader.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported waveform file version {}.", version));
+        }
+        let sample_rate = reader.u64()? as usize;
+        let channels = reader.u64()? as usize;
+        let level_count = reader.u64()? as usize;
+
+        let mut levels = Vec::new();
+        for _ in 0..level_count {
+            let blocks = reader.u64()? as usize;
+            let mut level = Vec::new();
+            for _ in 0..channels {
+                let mut channel = Vec::new();
+                for _ in 0..blocks {
+                    channel.push(Peak {
+                        min: reader.f32()?,
+                        max: reader.f32()?,
+                        rms: reader.f32()?,
+                    });
+                }
+                level.push(channel);
+            }
+            levels.push(level);
+        }
+        Ok(WaveformMipmap {
+            sample_rate,
+            levels,
+        })
+    }
+}
+
+/// Reads the little-endian values of a waveform file in order.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl ByteReader<'_> {
+    fn take(&mut self, length: usize) -> Result<&[u8], String> {
+        let end = self.position + length;
+        let bytes = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| "The waveform file is truncated.".to_string())?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two channels of a slow sine, the second one at half the level.
+    fn source(frames: usize) -> AudioSource {
+        let mut source = AudioSource::new(48000, 2);
+        let channel = (0..frames)
+            .map(|frame| (frame as Sample * 0.001).sin())
+            .collect::<Vec<_>>();
+        source.data = vec![
+            channel.clone(),
+            channel.iter().map(|sample| sample * 0.5).collect(),
+        ];
+        source
+    }
+
+    fn assert_close(actual: Sample, expected: Sample) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn levels_get_coarser_until_one_block() {
+        let mipmap = WaveformMipmap::from_source(&source(BASE_BLOCK_FRAMES * 20));
+        let blocks = mipmap
+            .levels
+            .iter()
+            .map(|level| level[0].len())
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![20, 5, 2, 1]);
+        assert_eq!(mipmap.channels(), 2);
+    }
+
+    #[test]
+    fn peak_matches_the_samples() {
+        let source = source(BASE_BLOCK_FRAMES * 37);
+        let mipmap = WaveformMipmap::from_source(&source);
+        let ranges = [
+            (0, BASE_BLOCK_FRAMES * 37),
+            (BASE_BLOCK_FRAMES * 3, BASE_BLOCK_FRAMES * 29),
+            (BASE_BLOCK_FRAMES * 16, BASE_BLOCK_FRAMES * 32),
+        ];
+        for (channel, samples) in source.data.iter().enumerate() {
+            for (from, to) in ranges {
+                let expected = Peak::from_samples(&samples[from..to]);
+                let peak = mipmap.peak(channel, from, to);
+                assert_close(peak.min, expected.min);
+                assert_close(peak.max, expected.max);
+                assert_close(peak.rms, expected.rms);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_weighs_the_peaks_by_their_frames() {
+        let loud = Peak {
+            min: -1.0,
+            max: 1.0,
+            rms: 1.0,
+        };
+        let quiet = Peak {
+            min: -0.5,
+            max: 0.25,
+            rms: 0.0,
+        };
+        let merged = Peak::merge([(loud, 1), (quiet, 3)]);
+        assert_eq!((merged.min, merged.max), (-1.0, 1.0));
+        assert_close(merged.rms, 0.5);
+        assert_eq!(Peak::merge([]), Peak::SILENT);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mipmap = WaveformMipmap::from_source(&source(BASE_BLOCK_FRAMES * 9 + 100));
+        let decoded = WaveformMipmap::from_bytes(&mipmap.to_bytes()).unwrap();
+        assert_eq!(decoded.sample_rate, mipmap.sample_rate);
+        assert_eq!(decoded.levels, mipmap.levels);
+    }
+
+    #[test]
+    fn invalid_bytes_are_rejected() {
+        let bytes = WaveformMipmap::from_source(&source(BASE_BLOCK_FRAMES * 4)).to_bytes();
+        assert!(WaveformMipmap::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(WaveformMipmap::from_bytes(b"RIFF0000").is_err());
+    }
+}
@@ -0,0 +1,23 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+pub mod mipmap;
+pub mod waveform;
+pub mod waveform_thread;
+
+pub use mipmap::{Peak, WaveformMipmap};
+pub use waveform::{Waveform, region_waveform};
+pub use waveform_thread::{WaveformJob, WaveformStore, start_waveform_thread, waveform_cache_dir};
@@ -0,0 +1,156 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::AppState;
+use crate::api::mixing::MixerContext;
+use crate::api::mixing::history::edit::region_position;
+use crate::api::mixing::{MixerCommand, MixerResult, request_mixer_result};
+use crate::api::waveform::Peak;
+use knodiq_engine::{Beats, Sample};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{State, command};
+
+/// Most pixels a waveform can be requested for at once.
+pub const MAX_WAVEFORM_PIXELS: usize = 16384;
+
+/// Waveform of a part of a buffer region, summarized for each pixel.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Waveform {
+    /// Lowest and highest sample of each pixel, per channel.
+    pub peaks: Vec<Vec<(Sample, Sample)>>,
+    /// RMS level of each pixel, per channel.
+    pub rms: Vec<Vec<Sample>>,
+}
+
+/// Get the waveform of the region between the beats relative to its start, summarized into
+/// the pixels. `None` while the audio is still being summarized,
+/// in which case a `waveform_ready` event follows once it is.
+#[command]
+pub fn get_waveform(
+    track_id: u32,
+    region_id: u32,
+    start: Beats,
+    end: Beats,
+    pixels: usize,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<Waveform>, String> {
+    if pixels == 0 || pixels > MAX_WAVEFORM_PIXELS {
+        return Err(format!(
+            "The waveform must be between 1 and {} pixels wide.",
+            MAX_WAVEFORM_PIXELS
+        ));
+    }
+    if !start.is_finite() || !end.is_finite() || start >= end {
+        return Err("The start of the waveform must be before its end.".to_string());
+    }
+
+    let command = MixerCommand::GetWaveform(track_id, region_id, start, end, pixels);
+    match request_mixer_result(command, &state)? {
+        MixerResult::Waveform(result) => result,
+        _ => Err("Unexpected result type received.".to_string()),
+    }
+}
+
+/// Read the waveform of the region from the summary of its audio,
+/// following its offset, stretch and loop.
+pub fn region_waveform(
+    context: &MixerContext,
+    track_id: u32,
+    region_id: u32,
+    start: Beats,
+    end: Beats,
+    pixels: usize,
+) -> Result<Option<Waveform>, String> {
+    let (region_start, _) = region_position(context, track_id, region_id).ok_or_else(|| {
+        format!(
+            "Region with ID {} not found in track {}.",
+            region_id, track_id
+        )
+    })?;
+    let source = context
+        .region_sources
+        .get(&(track_id, region_id))
+        .ok_or_else(|| format!("Region with ID {} has no audio.", region_id))?;
+    let Some(mipmap) = context
+        .waveforms
+        .lock()
+        .unwrap()
+        .get(&source.path, source.track_index)
+    else {
+        return Ok(None);
+    };
+
+    let settings = context
+        .region_settings
+        .get(&(track_id, region_id))
+        .copied()
+        .unwrap_or_default();
+    let tempo = context.mixer.tempo;
+    let ratio = source.stretch_ratio(tempo, settings.stretch);
+    let start_seconds = context.tempo_map.beat_to_seconds(tempo, region_start);
+    // Frame of the audio played at the beat relative to the start of the region
+    let frame_at = |beat: Beats| {
+        let seconds = context
+            .tempo_map
+            .beat_to_seconds(tempo, region_start + beat)
+            - start_seconds;
+        ((source.offset + seconds / ratio) * mipmap.sample_rate as f64).max(0.0) as usize
+    };
+
+    let channels = mipmap.channels();
+    let mut waveform = Waveform {
+        peaks: vec![Vec::with_capacity(pixels); channels],
+        rms: vec![Vec::with_capacity(pixels); channels],
+    };
+    let beats_per_pixel = (end - start) / pixels as Beats;
+    for pixel in 0..pixels {
+        let from = start + pixel as Beats * beats_per_pixel;
+        let to = from + beats_per_pixel;
+        let mut ranges = vec![(from, to)];
+        // A looping region plays the content of its first repeat again
+        if let Some(loop_length) = settings.loop_length {
+            let repeat_start = (from / loop_length).floor() * loop_length;
+            let (from, to) = (from - repeat_start, to - repeat_start);
+            ranges = if to <= loop_length {
+                vec![(from, to)]
+            } else if to - loop_length >= from {
+                // The pixel covers a whole repeat
+                vec![(0.0, loop_length)]
+            } else {
+                // The end of the pixel wraps around to the start of the next repeat
+                vec![(from, loop_length), (0.0, to - loop_length)]
+            };
+        }
+
+        let frames = ranges
+            .iter()
+            .map(|(from, to)| (frame_at(*from), frame_at(*to)))
+            .collect::<Vec<_>>();
+        let channels = waveform.peaks.iter_mut().zip(waveform.rms.iter_mut());
+        for (channel, (peaks, rms)) in channels.enumerate() {
+            let peak = Peak::merge(frames.iter().map(|(from_frame, to_frame)| {
+                (
+                    mipmap.peak(channel, *from_frame, *to_frame),
+                    to_frame.saturating_sub(*from_frame).max(1),
+                )
+            }));
+            peaks.push((peak.min, peak.max));
+            rms.push(peak.rms);
+        }
+    }
+    Ok(Some(waveform))
+}
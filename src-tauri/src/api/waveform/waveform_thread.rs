@@ -0,0 +1,194 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::api::waveform::mipmap::WaveformMipmap;
+use knodiq_engine::AudioSource;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Emitter};
+
+/// Name of the directory next to the project file where the summaries are cached.
+const CACHE_DIRECTORY: &str = ".waveforms";
+
+/// Payload of the `waveform_ready` event, emitted when an audio file has been summarized.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WaveformReady {
+    pub path: String,
+    pub track_index: usize,
+}
+
+/// Audio file to be summarized by the waveform thread.
+pub struct WaveformJob {
+    pub path: String,
+    pub track_index: usize,
+    /// Decoded audio of the file.
+    pub source: AudioSource,
+    /// Directory to read the summary from, or write it to.
+    pub cache_dir: PathBuf,
+}
+
+/// Summaries of the audio files, shared between the mixer and the waveform thread.
+#[derive(Default)]
+pub struct WaveformStore {
+    /// Summaries keyed by path and track index.
+    mipmaps: HashMap<(String, usize), Arc<WaveformMipmap>>,
+    /// Files waiting to be summarized.
+    pending: HashSet<(String, usize)>,
+}
+
+impl WaveformStore {
+    pub fn new() -> Self {
+        WaveformStore::default()
+    }
+
+    pub fn get(&self, path: &str, track_index: usize) -> Option<Arc<WaveformMipmap>> {
+        self.mipmaps.get(&(path.to_string(), track_index)).cloned()
+    }
+
+    /// Mark the file as waiting to be summarized.
+    /// Returns `false` if it already is, or has been summarized.
+    pub fn request(&mut self, path: &str, track_index: usize) -> bool {
+        let key = (path.to_string(), track_index);
+        !self.mipmaps.contains_key(&key) && self.pending.insert(key)
+    }
+
+    fn finish(&mut self, path: String, track_index: usize, mipmap: WaveformMipmap) {
+        let key = (path, track_index);
+        self.pending.remove(&key);
+        self.mipmaps.insert(key, Arc::new(mipmap));
+    }
+
+    /// Write the summaries of the decoded audio files to the cache directory, unless they're
+    /// already there, so that the ones summarized before the project was saved are kept next to it.
+    pub fn save(&self, sources: &HashMap<(String, usize), AudioSource>, cache_dir: &Path) {
+        for ((path, track_index), source) in sources {
+            let Some(mipmap) = self.mipmaps.get(&(path.clone(), *track_index)) else {
+                continue;
+            };
+            let Some(cache_path) =
+                cache_file_path(cache_dir, path, *track_index, source.sample_rate)
+            else {
+                continue;
+            };
+            if cache_path.exists() {
+                continue;
+            }
+            let written = fs::create_dir_all(cache_dir)
+                .and_then(|_| fs::write(&cache_path, mipmap.to_bytes()));
+            if let Err(e) = written {
+                eprintln!("Error caching waveform to {}: {}", cache_path.display(), e);
+            }
+        }
+    }
+}
+
+/// Directory the summaries are cached in: next to the project file once it's saved,
+/// and in the temporary directory before that.
+pub fn waveform_cache_dir(project_path: Option<&Path>) -> PathBuf {
+    match project_path.and_then(Path::parent) {
+        Some(directory) => directory.join(CACHE_DIRECTORY),
+        None => std::env::temp_dir().join("knodiq").join(CACHE_DIRECTORY),
+    }
+}
+
+/// Start the thread summarizing the audio files sent to it,
+/// so that long files don't keep the mixer busy.
+pub fn start_waveform_thread(
+    job_receiver: Receiver<WaveformJob>,
+    store: Arc<Mutex<WaveformStore>>,
+    app: AppHandle,
+) -> Result<(), std::io::Error> {
+    thread::Builder::new()
+        .name("waveform_thread".into())
+        .spawn(move || {
+            while let Ok(job) = job_receiver.recv() {
+                let mipmap = summarize(&job);
+                store
+                    .lock()
+                    .unwrap()
+                    .finish(job.path.clone(), job.track_index, mipmap);
+                let payload = WaveformReady {
+                    path: job.path,
+                    track_index: job.track_index,
+                };
+                app.emit("waveform_ready", payload).ok();
+            }
+        })
+        .map(|_| ())
+}
+
+/// Read the summary of the file from the cache, or summarize it and cache it.
+fn summarize(job: &WaveformJob) -> WaveformMipmap {
+    let cache_path = cache_file_path(
+        &job.cache_dir,
+        &job.path,
+        job.track_index,
+        job.source.sample_rate,
+    );
+    if let Some(cache_path) = &cache_path {
+        if let Ok(bytes) = fs::read(cache_path) {
+            match WaveformMipmap::from_bytes(&bytes) {
+                Ok(mipmap) => return mipmap,
+                Err(e) => eprintln!("Error reading {}: {}", cache_path.display(), e),
+            }
+        }
+    }
+
+    let mipmap = WaveformMipmap::from_source(&job.source);
+    if let Some(cache_path) = &cache_path {
+        let written = fs::create_dir_all(&job.cache_dir)
+            .and_then(|_| fs::write(cache_path, mipmap.to_bytes()));
+        if let Err(e) = written {
+            eprintln!("Error caching waveform to {}: {}", cache_path.display(), e);
+        }
+    }
+    mipmap
+}
+
+/// Path of the cached summary of the file, named after the file and when it was modified
+/// so that editing the file doesn't show the old waveform.
+/// `None` if the file can't be read, as there would be nothing to name it after.
+fn cache_file_path(
+    cache_dir: &Path,
+    path: &str,
+    track_index: usize,
+    sample_rate: usize,
+) -> Option<PathBuf> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    // The name has to stay the same across runs and builds, so it's hashed by hand
+    let mut bytes = path.as_bytes().to_vec();
+    bytes.extend((track_index as u64).to_le_bytes());
+    bytes.extend(metadata.len().to_le_bytes());
+    bytes.extend(modified.as_secs().to_le_bytes());
+    bytes.extend(modified.subsec_nanos().to_le_bytes());
+    bytes.extend((sample_rate as u64).to_le_bytes());
+    Some(cache_dir.join(format!("{:016x}.peaks", fnv1a(&bytes))))
+}
+
+/// 64-bit FNV-1a hash of the bytes.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
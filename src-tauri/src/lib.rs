@@ -18,9 +18,9 @@ mod api;
 
 use api::AppState;
 use api::graph;
-use api::mixing::{region, track};
+use api::mixing::{history, region, settings, tempo, track};
 use api::window;
-use api::{playback, setup};
+use api::{export, playback, project, setup, waveform};
 
 use std::sync::Mutex;
 use tauri_plugin_log;
@@ -38,8 +38,13 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             playback::pause_audio,
             playback::play_audio,
+            playback::stop_audio,
+            playback::set_loop_range,
+            playback::set_loop_enabled,
             graph::graph::connect_graph,
             graph::graph::disconnect_graph,
+            graph::graph::connect_sidechain,
+            graph::graph::disconnect_sidechain,
             graph::graph::add_node,
             graph::graph::remove_node,
             graph::graph::move_node,
@@ -49,17 +54,58 @@ pub fn run() {
             graph::node::audio_shader_node::set_audio_shader,
             track::track::add_track,
             track::track::remove_track,
+            track::track::rename_track,
+            track::track::move_track,
+            track::track::duplicate_track,
+            track::track::set_track_parent,
+            track::track::set_folder_collapsed,
             track::track::set_track_color,
+            track::track::set_track_volume,
+            track::track::set_track_pan,
+            track::track::set_track_mute,
+            track::track::set_track_solo,
+            track::track::set_track_solo_safe,
+            track::track::set_send,
+            track::track::remove_send,
+            track::track::set_send_automation,
             region::region::add_region,
             region::region::remove_region,
             region::region::move_region,
             region::region::set_duration,
+            region::region::split_region,
+            region::region::trim_region_start,
+            region::region::trim_region_end,
+            region::region::slip_region,
+            region::region::set_source_offset,
+            region::region::duplicate_region,
+            region::region::copy_regions,
+            region::region::paste_regions,
             region::region::set_region_name,
             region::region::scale_region,
+            region::region::set_fade_in,
+            region::region::set_fade_out,
+            region::region::set_region_gain,
+            region::region::set_region_invert,
+            region::region::set_pitch_shift,
+            region::region::set_loop_length,
             region::region::add_note_to_region,
             region::region::remove_note_from_region,
             region::region::modify_note_in_region,
             window::open_track_config_window,
+            project::project::save_project,
+            project::project::open_project,
+            history::history::undo,
+            history::history::redo,
+            export::export::export_mix,
+            export::export::export_stems,
+            export::export::cancel_export,
+            waveform::waveform::get_waveform,
+            tempo::tempo::set_tempo,
+            tempo::tempo::set_time_signature,
+            tempo::tempo::add_tempo_event,
+            tempo::tempo::move_tempo_event,
+            tempo::tempo::remove_tempo_event,
+            settings::settings::set_audio_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,34 @@
+//
+// Copyright 2025 Shuntaro Kasatani
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+/** Step an audio file being imported has reached. */
+export enum ImportStage {
+    Queued = "Queued",
+    Decoding = "Decoding",
+    Resampling = "Resampling",
+    Finished = "Finished",
+    Failed = "Failed",
+}
+
+/** Payload of the `import_progress` event. */
+export type ImportProgress = {
+    path: string;
+    track_index: number;
+    stage: ImportStage;
+    /** Why the import failed, with the `Failed` stage. */
+    error: string | null;
+}
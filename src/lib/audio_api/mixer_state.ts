@@ -15,10 +15,52 @@
 //
 
 import { TrackState } from './track_state';
+import { GraphState } from './graph_state';
+
+/** Track ID addressing the master bus in the graph commands. */
+export const MASTER_TRACK_ID = 4294967295;
 
 export type MixerState = {
-    tracks: TrackState[];
+    tracks: TrackState[]; // without the tracks in a collapsed folder
+    master_graph: GraphState; // addressed with MASTER_TRACK_ID
     bpm: number;
+    time_signature: TimeSignature;
+    audio_settings: AudioSettings;
+    tempo_events: TempoEvent[]; // sorted by beat
+    loop_range: LoopRange;
     samples_per_beat: number;
     duration: number; // in beats
+}
+
+export type TimeSignature = {
+    numerator: number;
+    denominator: number;
+}
+
+export enum ChannelLayout {
+    Mono = "Mono",
+    Stereo = "Stereo",
+}
+
+export type AudioSettings = {
+    sample_rate: number; // 44100, 48000, 88200 or 96000
+    channel_layout: ChannelLayout;
+}
+
+export enum TempoCurve {
+    Stepped = "Stepped",
+    Ramped = "Ramped",
+}
+
+export type TempoEvent = {
+    id: number;
+    beat: number;
+    tempo: number; // in BPM
+    curve: TempoCurve;
+}
+
+export type LoopRange = {
+    start: number; // in beats
+    end: number; // in beats
+    enabled: boolean;
 }
\ No newline at end of file
@@ -27,6 +27,44 @@ export type RegionState = {
     duration: number;
     /** The data of the region. */
     data: RegionDataState;
+    /** Fade at the start of the region set by the user. */
+    fade_in: Fade;
+    /** Fade at the end of the region set by the user. */
+    fade_out: Fade;
+    /** Length in beats of the automatic equal-power crossfade with an earlier overlapping region. */
+    crossfade_in: number;
+    /** Length in beats of the automatic equal-power crossfade with a later overlapping region. */
+    crossfade_out: number;
+    /** Clip gain of the region in dB. */
+    gain: number;
+    /** Whether the polarity of the audio is inverted. */
+    invert: boolean;
+    /** How much longer the audio plays than at its own speed, on top of following the tempo. */
+    stretch: number;
+    /** Pitch shift of the audio in semitones. */
+    pitch_shift: number;
+    /** Seconds into the audio where the region starts playing. */
+    source_offset: number;
+    /** Length in beats of the content repeated over the region, if it loops. */
+    loop_length: number | null;
+    /** Beats relative to the start of the region where its content starts over. */
+    repeats: number[];
+    /** Whether the audio file is still being decoded. */
+    loading: boolean;
+    /** Why the audio file couldn't be decoded. */
+    load_error: string | null;
+}
+
+export type Fade = {
+    /** Length of the fade in beats, no fade at 0. */
+    length: number;
+    curve: FadeCurve;
+}
+
+export enum FadeCurve {
+    Linear = "Linear",
+    EqualPower = "EqualPower",
+    Exponential = "Exponential",
 }
 
 export enum RegionType {
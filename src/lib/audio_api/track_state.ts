@@ -32,11 +32,66 @@ export type TrackState = {
     color: string;
     /** The graph structure of the track. */
     graph: GraphState;
+    /** Volume, pan, mute and solo of the track. */
+    mix: TrackMix;
+    /** Whether the track is heard, taking the solo of the other tracks into account. */
+    audible: boolean;
+    /** Sends of the track to the bus tracks. */
+    sends: TrackSend[];
+    /** Outputs of other tracks routed into the nodes of the track. */
+    sidechains: Sidechain[];
+    /** ID of the folder track the track is in. */
+    parent_id: number | null;
+    /** Whether the tracks in the folder are hidden. */
+    collapsed: boolean;
+}
+
+export type TrackMix = {
+    /** Volume of the track in dB. */
+    volume: number;
+    /** Position in the stereo field, from -1 (left) to 1 (right). */
+    pan: number;
+    mute: boolean;
+    solo: boolean;
+    /** Whether the track keeps playing when other tracks are soloed. */
+    solo_safe: boolean;
+}
+
+export type TrackSend = {
+    /** ID of the bus track receiving the audio. */
+    bus_id: number;
+    /** Level of the send in dB. */
+    level: number;
+    /** Whether the audio is taken before the volume and pan of the track. */
+    pre_fader: boolean;
+    /** Level of the send in dB over the timeline. Overrides `level` when it has points. */
+    automation: Automation;
+}
+
+export type Sidechain = {
+    /** ID of the track whose output is routed. */
+    source_id: number;
+    /** Node receiving the audio, in the graph of the track owning the sidechain. */
+    node_id: string;
+    /** Name of the input of the node. */
+    param: string;
+}
+
+export type Automation = {
+    /** Points sorted by beat. The value is interpolated linearly between them. */
+    points: AutomationPoint[];
+}
+
+export type AutomationPoint = {
+    beat: number;
+    value: number;
 }
 
 export enum TrackType {
     BufferTrack = "BufferTrack",
     NoteTrack = "NoteTrack",
+    BusTrack = "BusTrack",
+    FolderTrack = "FolderTrack",
 }
 
 export function getTrackTypeString(trackType: TrackType): string {
@@ -45,6 +100,10 @@ export function getTrackTypeString(trackType: TrackType): string {
             return "Buffer Track";
         case TrackType.NoteTrack:
             return "Note Track";
+        case TrackType.BusTrack:
+            return "Bus Track";
+        case TrackType.FolderTrack:
+            return "Folder Track";
         default:
             return "Unknown Track Type";
     }